@@ -91,14 +91,14 @@ fuzz_target!(|input: Input| -> Corpus {
             // precision. But we can say some things about the relation between
             // a, b, and a + b.
             if let Some(sum) = a_dec.checked_add(&b_dec) {
-                if a_dec.mantissa > 0 {
+                if a_dec.signum() > 0 {
                     assert_eq!(
                         sum.cmp(&b_dec),
                         Ordering::Greater,
                         "{a_dec:?} > 0 ==> {sum:?} > {b_dec:?}",
                     );
                 }
-                if b_dec.mantissa > 0 {
+                if b_dec.signum() > 0 {
                     assert_eq!(
                         sum.cmp(&a_dec),
                         Ordering::Greater,
@@ -116,7 +116,7 @@ fuzz_target!(|input: Input| -> Corpus {
             decimals,
         } => {
             let a = Decimal {
-                mantissa,
+                mantissa: mantissa.into(),
                 exponent,
                 decimals,
             };
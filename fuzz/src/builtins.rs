@@ -39,68 +39,68 @@ pub const BUILTIN_TYPES: [&str; 256] = [
 
 #[rustfmt::skip]
 pub const BUILTINS: [&str; 256] = [
-     "ends_with", "sort", "to_lowercase", "values",
-     "starts_with", "join", "std", "to_uppercase",
-     "parse_int", "ends_with", "enumerate", "key_by",
-     "contains", "empty_set", "to_uppercase", "to_set_dedup",
-     "group_by", "to_set_dedup", "reverse", "to_lowercase",
-     "group_by", "remove_suffix", "ends_with", "all",
-     "starts_with", "empty_set", "get", "replace",
-     "all", "except", "except", "split",
-     "parse_int", "filter", "round", "key_by",
-     "sum", "group_by", "keys", "enumerate",
-     "contains", "parse_number", "parse_int", "read_file_utf8",
-     "map", "round", "read_file_utf8", "any",
-     "range", "replace", "group_by", "ends_with",
-     "len", "sort", "remove_suffix", "to_set_dedup",
-     "starts_with", "keys", "parse_number", "get",
-     "round", "parse_int", "flat_map", "to_uppercase",
-     "range", "read_file_utf8", "std", "to_list",
-     "parse_number", "sort", "split_lines", "get",
-     "remove_prefix", "to_list", "sort_by", "to_lowercase",
-     "to_list", "key_by", "fold", "to_uppercase",
-     "remove_prefix", "all", "format_json", "fold",
-     "flat_map_dedup", "flat_map_dedup", "ends_with", "std",
-     "range", "split_lines", "remove_prefix", "starts_with",
-     "len", "std", "split_lines", "to_set_unique",
-     "split", "reverse", "get", "split_lines",
-     "flat_map", "flat_map", "split", "key_by",
-     "fold", "flat_map", "sum", "std",
-     "to_set_dedup", "range", "len", "read_file_utf8",
-     "parse_int", "round", "get", "flat_map",
-     "sort", "round", "remove_prefix", "join",
-     "flat_map_dedup", "sum", "starts_with", "format_json",
-     "sum", "keys", "to_list", "keys",
-     "format_json", "any", "remove_suffix", "all",
-     "key_by", "std", "key_by", "sort_by",
-     "empty_set", "map", "to_lowercase", "except",
-     "starts_with", "filter", "to_set_dedup", "sum",
-     "any", "map_dedup", "fold", "to_uppercase",
-     "values", "filter", "map_dedup", "split",
-     "any", "flat_map", "values", "replace",
-     "to_lowercase", "ends_with", "read_file_utf8", "flat_map",
-     "to_uppercase", "starts_with", "map_dedup", "format_json",
-     "map_dedup", "ends_with", "map_dedup", "chars",
-     "chars", "range", "get", "get",
-     "sort_by", "parse_number", "starts_with", "join",
-     "round", "remove_suffix", "enumerate", "filter",
-     "remove_suffix", "map_dedup", "join", "replace",
-     "flat_map", "sort_by", "split_lines", "fold",
-     "sort", "sum", "replace", "reverse",
-     "to_set_unique", "flat_map_dedup", "map_dedup", "get",
-     "to_uppercase", "any", "len", "range",
-     "starts_with", "join", "parse_number", "read_file_utf8",
-     "sum", "reverse", "ends_with", "empty_set",
-     "all", "len", "all", "parse_int",
-     "sort", "format_json", "map", "flat_map_dedup",
-     "starts_with", "range", "parse_number", "fold",
-     "all", "range", "join", "sort_by",
-     "transitive_closure", "split_lines", "remove_suffix", "sum",
-     "starts_with", "contains", "group_by", "filter",
-     "parse_number", "parse_number", "key_by", "values",
-     "sum", "map_dedup", "map", "to_uppercase",
-     "sum", "split_lines", "transitive_closure", "std",
-     "except", "any", "to_set_unique", "parse_int",
-     "any", "values", "group_by", "sum",
-     "filter", "transitive_closure", "all", "sort",
+     "to_title_case", "sort", "partition", "values",
+     "repeat", "average", "hex_encode", "to_uppercase",
+     "floor", "ends_with", "enumerate", "pow",
+     "is_subset_of", "empty_set", "min", "skip",
+     "map_keys", "sha1", "pad_start", "to_lowercase",
+     "replace_regex", "dedup", "cidr_subnet", "all",
+     "last_index_of", "max_by", "find", "replace",
+     "map_keys", "except", "except", "sqrt",
+     "is_superset_of", "md5", "index_of", "base64_decode",
+     "dedup", "group_by", "windows", "enumerate",
+     "contains", "parse_number", "mod", "read_file_utf8",
+     "count", "round", "pow", "any",
+     "flatten", "split_at", "hex_encode", "ends_with",
+     "merge_deep_with", "sort", "is_subset_of", "matches",
+     "count", "is_disjoint", "trim_start", "hex_encode",
+     "pow", "zip", "flat_map", "to_uppercase",
+     "sqrt", "cidr_host", "average", "captures",
+     "mod", "sort", "take_while", "get",
+     "remove_prefix", "cidr_subnet", "windows", "to_lowercase",
+     "find_all", "sha1", "pad_end", "merge_with",
+     "zip", "all", "div", "find_all",
+     "flat_map_dedup", "min", "merge_deep_with", "std",
+     "only", "div", "hex_decode", "starts_with",
+     "first", "std", "rem", "split_at",
+     "split", "div", "get", "url_encode",
+     "flat_map", "flat_map", "captures", "flatten",
+     "index_of", "replace_regex", "sum", "repeat",
+     "to_set_dedup", "range", "len", "bytes",
+     "hex_encode", "round", "parse_ip", "flat_map",
+     "merge_deep_with", "round", "sqrt", "sha1",
+     "is_superset_of", "sum", "starts_with", "format_json",
+     "mod", "keys", "average", "clamp",
+     "format_json", "any", "to_snake_case", "chunks",
+     "key_by", "std", "trim", "windows",
+     "max", "symmetric_difference", "to_lowercase", "unique_by",
+     "to_camel_case", "filter", "intersect", "split_at",
+     "any", "pow", "slice", "to_uppercase",
+     "values", "matches", "map_dedup", "split",
+     "any", "flat_map", "chunks", "min",
+     "div", "div", "hex_decode", "abs",
+     "symmetric_difference", "to_title_case", "mod", "slice",
+     "merge_deep_with", "invert", "parse_ip", "is_subset_of",
+     "chars", "take", "get", "intersect",
+     "cidr_contains", "ceil", "clamp", "map_keys",
+     "chunks", "find_all", "log2", "filter",
+     "remove_suffix", "unique_by", "first", "chunks",
+     "floor", "sort_by", "last_index_of", "fold",
+     "to_kebab_case", "find_index", "format", "reverse",
+     "to_set_unique", "merge_deep_with", "chunks", "index_of",
+     "is_subset_of", "url_decode", "count", "sqrt",
+     "starts_with", "dedup", "parse_number", "invert",
+     "sum", "map_values", "bytes", "symmetric_difference",
+     "map_values", "cidr_subnet", "merge_deep", "parse_int",
+     "is_superset_of", "format_json", "map", "trim_end",
+     "starts_with", "base64_encode", "is_superset_of", "windows",
+     "only", "merge_deep_with", "join", "sort_by",
+     "hex_encode", "min_by", "url_encode", "skip",
+     "map_keys", "dedup", "dict_from_pairs", "filter",
+     "hex_decode", "last_index_of", "base64_encode", "slice",
+     "sum", "map_dedup", "take_while", "parse_csv",
+     "bytes", "split_lines", "to_list", "last",
+     "except", "any", "chunks", "take_while",
+     "repeat", "captures", "sha256", "intersect",
+     "to_title_case", "transitive_closure", "partition", "pad_end",
 ];
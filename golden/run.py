@@ -41,10 +41,12 @@ OPTIONS
 import difflib
 import json
 import os
+import plistlib
 import re
 import subprocess
 import sys
 import tomllib
+import xml.dom.minidom
 
 from typing import List, Optional
 
@@ -86,10 +88,20 @@ def test_one(fname: str, fname_friendly: str, *, rewrite_output: bool) -> Option
 
     # Decide which subcommand to test based on the test directory.
     cmd_suffix = ["-"]
+
+    # For binary output formats, we compare a hex dump of stdout.
+    is_binary = False
     match os.path.basename(os.path.dirname(fname)):
+        case "bash":
+            cmd = ["eval", "--format=bash"]
+
         case "build":
             cmd = ["build", "--dry-run"]
 
+        case "cbor":
+            cmd = ["eval", "--format=cbor"]
+            is_binary = True
+
         case "cmd":
             # For files in the `cmd` directory, the exact RCL arguments are
             # configurable as part of the test.
@@ -99,6 +111,12 @@ def test_one(fname: str, fname_friendly: str, *, rewrite_output: bool) -> Option
             cmd = json.loads(header.removeprefix("# command: "))
             cmd_suffix = []
 
+        case "csv":
+            cmd = ["eval", "--format=csv"]
+
+        case "dotenv":
+            cmd = ["eval", "--format=dotenv"]
+
         case "error" | "types":
             cmd = ["eval"]
 
@@ -111,21 +129,55 @@ def test_one(fname: str, fname_friendly: str, *, rewrite_output: bool) -> Option
         case "fmt":
             cmd = ["fmt"]
 
+        case "hcl":
+            cmd = ["eval", "--format=hcl"]
+
+        case "ini":
+            cmd = ["eval", "--format=ini"]
+
         case "json":
             cmd = ["eval", "--format=json"]
 
+        case "json_canonical":
+            cmd = ["eval", "--format=json-canonical"]
+
+        case "json_compact":
+            cmd = ["eval", "--format=json-compact"]
+
         case "json_lines":
             cmd = ["eval", "--format=json-lines"]
 
         case "html":
             cmd = ["format", "--color=html"]
 
+        case "msgpack":
+            cmd = ["eval", "--format=msgpack"]
+            is_binary = True
+
+        case "nix":
+            cmd = ["eval", "--format=nix"]
+
+        case "plist":
+            cmd = ["eval", "--format=plist"]
+            # Like for TOML, confirm that Python can parse the expected output.
+            if not os.path.basename(fname).startswith("error_"):
+                try:
+                    plistlib.loads("".join(golden_lines).encode("utf-8"))
+                except Exception as err:
+                    raise Exception(f"Invalid plist in {fname}") from err
+
+        case "properties":
+            cmd = ["eval", "--format=properties"]
+
         case "raw":
             cmd = ["eval", "--format=raw"]
 
         case "rcl":
             cmd = ["eval", "--format=rcl"]
 
+        case "sh":
+            cmd = ["eval", "--format=sh"]
+
         case "toml":
             cmd = ["eval", "--format=toml"]
             # For TOML, when the test case is not an error, we additionally test
@@ -137,6 +189,17 @@ def test_one(fname: str, fname_friendly: str, *, rewrite_output: bool) -> Option
                 except Exception as err:
                     raise Exception(f"Invalid TOML in {fname}") from err
 
+        case "tsv":
+            cmd = ["eval", "--format=tsv"]
+
+        case "xml":
+            cmd = ["eval", "--format=xml"]
+            if not os.path.basename(fname).startswith("error_"):
+                try:
+                    xml.dom.minidom.parseString("".join(golden_lines))
+                except Exception as err:
+                    raise Exception(f"Invalid XML in {fname}") from err
+
         case "yaml_stream":
             cmd = ["eval", "--format=yaml-stream"]
 
@@ -145,16 +208,22 @@ def test_one(fname: str, fname_friendly: str, *, rewrite_output: bool) -> Option
 
     result = subprocess.run(
         [rcl_bin, "-C", os.path.dirname(fname), *cmd, *cmd_suffix],
-        input="".join(input_lines),
+        input="".join(input_lines).encode("utf-8"),
         capture_output=True,
-        encoding="utf-8",
     )
+    if is_binary:
+        # Print 16 bytes per line, as space-separated hex pairs.
+        stdout_lines = [
+            result.stdout[i : i + 16].hex(" ") for i in range(0, len(result.stdout), 16)
+        ]
+    else:
+        stdout_lines = result.stdout.decode("utf-8").splitlines()
     common_root = os.path.dirname(__file__)
     output_lines = [
         # Strip ANSI escape codes from the output. Also replace references to
         # absolute paths with a known path to make the test results portable.
         STRIP_ESCAPES.sub("", line).replace(common_root, "/WORKDIR")
-        for line in result.stdout.splitlines() + result.stderr.splitlines()
+        for line in stdout_lines + result.stderr.decode("utf-8").splitlines()
     ]
 
     report_lines: List[str] = []
@@ -48,7 +48,7 @@ syn region  rclFormatTriple  start='f"""' end='"""' skip='\\"\|\\{' contains=rcl
 
 " See also https://vi.stackexchange.com/questions/5966/ for why the `contains`
 " needs to end in `[]`.
-syn keyword rclBuiltin all any chars contains[] ends_with enumerate except filter flat_map flat_map_dedup fold get group_by join key_by keys len map map_dedup parse_int parse_number remove_prefix remove_suffix replace reverse round sort sort_by split split_lines starts_with sum to_list to_lowercase to_set_dedup to_set_unique to_uppercase transitive_closure values empty_set format_json range read_file_utf8 std
+syn keyword rclBuiltin abs all any average bytes captures ceil chars chunks clamp contains[] count dedup div ends_with enumerate except filter find find_all find_index first flat_map flat_map_dedup flatten floor fold get group_by index_of intersect invert is_disjoint is_subset_of is_superset_of join key_by keys last last_index_of len log2 map map_dedup map_keys map_values matches max max_by merge_deep merge_deep_with merge_with min min_by mod only pad_end pad_start parse_int parse_number partition pow rem remove_prefix remove_suffix repeat replace replace_regex reverse round skip slice sort sort_by split split_at split_lines sqrt starts_with sum symmetric_difference take take_while to_camel_case to_kebab_case to_list to_lowercase to_set_dedup to_set_unique to_snake_case to_title_case to_uppercase transitive_closure trim trim_end trim_start unique_by values windows zip base64_decode base64_encode cidr_contains cidr_host cidr_subnet dict_from_pairs empty_set format format_json hex_decode hex_encode md5 parse_csv parse_ip range read_file_utf8 sha1 sha256 url_decode url_encode std
 syn match   rclType    '\<\(Any\|Bool\|Dict\|List\|Null\|Number\|Set\|String\|Union\|Void\)\>'
 
 syn cluster rclString contains=rclStringDouble,rclStringTriple,rclFormatDouble,rclFormatTriple
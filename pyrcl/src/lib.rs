@@ -29,7 +29,8 @@ fn runtime_error(message: &'static str) -> PyErr {
 }
 
 fn build_python_value(py: Python, v: &Value) -> PyResult<PyObject> {
-    use pyo3::types::{PyDict, PyList, PyNone, PySet};
+    use pyo3::types::{PyDict, PyList, PyLong, PyNone, PySet};
+    use rcl::decimal::Mantissa;
     let result = match v {
         Value::Null => PyNone::get(py).into(),
         Value::Bool(b) => b.to_object(py),
@@ -37,7 +38,12 @@ fn build_python_value(py: Python, v: &Value) -> PyResult<PyObject> {
             // Like Python's json module, try to preserve integers as an int
             // object.
             if d.decimals == 0 && d.exponent == 0 {
-                d.mantissa.to_object(py)
+                match &d.mantissa {
+                    Mantissa::Small(n) => n.to_object(py),
+                    // Python ints have arbitrary precision, so we can convert
+                    // big mantissas exactly by going through a string.
+                    Mantissa::Big(n) => py.get_type::<PyLong>().call1((n.to_string(),))?.into(),
+                }
             } else {
                 d.to_f64_lossy().to_object(py)
             }
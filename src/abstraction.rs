@@ -32,6 +32,11 @@ pub fn abstract_expr(input: &str, expr: &CExpr) -> Result<AExpr> {
     Abstractor::new(input).expr(expr)
 }
 
+/// Abstract a type expression.
+pub fn abstract_type(input: &str, type_: &CType) -> Result<AType> {
+    Abstractor::new(input).type_expr(type_)
+}
+
 /// The abstractor can convert CST nodes to AST nodes for a given document.
 struct Abstractor<'a> {
     input: &'a str,
@@ -207,9 +212,9 @@ impl<'a> Abstractor<'a> {
             CExpr::NumHexadecimal(span) => {
                 // Cut off the 0x, then parse the rest.
                 let num_str = span.trim_start(2).resolve(self.input).replace('_', "");
-                match i64::from_str_radix(&num_str, 16) {
-                    Ok(i) => AExpr::NumberLit(Decimal::from(i)),
-                    Err(..) => {
+                match Decimal::from_str_radix(&num_str, 16) {
+                    Some(d) => AExpr::NumberLit(d),
+                    None => {
                         let err = span.error("Overflow in integer literal.");
                         return Err(err.into());
                     }
@@ -219,9 +224,9 @@ impl<'a> Abstractor<'a> {
             CExpr::NumBinary(span) => {
                 // Cut off the 0b, then parse the rest.
                 let num_str = span.trim_start(2).resolve(self.input).replace('_', "");
-                match i64::from_str_radix(&num_str, 2) {
-                    Ok(i) => AExpr::NumberLit(Decimal::from(i)),
-                    Err(..) => {
+                match Decimal::from_str_radix(&num_str, 2) {
+                    Some(d) => AExpr::NumberLit(d),
+                    None => {
                         let err = span.error("Overflow in integer literal.");
                         return Err(err.into());
                     }
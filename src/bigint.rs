@@ -0,0 +1,467 @@
+// RCL -- A reasonable configuration language.
+// Copyright 2025 Ruud van Asseldonk
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// A copy of the License has been included in the root of the repository.
+
+//! Arbitrary-precision integers.
+//!
+//! This is a small and simple implementation that is only used as a fallback
+//! for decimal mantissas that do not fit in an `i64`. It is not optimized for
+//! huge numbers; the algorithms are the schoolbook ones. Limbs store 9 decimal
+//! digits each, which makes formatting and scaling by powers of 10 cheap, and
+//! those are the operations that decimals need most.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+/// The base of a limb.
+const BASE: u32 = 1_000_000_000;
+
+/// The number of decimal digits in one limb.
+const BASE_DIGITS: u32 = 9;
+
+/// A signed integer of arbitrary size.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BigInt {
+    /// Whether the number is negative. Zero is never negative.
+    negative: bool,
+
+    /// The magnitude in base 10^9, least significant limb first.
+    ///
+    /// There are no trailing zero limbs, so zero is the empty vector.
+    limbs: Vec<u32>,
+}
+
+/// Remove trailing zero limbs.
+fn normalize(limbs: &mut Vec<u32>) {
+    while limbs.last() == Some(&0) {
+        limbs.pop();
+    }
+}
+
+fn mag_cmp(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn mag_add(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut result = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0;
+    for i in 0..a.len().max(b.len()) {
+        let x = a.get(i).copied().unwrap_or(0) + b.get(i).copied().unwrap_or(0) + carry;
+        carry = x / BASE;
+        result.push(x % BASE);
+    }
+    result.push(carry);
+    normalize(&mut result);
+    result
+}
+
+/// Compute `a - b`, requires `a >= b`.
+fn mag_sub(a: &[u32], b: &[u32]) -> Vec<u32> {
+    debug_assert_ne!(
+        mag_cmp(a, b),
+        Ordering::Less,
+        "Magnitude subtraction requires a >= b."
+    );
+    let mut result = Vec::with_capacity(a.len());
+    let mut borrow = 0;
+    for (i, x) in a.iter().enumerate() {
+        let y = b.get(i).copied().unwrap_or(0) + borrow;
+        if *x >= y {
+            result.push(x - y);
+            borrow = 0;
+        } else {
+            result.push(x + BASE - y);
+            borrow = 1;
+        }
+    }
+    normalize(&mut result);
+    result
+}
+
+fn mag_mul(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut result = vec![0_u64; a.len() + b.len()];
+    for (i, x) in a.iter().enumerate() {
+        let mut carry = 0_u64;
+        for (j, y) in b.iter().enumerate() {
+            let z = result[i + j] + (*x as u64) * (*y as u64) + carry;
+            carry = z / BASE as u64;
+            result[i + j] = z % BASE as u64;
+        }
+        result[i + b.len()] += carry;
+    }
+    let mut result: Vec<u32> = result.into_iter().map(|x| x as u32).collect();
+    normalize(&mut result);
+    result
+}
+
+/// Compute `a * m + c` for a single-limb `m` and `c`.
+fn mag_mul_small(a: &[u32], m: u32, c: u32) -> Vec<u32> {
+    let mut result = Vec::with_capacity(a.len() + 1);
+    let mut carry = c as u64;
+    for x in a {
+        let z = (*x as u64) * (m as u64) + carry;
+        carry = z / BASE as u64;
+        result.push((z % BASE as u64) as u32);
+    }
+    result.push(carry as u32);
+    normalize(&mut result);
+    result
+}
+
+/// Divide by a single limb, return the quotient and remainder.
+fn mag_div_small(a: &[u32], d: u32) -> (Vec<u32>, u32) {
+    let mut quotient = vec![0; a.len()];
+    let mut rem = 0_u64;
+    for i in (0..a.len()).rev() {
+        let x = rem * BASE as u64 + a[i] as u64;
+        quotient[i] = (x / d as u64) as u32;
+        rem = x % d as u64;
+    }
+    normalize(&mut quotient);
+    (quotient, rem as u32)
+}
+
+/// Long division, return the quotient and remainder.
+fn mag_div_rem(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    assert!(!b.is_empty(), "Division by zero.");
+    if b.len() == 1 {
+        let (q, r) = mag_div_small(a, b[0]);
+        let mut r = vec![r];
+        normalize(&mut r);
+        return (q, r);
+    }
+
+    let mut quotient = vec![0; a.len()];
+    let mut rem: Vec<u32> = Vec::with_capacity(b.len() + 1);
+    for i in (0..a.len()).rev() {
+        rem.insert(0, a[i]);
+        normalize(&mut rem);
+
+        // Find the largest limb q such that b * q <= rem by bisection. This
+        // is not the fastest way, but it is simple and obviously correct.
+        let (mut lo, mut hi) = (0, BASE - 1);
+        while lo < hi {
+            let mid = lo + (hi - lo + 1) / 2;
+            if mag_cmp(&mag_mul_small(b, mid, 0), &rem) == Ordering::Greater {
+                hi = mid - 1;
+            } else {
+                lo = mid;
+            }
+        }
+        if lo > 0 {
+            rem = mag_sub(&rem, &mag_mul_small(b, lo, 0));
+        }
+        quotient[i] = lo;
+    }
+
+    normalize(&mut quotient);
+    (quotient, rem)
+}
+
+impl BigInt {
+    fn from_parts(negative: bool, limbs: Vec<u32>) -> BigInt {
+        BigInt {
+            negative: negative && !limbs.is_empty(),
+            limbs,
+        }
+    }
+
+    /// Parse an unsigned integer in the given radix, which must be at most 16.
+    ///
+    /// Returns `None` if the string is empty or contains a digit that is not
+    /// valid in the radix.
+    pub fn from_str_radix(digits: &str, radix: u32) -> Option<BigInt> {
+        debug_assert!((2..=16).contains(&radix), "Unsupported radix.");
+        if digits.is_empty() {
+            return None;
+        }
+        let mut limbs = Vec::new();
+        for ch in digits.chars() {
+            limbs = mag_mul_small(&limbs, radix, ch.to_digit(radix)?);
+        }
+        Some(BigInt::from_parts(false, limbs))
+    }
+
+    /// Return the value if it fits in an `i64`.
+    pub fn to_i64(&self) -> Option<i64> {
+        // An i64 has at most 19 digits, which fits in 3 limbs.
+        if self.limbs.len() > 3 {
+            return None;
+        }
+        let mut n: i128 = 0;
+        for limb in self.limbs.iter().rev() {
+            n = n * BASE as i128 + *limb as i128;
+        }
+        i64::try_from(if self.negative { -n } else { n }).ok()
+    }
+
+    /// Return the magnitude if it fits in a `u128`.
+    fn magnitude_to_u128(&self) -> Option<u128> {
+        let mut n: u128 = 0;
+        for limb in self.limbs.iter().rev() {
+            n = n.checked_mul(BASE as u128)?.checked_add(*limb as u128)?;
+        }
+        Some(n)
+    }
+
+    /// Return the value if it fits in an `i128`.
+    pub fn to_i128(&self) -> Option<i128> {
+        let n = self.magnitude_to_u128()?;
+        match self.negative {
+            true => 0_i128.checked_sub_unsigned(n),
+            false => i128::try_from(n).ok(),
+        }
+    }
+
+    /// Return the value if it is non-negative and fits in a `u128`.
+    pub fn to_u128(&self) -> Option<u128> {
+        match self.negative {
+            true => None,
+            false => self.magnitude_to_u128(),
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    /// Return -1, 0, or 1, depending on the sign of the number.
+    pub fn signum(&self) -> i64 {
+        match (self.negative, self.is_zero()) {
+            (true, _) => -1,
+            (false, true) => 0,
+            (false, false) => 1,
+        }
+    }
+
+    /// Return the number of decimal digits in the absolute value.
+    ///
+    /// Zero has one digit.
+    pub fn num_digits(&self) -> u32 {
+        match self.limbs.last() {
+            None => 1,
+            Some(top) => (self.limbs.len() as u32 - 1) * BASE_DIGITS + top.ilog10() + 1,
+        }
+    }
+
+    /// Return the magnitude as big-endian bytes, without leading zeros.
+    pub fn magnitude_to_bytes_be(&self) -> Vec<u8> {
+        let mut result = Vec::new();
+        let mut limbs = self.limbs.clone();
+        while !limbs.is_empty() {
+            let (q, r) = mag_div_small(&limbs, 256);
+            result.push(r as u8);
+            limbs = q;
+        }
+        result.reverse();
+        result
+    }
+
+    pub fn neg(&self) -> BigInt {
+        BigInt::from_parts(!self.negative, self.limbs.clone())
+    }
+
+    pub fn add(&self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::from_parts(self.negative, mag_add(&self.limbs, &other.limbs));
+        }
+        match mag_cmp(&self.limbs, &other.limbs) {
+            Ordering::Less => {
+                BigInt::from_parts(other.negative, mag_sub(&other.limbs, &self.limbs))
+            }
+            _ => BigInt::from_parts(self.negative, mag_sub(&self.limbs, &other.limbs)),
+        }
+    }
+
+    pub fn sub(&self, other: &BigInt) -> BigInt {
+        self.add(&other.neg())
+    }
+
+    pub fn mul(&self, other: &BigInt) -> BigInt {
+        BigInt::from_parts(
+            self.negative != other.negative,
+            mag_mul(&self.limbs, &other.limbs),
+        )
+    }
+
+    /// Multiply by `10^n`.
+    pub fn mul_pow10(&self, n: u32) -> BigInt {
+        if self.is_zero() {
+            return self.clone();
+        }
+        let mut limbs = vec![0; (n / BASE_DIGITS) as usize];
+        limbs.extend_from_slice(&self.limbs);
+        let limbs = mag_mul_small(&limbs, 10_u32.pow(n % BASE_DIGITS), 0);
+        BigInt::from_parts(self.negative, limbs)
+    }
+
+    /// Divide, rounding towards zero, and return the quotient and remainder.
+    ///
+    /// Like for the builtin integers, the remainder has the sign of `self`.
+    /// Panics on division by zero.
+    pub fn div_rem(&self, other: &BigInt) -> (BigInt, BigInt) {
+        let (q, r) = mag_div_rem(&self.limbs, &other.limbs);
+        (
+            BigInt::from_parts(self.negative != other.negative, q),
+            BigInt::from_parts(self.negative, r),
+        )
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(x: i64) -> BigInt {
+        let mut n = x.unsigned_abs();
+        let mut limbs = Vec::with_capacity(3);
+        while n > 0 {
+            limbs.push((n % BASE as u64) as u32);
+            n /= BASE as u64;
+        }
+        BigInt::from_parts(x < 0, limbs)
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &BigInt) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => mag_cmp(&self.limbs, &other.limbs),
+            (true, true) => mag_cmp(&other.limbs, &self.limbs),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &BigInt) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut limbs = self.limbs.iter().rev();
+        match limbs.next() {
+            None => return f.write_str("0"),
+            Some(top) if self.negative => write!(f, "-{top}")?,
+            Some(top) => write!(f, "{top}")?,
+        }
+        for limb in limbs {
+            write!(f, "{limb:09}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::BigInt;
+
+    fn big(digits: &str) -> BigInt {
+        match digits.strip_prefix('-') {
+            Some(abs) => BigInt::from_str_radix(abs, 10).unwrap().neg(),
+            None => BigInt::from_str_radix(digits, 10).unwrap(),
+        }
+    }
+
+    #[test]
+    fn bigint_from_i64_roundtrips() {
+        for x in [0, 1, -1, 999_999_999, 1_000_000_000, i64::MIN, i64::MAX] {
+            let b = BigInt::from(x);
+            assert_eq!(b.to_i64(), Some(x));
+            assert_eq!(b.to_string(), x.to_string());
+        }
+        assert_eq!(big("9223372036854775808").to_i64(), None);
+        assert_eq!(big("-9223372036854775809").to_i64(), None);
+    }
+
+    #[test]
+    fn bigint_to_i128_and_u128_check_range() {
+        let u128_max = "340282366920938463463374607431768211455";
+        assert_eq!(big(u128_max).to_u128(), Some(u128::MAX));
+        assert_eq!(big(u128_max).to_i128(), None);
+        assert_eq!(
+            big("340282366920938463463374607431768211456").to_u128(),
+            None
+        );
+        assert_eq!(big(&i128::MAX.to_string()).to_i128(), Some(i128::MAX));
+        assert_eq!(big(&i128::MIN.to_string()).to_i128(), Some(i128::MIN));
+        assert_eq!(
+            big("-170141183460469231731687303715884105729").to_i128(),
+            None
+        );
+        assert_eq!(big("-1").to_u128(), None);
+        assert_eq!(big("0").to_u128(), Some(0));
+    }
+
+    #[test]
+    fn bigint_from_str_radix_works() {
+        assert_eq!(BigInt::from_str_radix("ff", 16), Some(BigInt::from(255)));
+        assert_eq!(BigInt::from_str_radix("101", 2), Some(BigInt::from(5)));
+        assert_eq!(BigInt::from_str_radix("000", 10), Some(BigInt::from(0)));
+        assert_eq!(BigInt::from_str_radix("12", 2), None);
+        assert_eq!(BigInt::from_str_radix("", 10), None);
+        assert_eq!(
+            BigInt::from_str_radix("ffffffffffffffffffffffffffffffff", 16)
+                .unwrap()
+                .to_string(),
+            "340282366920938463463374607431768211455",
+        );
+    }
+
+    #[test]
+    fn bigint_arithmetic_works() {
+        let a = big("123456789012345678901234567890");
+        let b = big("-987654321098765432109876543210");
+        assert_eq!(a.add(&b).to_string(), "-864197532086419753208641975320");
+        assert_eq!(a.sub(&b).to_string(), "1111111110111111111011111111100");
+        assert_eq!(
+            a.mul(&b).to_string(),
+            "-121932631137021795226185032733622923332237463801111263526900",
+        );
+        assert_eq!(a.sub(&a), BigInt::from(0));
+        assert_eq!(
+            a.mul_pow10(10).to_string(),
+            "1234567890123456789012345678900000000000"
+        );
+    }
+
+    #[test]
+    fn bigint_div_rem_rounds_towards_zero() {
+        let a = big("121932631137021795226185032733622923332237463801111263526901");
+        let b = big("987654321098765432109876543210");
+        let (q, r) = a.div_rem(&b);
+        assert_eq!(q.to_string(), "123456789012345678901234567890");
+        assert_eq!(r, BigInt::from(1));
+        let (q, r) = a.neg().div_rem(&b);
+        assert_eq!(q.to_string(), "-123456789012345678901234567890");
+        assert_eq!(r, BigInt::from(-1));
+        let (q, r) = big("100000000000000000000").div_rem(&BigInt::from(-7));
+        assert_eq!(q.to_string(), "-14285714285714285714");
+        assert_eq!(r, BigInt::from(2));
+    }
+
+    #[test]
+    fn bigint_cmp_and_digits() {
+        assert!(big("-100000000000000000000") < big("-99999999999999999999"));
+        assert!(big("100000000000000000000") > big("99999999999999999999"));
+        assert!(BigInt::from(-1) < BigInt::from(0));
+        assert_eq!(BigInt::from(0).num_digits(), 1);
+        assert_eq!(BigInt::from(-999).num_digits(), 3);
+        assert_eq!(big("1000000000").num_digits(), 10);
+    }
+
+    #[test]
+    fn bigint_magnitude_to_bytes_be() {
+        assert_eq!(BigInt::from(0).magnitude_to_bytes_be(), Vec::<u8>::new());
+        assert_eq!(BigInt::from(-258).magnitude_to_bytes_be(), vec![1, 2]);
+        assert_eq!(
+            big("18446744073709551616").magnitude_to_bytes_be(),
+            vec![1, 0, 0, 0, 0, 0, 0, 0, 0],
+        );
+    }
+}
@@ -32,6 +32,7 @@ Commands:
   highlight    Print a document with syntax highlighting.
   patch        Replace a value inside an RCL document.
   query        Evaluate an expression against an input document.
+  schema       Print the JSON Schema for an RCL type.
 "#;
 
 const USAGE_MAIN_EXTENDED: &str = r#"
@@ -115,6 +116,10 @@ Build target fields:
   format: String    The output format, must be one of the formats supported by
                     'rcl evaluate --format', see 'rcl evaluate --help'.
 
+  sort_keys: Bool   Output dicts and sets in sorted order rather than insertion
+                    order, as for 'rcl evaluate --sort-keys'. Optional, defaults
+                    to false.
+
   width: Number     Target width for formatting, as for 'rcl evaluate --width'.
                     Optional, defaults to 80.
 "##;
@@ -146,19 +151,50 @@ Options:
                            evaluation to <file> in Makefile syntax. This can be
                            used by e.g. the Ninja build system.
   --sandbox <mode>         Sandboxing mode, see below. Defaults to 'workdir'.
+  --schema <file>          Check the result against the JSON Schema in <file>,
+                           and report all values that violate it.
+  --sort-keys              Output dicts and sets in sorted order, rather than in
+                           insertion order.
   -w --width <width>       Target width for pretty-printing, must be an integer.
                            Defaults to 80.
 
 Output format:
+  bash          Like sh, but output lists and sets as Bash arrays.
+  cbor          Output binary CBOR. Integers become CBOR integers, other numbers
+                become decimal fractions.
+  csv           If the document is a list of dicts or a list of lists, output
+                every element as a row of comma-separated values. Dict rows get
+                a header line with the keys.
+  dotenv        Output KEY=value lines with shell-compatible quoting. The
+                document must be a dict of strings, numbers, or booleans.
+  hcl           Output HCL, as used by Terraform. Lists of dicts become blocks.
+  ini           Output an INI file. The document must be a dict. Dicts in the
+                document become sections.
   json          Output pretty-printed JSON.
+  json-canonical
+                Output canonical JSON following RFC 8785, the JSON
+                Canonicalization Scheme. Like json-compact, but with keys
+                sorted, and numbers formatted as 64-bit floats.
+  json-compact  Output JSON on a single line, without any whitespace.
   json-lines    If the document is a list, output every element as a JSON value
                 on its own line. Top-level values other than lists are not valid
                 for this format.
+  msgpack       Output binary MessagePack. Integers become MessagePack integers,
+                other numbers become 64-bit floats.
+  nix           Output a Nix expression.
+  plist         Output an Apple property list.
+  properties    Output a Java properties file. The document must be a dict of
+                strings, numbers, or booleans.
   raw           If the document is a string, output the string itself. If the
                 document is a list or set of strings, output each string on its
                 own line.
   rcl           Output pretty-printed RCL.
+  sh            Output KEY='value' shell variable assignments. The document
+                must be a dict of strings, numbers, or booleans.
   toml          Output TOML.
+  tsv           Like csv, but separate values by tabs.
+  xml           Output XML. The document must be a dict with a single key, the
+                root element. Keys that start with @ become attributes.
   yaml-stream   If the document is a list, output every element as a JSON
                 document, prefixed by the '---' YAML document separator.
                 Top-level values other than lists are not valid for this format.
@@ -244,6 +280,47 @@ Options:
 See also --help for global options.
 "#;
 
+const USAGE_SCHEMA: &str = r#"
+RCL -- A reasonable configuration language.
+
+Usage:
+  rcl [<options>] schema [<options>] [<file>] <type>
+
+The 'schema' command prints the JSON Schema (draft 2020-12) that corresponds to
+an RCL type, in JSON format. Before it does so, it evaluates the input file, and
+checks that the result is a value of that type. This way, the same type can
+validate a configuration in RCL, and in tools that support JSON Schema.
+
+As an example, consider the file 'servers.rcl':
+
+  { "web-1": ["10.0.0.1"], "web-2": ["10.0.0.2", "10.0.0.3"] }
+
+The command 'rcl schema servers.rcl "Dict[String, List[String]]"' would print:
+
+  {
+    "$schema": "https://json-schema.org/draft/2020-12/schema",
+    "type": "object",
+    "additionalProperties": {"type": "array", "items": {"type": "string"}}
+  }
+
+Arguments:
+  <file>     The input file to check, or '-' for stdin. Defaults to stdin when
+             no file is specified.
+  <type>     An RCL type expression, such as 'List[String]'. Dict key types
+             must be 'String', and the type cannot contain functions.
+
+Options:
+  -o --output <outfile>    Write to the given file instead of stdout.
+  --output-depfile <file>  Write all dependencies that were loaded during
+                           evaluation to <file> in Makefile syntax.
+  --sandbox <mode>         Sandboxing mode, see 'rcl evaluate --help' for an
+                           explanation of the modes. Defaults to 'workdir'.
+  -w --width <width>       Target width for pretty-printing, must be an integer.
+                           Defaults to 80.
+
+See also --help for global options.
+"#;
+
 const USAGE_ABOUT: &str = r#"
 RCL -- A reasonable configuration language.
 Copyright 2025 Ruud van Asseldonk and contributors
@@ -270,12 +347,27 @@ pub struct GlobalOptions {
 /// The available output formats (JSON, RCL).
 #[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
 pub enum OutputFormat {
+    Bash,
+    Cbor,
+    Csv,
+    Dotenv,
+    Hcl,
+    Ini,
     Json,
+    JsonCanonical,
+    JsonCompact,
     JsonLines,
+    Msgpack,
+    Nix,
+    Plist,
+    Properties,
     Raw,
     #[default]
     Rcl,
+    Sh,
     Toml,
+    Tsv,
+    Xml,
     YamlStream,
 }
 
@@ -296,6 +388,13 @@ pub struct EvalOptions {
 
     /// A banner message to prepend to the output.
     pub banner: Option<String>,
+
+    /// A JSON Schema file to validate the result against.
+    pub schema: Option<String>,
+
+    /// Whether to sort dicts and sets in the output, rather than preserving
+    /// insertion order.
+    pub sort_keys: bool,
 }
 
 /// Options for commands that pretty-print their output.
@@ -367,6 +466,13 @@ pub enum Cmd {
         query: String,
         output: OutputTarget,
     },
+    Schema {
+        eval_opts: EvalOptions,
+        style_opts: StyleOptions,
+        fname: Target,
+        type_expr: String,
+        output: OutputTarget,
+    },
     Format {
         style_opts: StyleOptions,
         target: FormatTarget,
@@ -441,11 +547,26 @@ pub fn parse(args: Vec<String>) -> Result<(GlobalOptions, Cmd)> {
             Arg::Long("format") | Arg::Short("f") => {
                 eval_opts.format = match_option! {
                     args: arg,
+                    "bash" => OutputFormat::Bash,
+                    "cbor" => OutputFormat::Cbor,
+                    "csv" => OutputFormat::Csv,
+                    "dotenv" => OutputFormat::Dotenv,
+                    "hcl" => OutputFormat::Hcl,
+                    "ini" => OutputFormat::Ini,
                     "json" => OutputFormat::Json,
+                    "json-canonical" => OutputFormat::JsonCanonical,
+                    "json-compact" => OutputFormat::JsonCompact,
                     "json-lines" => OutputFormat::JsonLines,
+                    "msgpack" => OutputFormat::Msgpack,
+                    "nix" => OutputFormat::Nix,
+                    "plist" => OutputFormat::Plist,
+                    "properties" => OutputFormat::Properties,
                     "raw" => OutputFormat::Raw,
                     "rcl" => OutputFormat::Rcl,
+                    "sh" => OutputFormat::Sh,
                     "toml" => OutputFormat::Toml,
+                    "tsv" => OutputFormat::Tsv,
+                    "xml" => OutputFormat::Xml,
                     "yaml-stream" => OutputFormat::YamlStream,
                 }
             }
@@ -468,6 +589,15 @@ pub fn parse(args: Vec<String>) -> Result<(GlobalOptions, Cmd)> {
                     "unrestricted" => SandboxMode::Unrestricted,
                 }
             }
+            Arg::Long("schema") => {
+                eval_opts.schema = parse_option! {
+                    args: arg,
+                    |x: &str| Ok::<_, std::convert::Infallible>(Some(x.to_string()))
+                };
+            }
+            Arg::Long("sort-keys") => {
+                eval_opts.sort_keys = true;
+            }
             Arg::Long("width") | Arg::Short("w") => {
                 style_opts.width = parse_option! { args: arg, u32::from_str };
             }
@@ -516,6 +646,9 @@ pub fn parse(args: Vec<String>) -> Result<(GlobalOptions, Cmd)> {
             Arg::Plain("patch") if cmd.is_none() => {
                 cmd = Some("patch");
             }
+            Arg::Plain("schema") if cmd.is_none() => {
+                cmd = Some("schema");
+            }
             Arg::Plain("highlight") | Arg::Plain("h") if cmd.is_none() => {
                 cmd = Some("highlight");
             }
@@ -574,6 +707,9 @@ pub fn parse(args: Vec<String>) -> Result<(GlobalOptions, Cmd)> {
         Some("query") => Some(Cmd::Help {
             usage: &[USAGE_EVAL_QUERY],
         }),
+        Some("schema") => Some(Cmd::Help {
+            usage: &[USAGE_SCHEMA],
+        }),
         _ => None,
     };
     if let Some(help) = help_opt {
@@ -600,37 +736,10 @@ pub fn parse(args: Vec<String>) -> Result<(GlobalOptions, Cmd)> {
             output,
         },
         Some("query") => {
-            let (fname, query) = match targets.len() {
-                2 => (
-                    targets.remove(0),
-                    // Not a file, but a consequence of the CLI parsing that
-                    // handles - being stdin/stdout.
-                    match targets.remove(0) {
-                        Target::File(query) => query,
-                        Target::Stdin => "-".to_string(),
-                        Target::StdinDefault => {
-                            unreachable!("Only produced through absence of args.")
-                        }
-                    },
-                ),
-                1 => (
-                    Target::StdinDefault,
-                    match targets.remove(0) {
-                        Target::File(query) => query,
-                        Target::Stdin => "-".to_string(),
-                        Target::StdinDefault => {
-                            unreachable!("Only produced through absence of args.")
-                        }
-                    },
-                ),
-                _ => {
-                    return Error::new(
-                        "Expected an input file and a query. \
-                        See --help for usage.",
-                    )
-                    .err()
-                }
-            };
+            let (fname, query) = get_target_and_expr(
+                targets,
+                "Expected an input file and a query. See --help for usage.",
+            )?;
             Cmd::Query {
                 eval_opts,
                 style_opts,
@@ -639,6 +748,19 @@ pub fn parse(args: Vec<String>) -> Result<(GlobalOptions, Cmd)> {
                 output,
             }
         }
+        Some("schema") => {
+            let (fname, type_expr) = get_target_and_expr(
+                targets,
+                "Expected an input file and a type. See --help for usage.",
+            )?;
+            Cmd::Schema {
+                eval_opts,
+                style_opts,
+                fname,
+                type_expr,
+                output,
+            }
+        }
         Some("format") => Cmd::Format {
             style_opts,
             target: get_format_target(in_place, check, targets)?,
@@ -682,6 +804,26 @@ fn get_unique_target(mut targets: Vec<Target>) -> Result<Target> {
     }
 }
 
+/// Split the arguments into an optional input file and a final expression.
+fn get_target_and_expr(
+    mut targets: Vec<Target>,
+    error_msg: &'static str,
+) -> Result<(Target, String)> {
+    // The expression is not a file, but the CLI parser treats `-` as stdin,
+    // so we have to undo that here.
+    let expr = match targets.pop() {
+        Some(Target::File(expr)) => expr,
+        Some(Target::Stdin) => "-".to_string(),
+        Some(Target::StdinDefault) => unreachable!("Only produced through absence of args."),
+        None => return Error::new(error_msg).err(),
+    };
+    match targets.len() {
+        0 => Ok((Target::StdinDefault, expr)),
+        1 => Ok((targets.remove(0), expr)),
+        _ => Error::new(error_msg).err(),
+    }
+}
+
 fn get_format_target(in_place: bool, check: bool, targets: Vec<Target>) -> Result<FormatTarget> {
     let result = if in_place {
         FormatTarget::InPlace { fnames: targets }
@@ -837,6 +979,22 @@ mod test {
             expected
         );
 
+        // Test --schema
+        if let Cmd::Evaluate { eval_opts, .. } = &mut expected.1 {
+            eval_opts.output_depfile = None;
+            eval_opts.schema = Some("schema.json".to_string());
+        }
+        assert_eq!(
+            parse(&["rcl", "e", "infile", "--schema", "schema.json"]),
+            expected
+        );
+
+        if let Cmd::Evaluate { eval_opts, .. } = &mut expected.1 {
+            eval_opts.schema = None;
+            eval_opts.sort_keys = true;
+        }
+        assert_eq!(parse(&["rcl", "e", "infile", "--sort-keys"]), expected);
+
         // Test that defaulting to stdin works. If '-' is there we get it
         // explicitly, if it's not, we get it implicitly.
         if let Cmd::Evaluate {
@@ -844,6 +1002,7 @@ mod test {
         } = &mut expected.1
         {
             eval_opts.output_depfile = None;
+            eval_opts.sort_keys = false;
             *fname = Target::Stdin;
         }
         assert_eq!(parse(&["rcl", "e", "-"]), expected);
@@ -872,7 +1031,7 @@ mod test {
         );
         assert_eq!(
             fail_parse(&["rcl", "eval", "infile", "--format=yamr"]),
-            "Error: Expected --format to be followed by one of json, json-lines, raw, rcl, toml, yaml-stream. See --help for usage.\n"
+            "Error: Expected --format to be followed by one of bash, cbor, csv, dotenv, hcl, ini, json, json-canonical, json-compact, json-lines, msgpack, nix, plist, properties, raw, rcl, sh, toml, tsv, xml, yaml-stream. See --help for usage.\n"
         );
         assert_eq!(
             fail_parse(&["rcl", "frobnicate", "infile"]),
@@ -1017,6 +1176,45 @@ mod test {
         );
     }
 
+    #[test]
+    fn parse_cmd_schema() {
+        let expected_opt = GlobalOptions {
+            markup: None,
+            workdir: None,
+        };
+        let expected_cmd = Cmd::Schema {
+            eval_opts: EvalOptions::default(),
+            style_opts: StyleOptions::default(),
+            fname: Target::File("infile".into()),
+            type_expr: "List[String]".to_string(),
+            output: OutputTarget::Stdout,
+        };
+        let mut expected = (expected_opt, expected_cmd);
+        assert_eq!(
+            parse(&["rcl", "schema", "infile", "List[String]"]),
+            expected
+        );
+
+        // With one argument, the arg is the type, and the input is stdin.
+        if let Cmd::Schema { fname, .. } = &mut expected.1 {
+            *fname = Target::StdinDefault;
+        };
+        assert_eq!(parse(&["rcl", "schema", "List[String]"]), expected);
+
+        assert!(matches!(
+            parse(&["rcl", "schema", "-h"]).1,
+            Cmd::Help { .. }
+        ));
+        assert_eq!(
+            fail_parse(&["rcl", "schema"]),
+            "Error: Expected an input file and a type. See --help for usage.\n"
+        );
+        assert_eq!(
+            fail_parse(&["rcl", "schema", "a", "b", "c"]),
+            "Error: Expected an input file and a type. See --help for usage.\n"
+        );
+    }
+
     #[test]
     fn parse_cmd_build() {
         let expected_opt = GlobalOptions {
@@ -7,10 +7,11 @@
 
 //! Implementation of the `rcl build` subcommand.
 
-use std::io::Read;
+use std::io::{Read, Write};
 use std::rc::Rc;
 
 use crate::cli::OutputFormat;
+use crate::cmd_eval::Formatted;
 use crate::error::{Error, PathElement, Result};
 use crate::fmt_rcl::format_rcl;
 use crate::loader::{Loader, OpenMode};
@@ -68,14 +69,29 @@ struct Target {
     width: u32,
 }
 
-fn parse_format(format: &str) -> Option<OutputFormat> {
+pub fn parse_format(format: &str) -> Option<OutputFormat> {
     // Note, this is duplicated between the CLI parser.
     let f = match format {
+        "bash" => OutputFormat::Bash,
+        "cbor" => OutputFormat::Cbor,
+        "csv" => OutputFormat::Csv,
+        "dotenv" => OutputFormat::Dotenv,
+        "hcl" => OutputFormat::Hcl,
+        "ini" => OutputFormat::Ini,
         "json" => OutputFormat::Json,
+        "json-canonical" => OutputFormat::JsonCanonical,
+        "json-compact" => OutputFormat::JsonCompact,
         "json-lines" => OutputFormat::JsonLines,
+        "msgpack" => OutputFormat::Msgpack,
+        "nix" => OutputFormat::Nix,
+        "plist" => OutputFormat::Plist,
+        "properties" => OutputFormat::Properties,
         "raw" => OutputFormat::Raw,
         "rcl" => OutputFormat::Rcl,
+        "sh" => OutputFormat::Sh,
         "toml" => OutputFormat::Toml,
+        "tsv" => OutputFormat::Tsv,
+        "xml" => OutputFormat::Xml,
         "yaml-stream" => OutputFormat::YamlStream,
         _ => return None,
     };
@@ -99,6 +115,7 @@ fn parse_targets(doc_span: Span, targets_value: Value) -> Result<Vec<Target>> {
         // be explicit about it.
         let mut format: Option<OutputFormat> = None;
         let mut contents: Option<Value> = None;
+        let mut sort_keys = false;
         let mut target = Target {
             out_path: out_path_value.expect_string_clone(),
             banner: banner.clone(),
@@ -131,6 +148,12 @@ fn parse_targets(doc_span: Span, targets_value: Value) -> Result<Vec<Target>> {
                         .with_help("See 'rcl evaluate --help' for supported output formats.")
                         .err();
                 }
+                "sort_keys" => match v {
+                    Value::Bool(b) => sort_keys = *b,
+                    _not_bool => {
+                        return make_error("Field 'sort_keys' must be a boolean.".into()).err()
+                    }
+                },
                 "width" => match v.to_i64() {
                     // Technically the upper limit is u32::MAX, but if we have
                     // to be technically correct in the error message, then let's
@@ -151,6 +174,7 @@ fn parse_targets(doc_span: Span, targets_value: Value) -> Result<Vec<Target>> {
         }
 
         match contents {
+            Some(v) if sort_keys => target.contents = v.sort_keys(),
             Some(v) => target.contents = v,
             None => {
                 let msg = concat! {
@@ -197,20 +221,35 @@ pub fn execute_build(
     for (i, target) in targets.iter().enumerate() {
         println!("[{}/{}] {}", i + 1, targets.len(), target.out_path);
 
-        let mut doc = crate::cmd_eval::format_value(target.format, doc_span, &target.contents)?;
-
-        if let Some(banner) = target.banner.as_ref() {
-            doc = concat! {
-                Doc::lines(banner)
-                Doc::HardBreak
-                doc
-            };
-        }
+        let formatted = crate::cmd_eval::format_value(target.format, doc_span, &target.contents)?;
 
-        let print_cfg = Config {
-            width: Some(target.width),
+        let result: Vec<u8> = match formatted {
+            Formatted::Doc(mut doc) => {
+                if let Some(banner) = target.banner.as_ref() {
+                    doc = concat! {
+                        Doc::lines(banner)
+                        Doc::HardBreak
+                        doc
+                    };
+                }
+                let print_cfg = Config {
+                    width: Some(target.width),
+                };
+                let mut out = Vec::new();
+                doc.println(&print_cfg)
+                    .write_bytes_no_markup(&mut out)
+                    .expect("Writing in memory does not fail.");
+                out
+            }
+            Formatted::Bytes(..) if target.banner.is_some() => {
+                return Error::new("Binary output formats do not support a banner.")
+                    .with_origin(doc_span)
+                    .with_path_element(PathElement::Key("banner".into()))
+                    .with_path_element(PathElement::Key(Value::String(target.out_path.clone())))
+                    .err();
+            }
+            Formatted::Bytes(data) => data,
         };
-        let result = doc.println(&print_cfg);
 
         match mode {
             BuildMode::WriteFilesystem => {
@@ -220,7 +259,7 @@ pub fn execute_build(
                     buildfile,
                     OpenMode::Write,
                 )?;
-                match result.write_bytes_no_markup(&mut out_file) {
+                match out_file.write_all(&result) {
                     Ok(()) => continue,
                     Err(err) => {
                         return Error::new(concat! {
@@ -238,10 +277,7 @@ pub fn execute_build(
                     buildfile,
                     OpenMode::Read,
                 )?;
-                let mut expected = Vec::new();
-                result
-                    .write_bytes_no_markup(&mut expected)
-                    .expect("Writing in memory does not fail.");
+                let expected = result;
                 let mut actual = Vec::with_capacity(expected.len());
                 match out_file.read_to_end(&mut actual) {
                     Ok(_) => {
@@ -265,7 +301,7 @@ pub fn execute_build(
                 let mut stdout = std::io::stdout().lock();
                 // Ignore the result here, if we fail to write to stdout,
                 // then we have no good way of reporting the error anyway.
-                let _ = result.write_bytes_no_markup(&mut stdout);
+                let _ = stdout.write_all(&result);
             }
         }
     }
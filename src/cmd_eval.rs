@@ -13,13 +13,48 @@ use crate::pprint::Doc;
 use crate::runtime::Value;
 use crate::source::Span;
 
-pub fn format_value(format: OutputFormat, value_span: Span, value: &Value) -> Result<Doc> {
+/// A value formatted in one of the output formats.
+pub enum Formatted<'a> {
+    /// A document for a textual format, still to be pretty-printed.
+    Doc(Doc<'a>),
+    /// The encoded value for a binary format, to be written as-is.
+    Bytes(Vec<u8>),
+}
+
+pub fn format_value(format: OutputFormat, value_span: Span, value: &Value) -> Result<Formatted> {
+    let result = match format {
+        OutputFormat::Cbor => Formatted::Bytes(crate::fmt_cbor::format_cbor(value_span, value)?),
+        OutputFormat::Msgpack => {
+            Formatted::Bytes(crate::fmt_msgpack::format_msgpack(value_span, value)?)
+        }
+        text_format => Formatted::Doc(format_text(text_format, value_span, value)?),
+    };
+    Ok(result)
+}
+
+fn format_text(format: OutputFormat, value_span: Span, value: &Value) -> Result<Doc> {
     let result = match format {
+        OutputFormat::Bash => crate::fmt_sh::format_bash(value_span, value)?,
+        OutputFormat::Cbor | OutputFormat::Msgpack => {
+            unreachable!("Binary formats are handled by format_value.")
+        }
+        OutputFormat::Csv => crate::fmt_csv::format_csv(value_span, value)?,
+        OutputFormat::Dotenv => crate::fmt_dotenv::format_dotenv(value_span, value)?,
+        OutputFormat::Hcl => crate::fmt_hcl::format_hcl(value_span, value)?,
+        OutputFormat::Ini => crate::fmt_ini::format_ini(value_span, value)?,
         OutputFormat::Json => crate::fmt_json::format_json(value_span, value)?,
+        OutputFormat::JsonCanonical => crate::fmt_json::format_json_canonical(value_span, value)?,
+        OutputFormat::JsonCompact => crate::fmt_json::format_json_compact(value_span, value)?,
         OutputFormat::JsonLines => crate::fmt_json_lines::format_json_lines(value_span, value)?,
+        OutputFormat::Nix => crate::fmt_nix::format_nix(value_span, value)?,
+        OutputFormat::Plist => crate::fmt_xml::format_plist(value_span, value)?,
+        OutputFormat::Properties => crate::fmt_properties::format_properties(value_span, value)?,
         OutputFormat::Raw => crate::fmt_raw::format_raw(value_span, value)?,
         OutputFormat::Rcl => crate::fmt_rcl::format_rcl(value),
+        OutputFormat::Sh => crate::fmt_sh::format_sh(value_span, value)?,
         OutputFormat::Toml => crate::fmt_toml::format_toml(value_span, value)?,
+        OutputFormat::Tsv => crate::fmt_csv::format_tsv(value_span, value)?,
+        OutputFormat::Xml => crate::fmt_xml::format_xml(value_span, value)?,
         OutputFormat::YamlStream => crate::fmt_yaml_stream::format_yaml_stream(value_span, value)?,
     };
     Ok(result)
@@ -0,0 +1,213 @@
+// RCL -- A reasonable configuration language.
+// Copyright 2025 Ruud van Asseldonk
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// A copy of the License has been included in the root of the repository.
+
+//! A parser for comma-separated values.
+//!
+//! The parser follows [RFC 4180](https://www.rfc-editor.org/rfc/rfc4180). It
+//! accepts both `\n` and `\r\n` line endings, and it skips blank lines.
+
+/// An error that occurred while parsing, with the line where it occurred.
+#[derive(Debug, Eq, PartialEq)]
+pub struct CsvError {
+    /// The 1-based line number where the error occurred.
+    pub line: usize,
+    pub message: &'static str,
+}
+
+pub type Result<T> = std::result::Result<T, CsvError>;
+
+/// A record with the 1-based line number on which the record starts.
+pub type Record = (usize, Vec<String>);
+
+/// Parse the input into records of fields, separated by `separator`.
+pub fn parse(input: &str, separator: char) -> Result<Vec<Record>> {
+    let mut parser = Parser {
+        input,
+        separator,
+        pos: 0,
+        line: 1,
+    };
+    parser.parse_records()
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    separator: char,
+    pos: usize,
+    line: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error<T>(&self, line: usize, message: &'static str) -> Result<T> {
+        Err(CsvError { line, message })
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn at_line_end(&self) -> bool {
+        let rest = self.rest();
+        rest.starts_with('\n') || rest.starts_with("\r\n")
+    }
+
+    fn consume_line_end(&mut self) {
+        self.pos += if self.rest().starts_with('\n') { 1 } else { 2 };
+        self.line += 1;
+    }
+
+    fn parse_records(&mut self) -> Result<Vec<Record>> {
+        let mut records = Vec::new();
+        while self.pos < self.input.len() {
+            // Blank lines do not count as records with a single empty field,
+            // we skip them entirely.
+            if self.at_line_end() {
+                self.consume_line_end();
+                continue;
+            }
+            let line = self.line;
+            records.push((line, self.parse_record()?));
+        }
+        Ok(records)
+    }
+
+    fn parse_record(&mut self) -> Result<Vec<String>> {
+        let mut fields = Vec::new();
+        loop {
+            fields.push(self.parse_field()?);
+            match self.peek() {
+                None => return Ok(fields),
+                Some(c) if c == self.separator => self.pos += c.len_utf8(),
+                Some(..) => {
+                    debug_assert!(self.at_line_end(), "Fields end at separator or line end.");
+                    self.consume_line_end();
+                    return Ok(fields);
+                }
+            }
+        }
+    }
+
+    fn parse_field(&mut self) -> Result<String> {
+        if self.peek() == Some('"') {
+            return self.parse_quoted_field();
+        }
+
+        let mut field = String::new();
+        loop {
+            match self.peek() {
+                None => return Ok(field),
+                Some(c) if c == self.separator => return Ok(field),
+                Some(..) if self.at_line_end() => return Ok(field),
+                Some('"') => {
+                    return self.error(
+                        self.line,
+                        "Unexpected quote in unquoted field. \
+                        Fields that contain quotes must be enclosed in quotes.",
+                    )
+                }
+                Some(c) => {
+                    field.push(c);
+                    self.pos += c.len_utf8();
+                }
+            }
+        }
+    }
+
+    fn parse_quoted_field(&mut self) -> Result<String> {
+        let start_line = self.line;
+        let mut field = String::new();
+
+        // Skip over the opening quote.
+        self.pos += 1;
+
+        loop {
+            match self.peek() {
+                None => return self.error(start_line, "Unterminated quoted field."),
+                Some('"') => {
+                    self.pos += 1;
+                    // A doubled quote is an escaped quote, a single quote
+                    // closes the field.
+                    if self.peek() == Some('"') {
+                        self.pos += 1;
+                        field.push('"');
+                    } else {
+                        break;
+                    }
+                }
+                Some(c) => {
+                    if c == '\n' {
+                        self.line += 1;
+                    }
+                    field.push(c);
+                    self.pos += c.len_utf8();
+                }
+            }
+        }
+
+        match self.peek() {
+            None => Ok(field),
+            Some(c) if c == self.separator => Ok(field),
+            Some(..) if self.at_line_end() => Ok(field),
+            Some(..) => self.error(
+                self.line,
+                "Expected a separator or line break after the closing quote.",
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse, CsvError};
+
+    fn fields(input: &str) -> Vec<Vec<String>> {
+        parse(input, ',')
+            .unwrap()
+            .into_iter()
+            .map(|(_line, fields)| fields)
+            .collect()
+    }
+
+    #[test]
+    fn parse_handles_quotes_and_line_endings() {
+        assert_eq!(fields(""), Vec::<Vec<String>>::new());
+        assert_eq!(fields("a,b"), vec![vec!["a", "b"]]);
+        assert_eq!(
+            fields("a,b\r\nc,\n\nd"),
+            vec![vec!["a", "b"], vec!["c", ""], vec!["d"]]
+        );
+        assert_eq!(fields("\"a,b\",\"\"\"\""), vec![vec!["a,b", "\""]]);
+        assert_eq!(fields("\"a\nb\",c\n"), vec![vec!["a\nb", "c"]]);
+    }
+
+    #[test]
+    fn parse_tracks_record_lines() {
+        let lines: Vec<usize> = parse("a\n\"b\nc\"\n\nd", ',')
+            .unwrap()
+            .into_iter()
+            .map(|(line, _fields)| line)
+            .collect();
+        assert_eq!(lines, vec![1, 2, 5]);
+    }
+
+    #[test]
+    fn parse_reports_errors() {
+        assert_eq!(
+            parse("a\n\"b", ',').map(|_| ()),
+            Err(CsvError {
+                line: 2,
+                message: "Unterminated quoted field.",
+            }),
+        );
+        assert_eq!(parse("a\"b", ',').map_err(|e| e.line), Err(1));
+        assert_eq!(parse("\"a\"b", ',').map_err(|e| e.line), Err(1));
+    }
+}
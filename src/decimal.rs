@@ -8,12 +8,271 @@
 //! Implementation of decimal and rational numbers for use in the interpreter.
 
 use std::cmp::Ordering;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::bigint::BigInt;
+
+/// The maximum number of decimal digits in a mantissa.
+///
+/// The mantissa can grow beyond an `i64`, but we do need a limit somewhere,
+/// otherwise e.g. repeated squaring would exhaust memory. Operations whose
+/// result would need more digits than this report overflow.
+pub const MAX_DIGITS: u32 = 1000;
+
+/// The mantissa of a decimal, an integer of arbitrary size.
+///
+/// Nearly all numbers fit in an `i64`, so we store those inline, and only fall
+/// back to an arbitrary-precision integer when needed. A `Big` mantissa never
+/// fits in an `i64`, this invariant is maintained by all operations.
+#[derive(Clone, Debug)]
+pub enum Mantissa {
+    Small(i64),
+    Big(Rc<BigInt>),
+}
+
+impl From<i64> for Mantissa {
+    fn from(x: i64) -> Mantissa {
+        Mantissa::Small(x)
+    }
+}
+
+impl Mantissa {
+    /// Wrap a big integer, return `None` if it has too many digits.
+    fn from_big(x: BigInt) -> Option<Mantissa> {
+        match x.to_i64() {
+            Some(n) => Some(Mantissa::Small(n)),
+            None if x.num_digits() > MAX_DIGITS => None,
+            None => Some(Mantissa::Big(Rc::new(x))),
+        }
+    }
+
+    fn to_big(&self) -> BigInt {
+        match self {
+            Mantissa::Small(n) => BigInt::from(*n),
+            Mantissa::Big(n) => n.as_ref().clone(),
+        }
+    }
+
+    pub fn to_i64(&self) -> Option<i64> {
+        match self {
+            Mantissa::Small(n) => Some(*n),
+            Mantissa::Big(..) => None,
+        }
+    }
+
+    pub fn to_i128(&self) -> Option<i128> {
+        match self {
+            Mantissa::Small(n) => Some(*n as i128),
+            Mantissa::Big(n) => n.to_i128(),
+        }
+    }
+
+    /// Return the value if it is non-negative and fits in a `u128`.
+    pub fn to_u128(&self) -> Option<u128> {
+        match self {
+            Mantissa::Small(n) => u128::try_from(*n).ok(),
+            Mantissa::Big(n) => n.to_u128(),
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        matches!(self, Mantissa::Small(0))
+    }
+
+    /// Return -1, 0, or 1, depending on the sign of the mantissa.
+    pub fn signum(&self) -> i64 {
+        match self {
+            Mantissa::Small(n) => n.signum(),
+            Mantissa::Big(n) => n.signum(),
+        }
+    }
+
+    /// Return the number of decimal digits in the absolute value.
+    pub fn num_digits(&self) -> u32 {
+        match self {
+            Mantissa::Small(0) => 1,
+            Mantissa::Small(n) => n.unsigned_abs().ilog10() + 1,
+            Mantissa::Big(n) => n.num_digits(),
+        }
+    }
+
+    pub fn neg(&self) -> Mantissa {
+        match self {
+            Mantissa::Small(n) => match n.checked_neg() {
+                Some(m) => Mantissa::Small(m),
+                None => Mantissa::Big(Rc::new(BigInt::from(*n).neg())),
+            },
+            Mantissa::Big(n) => Mantissa::from_big(n.neg()).expect("Negation does not add digits."),
+        }
+    }
+
+    pub fn checked_add(&self, other: &Mantissa) -> Option<Mantissa> {
+        if let (Mantissa::Small(x), Mantissa::Small(y)) = (self, other) {
+            if let Some(z) = x.checked_add(*y) {
+                return Some(Mantissa::Small(z));
+            }
+        }
+        Mantissa::from_big(self.to_big().add(&other.to_big()))
+    }
+
+    pub fn checked_mul(&self, other: &Mantissa) -> Option<Mantissa> {
+        if let (Mantissa::Small(x), Mantissa::Small(y)) = (self, other) {
+            if let Some(z) = x.checked_mul(*y) {
+                return Some(Mantissa::Small(z));
+            }
+        }
+        if self.num_digits() + other.num_digits() > MAX_DIGITS + 1 {
+            return None;
+        }
+        Mantissa::from_big(self.to_big().mul(&other.to_big()))
+    }
+
+    /// Multiply by `10^n`.
+    pub fn checked_mul_pow10(&self, n: u32) -> Option<Mantissa> {
+        if let Mantissa::Small(x) = self {
+            if let Some(z) = 10_i64.checked_pow(n).and_then(|f| x.checked_mul(f)) {
+                return Some(Mantissa::Small(z));
+            }
+        }
+        if self.is_zero() {
+            return Some(Mantissa::Small(0));
+        }
+        if self.num_digits().saturating_add(n) > MAX_DIGITS {
+            return None;
+        }
+        Mantissa::from_big(self.to_big().mul_pow10(n))
+    }
+
+    /// Divide, rounding towards zero, and return the quotient and remainder.
+    ///
+    /// The remainder has the sign of `self`. Panics on division by zero.
+    pub fn div_rem(&self, other: &Mantissa) -> (Mantissa, Mantissa) {
+        if let (Mantissa::Small(x), Mantissa::Small(y)) = (self, other) {
+            // This can only fail for i64::MIN / -1.
+            if let (Some(q), Some(r)) = (x.checked_div(*y), x.checked_rem(*y)) {
+                return (Mantissa::Small(q), Mantissa::Small(r));
+            }
+        }
+        let (q, r) = self.to_big().div_rem(&other.to_big());
+        (
+            Mantissa::from_big(q).expect("The quotient is not larger than the dividend."),
+            Mantissa::from_big(r).expect("The remainder is not larger than the divisor."),
+        )
+    }
+
+    /// Divide by `10^n` and round half away from zero.
+    fn div_pow10_round(&self, n: u32) -> Option<Mantissa> {
+        if n == 0 {
+            return Some(self.clone());
+        }
+        let f = Mantissa::Small(1).checked_mul_pow10(n)?;
+        let half = Mantissa::Small(5 * self.signum()).checked_mul_pow10(n - 1)?;
+        Some(self.checked_add(&half)?.div_rem(&f).0)
+    }
+
+    /// Return the integer square root, the square root rounded down.
+    ///
+    /// Requires the mantissa to be non-negative.
+    fn isqrt(&self) -> Mantissa {
+        debug_assert!(
+            self.signum() >= 0,
+            "Square root requires a non-negative input."
+        );
+        if self.is_zero() {
+            return Mantissa::Small(0);
+        }
+        // Newton's method, starting from a power of 10 that is at least the
+        // square root, converges monotonically from above.
+        let msg = "The iterates are not larger than the initial guess.";
+        let two = Mantissa::Small(2);
+        let mut x = Mantissa::Small(1)
+            .checked_mul_pow10((self.num_digits() + 1) / 2)
+            .expect(msg);
+        loop {
+            let y = x
+                .checked_add(&self.div_rem(&x).0)
+                .expect(msg)
+                .div_rem(&two)
+                .0;
+            if y >= x {
+                return x;
+            }
+            x = y;
+        }
+    }
+
+    /// Return `k` if the mantissa is equal to `2^k`.
+    fn log2_exact(&self) -> Option<u32> {
+        match self {
+            Mantissa::Small(n) if *n > 0 && (*n as u64).is_power_of_two() => {
+                Some(n.trailing_zeros())
+            }
+            Mantissa::Small(..) => None,
+            Mantissa::Big(..) => {
+                let two = Mantissa::Small(2);
+                let (q, r) = self.div_rem(&two);
+                match r.is_zero() {
+                    true => Some(q.log2_exact()? + 1),
+                    false => None,
+                }
+            }
+        }
+    }
+
+    /// Convert to a float, this is lossy for large mantissas.
+    fn to_f64_lossy(&self) -> f64 {
+        match self {
+            Mantissa::Small(n) => *n as f64,
+            Mantissa::Big(n) => n
+                .to_string()
+                .parse()
+                .expect("Formatted integers are valid floats."),
+        }
+    }
+}
+
+impl PartialEq for Mantissa {
+    fn eq(&self, other: &Mantissa) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Mantissa {}
+
+impl Ord for Mantissa {
+    fn cmp(&self, other: &Mantissa) -> Ordering {
+        match (self, other) {
+            (Mantissa::Small(x), Mantissa::Small(y)) => x.cmp(y),
+            // A big mantissa is always outside of the range of a small one,
+            // so its sign determines the order.
+            (Mantissa::Small(_), Mantissa::Big(y)) => 0.cmp(&y.signum()),
+            (Mantissa::Big(x), Mantissa::Small(_)) => x.signum().cmp(&0),
+            (Mantissa::Big(x), Mantissa::Big(y)) => x.cmp(y),
+        }
+    }
+}
+
+impl PartialOrd for Mantissa {
+    fn partial_cmp(&self, other: &Mantissa) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Mantissa {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Mantissa::Small(n) => write!(f, "{n}"),
+            Mantissa::Big(n) => write!(f, "{n}"),
+        }
+    }
+}
 
 /// A rational number of the form `m × 10^(n - d)`.
-#[derive(Copy, Clone, Debug)]
+#[derive(Clone, Debug)]
 pub struct Decimal {
     /// The value `m`.
-    pub mantissa: i64,
+    pub mantissa: Mantissa,
     /// The exponent `n`.
     pub exponent: i16,
     /// The exponent `d`, which is also the number of decimal digits.
@@ -23,7 +282,7 @@ pub struct Decimal {
 impl From<i64> for Decimal {
     fn from(x: i64) -> Decimal {
         Decimal {
-            mantissa: x,
+            mantissa: Mantissa::Small(x),
             decimals: 0,
             exponent: 0,
         }
@@ -49,7 +308,9 @@ impl Decimal {
     /// Parse a json number into either a decimal or an integer.
     ///
     /// Assumes the string is already validated (by the lexer), panics on
-    /// unknown characters. Returns `None` on overflow.
+    /// unknown characters. Returns `None` on overflow, which can only happen
+    /// in the exponent. Integers that do not fit in an `i64` are returned as
+    /// a decimal without decimals and exponent.
     ///
     /// To aid testability and use in other places than the parser, this
     /// function can handle inputs that start with a minus sign, even though
@@ -59,24 +320,25 @@ impl Decimal {
     pub fn parse_str(dec: &str) -> Option<ParseResult> {
         // When we count the number of decimals, and the exponent, we may
         // increment those counts for every digit we parse, so after 255
-        // decimals we may overflow the `decimals` counter, and after 16 KiB of
-        // zeros we may overflow the `exponent_offset` counter. We could do a
+        // decimals we may overflow the `decimals` counter. We could do a
         // checked add for that, but if you have a 256-byte number literal, then
-        // what are you even doing? We just disallow that.
+        // what are you even doing? We just disallow that. This also keeps the
+        // mantissa well below `MAX_DIGITS`.
         if dec.len() > 255 {
             return None;
         }
 
+        // We accumulate the mantissa in an i64 as long as it fits, and only
+        // switch to a big integer when it no longer does.
         let mut n: i64 = 0;
+        let mut n_big: Option<BigInt> = None;
         let mut decimals: u8 = 0;
         let mut exponent: i16 = 0;
-        let mut exponent_offset: i16 = 0;
 
         let mut is_int = true;
         let mut is_exp = false;
-        let mut is_precise = true;
         let mut exponent_sign: i16 = 1;
-        let mut mantissa_sign: i64 = 1;
+        let mut is_negative = false;
 
         for ch in dec.as_bytes() {
             match ch {
@@ -88,41 +350,26 @@ impl Decimal {
                         .checked_mul(10)?
                         .checked_add((ch - b'0') as i16 * exponent_sign)?;
                 }
-                b'0'..=b'9' if !is_precise => {
-                    // If the mantissa is already saturated, but we keep getting
-                    // more digits, we just drop them, but we do need to adjust
-                    // the exponent if we are still parsing an int. This does
-                    // not overflow because we validated the length of the input
-                    // string.
-                    exponent_offset += if is_int { 1 } else { 0 };
-                }
                 b'0'..=b'9' => {
-                    match n
-                        .checked_mul(10)
-                        .and_then(|n10| n10.checked_add((ch - b'0') as i64 * mantissa_sign))
-                    {
-                        // We added one digit to the mantissa and it still fits.
-                        Some(m) => {
-                            n = m;
-                            // This add does not overflow because the input is
-                            // at most 255 bytes long.
-                            decimals += if is_int { 0 } else { 1 };
-                        }
-                        // The mantissa is saturated, we switch to dropping
-                        // digits. If appropriate and when possible, round up
-                        // the last digit we had.
-                        None => {
-                            let round_up = *ch >= b'5';
-                            n = n.saturating_add(if round_up { 1 } else { 0 });
-                            exponent_offset += if is_int { 1 } else { 0 };
-                            is_precise = false;
-                        }
+                    let digit = (ch - b'0') as i64;
+                    match n_big.as_mut() {
+                        Some(big) => *big = big.mul_pow10(1).add(&BigInt::from(digit)),
+                        None => match n.checked_mul(10).and_then(|n10| n10.checked_add(digit)) {
+                            Some(m) => n = m,
+                            None => {
+                                let big = BigInt::from(n).mul_pow10(1).add(&BigInt::from(digit));
+                                n_big = Some(big);
+                            }
+                        },
                     }
+                    // This add does not overflow because the input is at most
+                    // 255 bytes long.
+                    decimals += if is_int { 0 } else { 1 };
                 }
                 b'.' => is_int = false,
                 b'+' => {}
                 b'-' if is_exp => exponent_sign = -1,
-                b'-' => mantissa_sign = -1,
+                b'-' => is_negative = true,
                 b'e' | b'E' => is_exp = true,
                 b'_' => {
                     // Numeric underscores are allowed, we just ignore them.
@@ -133,36 +380,61 @@ impl Decimal {
             }
         }
 
-        exponent = exponent.checked_add(exponent_offset)?;
+        let mut mantissa = match n_big {
+            None => Mantissa::Small(n),
+            Some(big) => Mantissa::from_big(big).expect("Inputs are short enough to fit."),
+        };
+        if is_negative {
+            mantissa = mantissa.neg();
+        }
 
-        // If we parsed something that was not an integer, but due to precision
-        // limits we ended up having no decimals and exponent, then if we would
-        // format it later, we would format it as int rather than float. Adjust
-        // for that by bumping the decimals, and compensating in the exponent.
-        // This means we parse this number's formatting as lossy, but avoiding
-        // silently turning floats into ints is more important than preserving
-        // the exact formatting of the number.
+        // If we parsed something that was not an integer, but we ended up
+        // having no decimals and exponent, as in `1.`, then if we would format
+        // it later, we would format it as int rather than float. Adjust for
+        // that by bumping the decimals, and compensating in the exponent.
         if !is_int && decimals == 0 && exponent == 0 {
             decimals = 1;
-            exponent = if n == 0 { 0 } else { 1 };
+            exponent = if mantissa.is_zero() { 0 } else { 1 };
         }
 
-        if is_int && !is_exp {
-            // For integers, if the literal is too large to fit in an i64 and we
-            // switched to imprecise, we don't want to implicitly turn that
-            // integer literal into a float, so treat that as overflow.
-            if is_precise {
-                Some(ParseResult::Int(n))
-            } else {
-                None
-            }
-        } else {
-            Some(ParseResult::Decimal(Decimal {
-                mantissa: n,
+        match mantissa {
+            Mantissa::Small(i) if is_int && !is_exp => Some(ParseResult::Int(i)),
+            mantissa => Some(ParseResult::Decimal(Decimal {
+                mantissa,
                 decimals,
                 exponent,
-            }))
+            })),
+        }
+    }
+
+    /// Parse an unsigned integer in the given radix, e.g. a hexadecimal literal.
+    ///
+    /// The input should not contain prefixes, signs, or numeric underscores.
+    /// Returns `None` on overflow or invalid digits.
+    pub fn from_str_radix(digits: &str, radix: u32) -> Option<Decimal> {
+        // `i64::from_str_radix` accepts a sign, but we only want digits.
+        if !digits.chars().all(|ch| ch.is_digit(radix)) {
+            return None;
         }
+        let mantissa = match i64::from_str_radix(digits, radix) {
+            Ok(n) => Mantissa::Small(n),
+            Err(..) => Mantissa::from_big(BigInt::from_str_radix(digits, radix)?)?,
+        };
+        let result = Decimal {
+            mantissa,
+            decimals: 0,
+            exponent: 0,
+        };
+        Some(result)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.mantissa.is_zero()
+    }
+
+    /// Return -1, 0, or 1, depending on the sign of the number.
+    pub fn signum(&self) -> i64 {
+        self.mantissa.signum()
     }
 
     /// Format the number.
@@ -177,7 +449,7 @@ impl Decimal {
 
         // If the result starts with a minus sign, we need to leave it in place
         // when inserting the decimal point.
-        let neg_off = if self.mantissa < 0 { 1 } else { 0 };
+        let neg_off = if self.mantissa.signum() < 0 { 1 } else { 0 };
 
         if self.decimals > 0 {
             while self.decimals as usize + neg_off >= result.len() {
@@ -196,48 +468,43 @@ impl Decimal {
 
     pub fn checked_neg(&self) -> Option<Decimal> {
         let result = Decimal {
-            mantissa: self.mantissa.checked_neg()?,
+            mantissa: self.mantissa.neg(),
             decimals: self.decimals,
             exponent: self.exponent,
         };
         Some(result)
     }
 
-    pub fn checked_add(&self, other: &Decimal) -> Option<Decimal> {
-        let (mut d1, mut d2) = (*self, *other);
-
-        // To add, we first make the number of decimals equal, then we make the
-        // exponents equal, by moving powers of 10 out of decimals/exponent and
-        // into the mantissa. This is a bit naive, there are cases where it
-        // overflows that we could avoid, but it'll do for now.
-
-        // The number of decimals in the result is the larger of the two.
-        if d1.decimals < d2.decimals {
-            std::mem::swap(&mut d1, &mut d2);
-        }
-        if d1.decimals > d2.decimals {
-            let f = 10_i64.checked_pow((d1.decimals - d2.decimals) as u32)?;
-            d2.mantissa = d2.mantissa.checked_mul(f)?;
-            d2.decimals = d1.decimals;
-        }
+    /// Scale both numbers to the same number of decimals and exponent.
+    ///
+    /// Returns the scaled mantissas of `self` and `other`, and the decimals and
+    /// exponent that they now share. The number of decimals is the larger of
+    /// the two, and the exponent the smaller of the two. We move powers of 10
+    /// out of decimals/exponent and into the mantissa. This is a bit naive,
+    /// there are cases where it overflows that we could avoid, but it'll do for
+    /// now.
+    fn align(&self, other: &Decimal) -> Option<(Mantissa, Mantissa, u8, i16)> {
+        let decimals = self.decimals.max(other.decimals);
+        let exponent = self.exponent.min(other.exponent);
+
+        // The exponent differences may not fit in an i16 when one exponent is
+        // close to i16::MAX and the other close to i16::MIN, so widen to i32
+        // before subtracting.
+        let scale = |d: &Decimal| {
+            d.mantissa
+                .checked_mul_pow10((decimals - d.decimals) as u32)?
+                .checked_mul_pow10((d.exponent as i32 - exponent as i32) as u32)
+        };
 
-        // The exponent of the result is the smaller of the two.
-        if d1.exponent > d2.exponent {
-            std::mem::swap(&mut d1, &mut d2);
-        }
-        if d1.exponent < d2.exponent {
-            // We know d2 > d1, so d2 - d1 is positive, but the difference may
-            // not fit in an i16 when d2 is close to i16::MAX and d1 is close to
-            // i16::MIN, so widen to i32 before subtracting.
-            let f = 10_i64.checked_pow((d2.exponent as i32 - d1.exponent as i32) as u32)?;
-            d2.mantissa = d2.mantissa.checked_mul(f)?;
-            d2.exponent = d1.exponent;
-        }
+        Some((scale(self)?, scale(other)?, decimals, exponent))
+    }
 
+    pub fn checked_add(&self, other: &Decimal) -> Option<Decimal> {
+        let (m1, m2, decimals, exponent) = self.align(other)?;
         let result = Decimal {
-            mantissa: d1.mantissa.checked_add(d2.mantissa)?,
-            decimals: d1.decimals,
-            exponent: d1.exponent,
+            mantissa: m1.checked_add(&m2)?,
+            decimals,
+            exponent,
         };
         Some(result)
     }
@@ -253,7 +520,7 @@ impl Decimal {
         // should try to eliminate powers of 10 until the result has as many
         // decimals as one of the inputs.
         let result = Decimal {
-            mantissa: self.mantissa.checked_mul(other.mantissa)?,
+            mantissa: self.mantissa.checked_mul(&other.mantissa)?,
             decimals: self.decimals.checked_add(other.decimals)?,
             exponent: self.exponent.checked_add(other.exponent)?,
         };
@@ -262,25 +529,24 @@ impl Decimal {
 
     /// Divide two numbers, but only if `self` is an exact multiple of `other`.
     pub fn checked_div_exact(&self, other: &Decimal) -> Option<Decimal> {
-        debug_assert_ne!(other.mantissa, 0, "Division by zero is not allowed.");
+        debug_assert!(!other.is_zero(), "Division by zero is not allowed.");
 
         // TODO: This could be smarter, now it might overflow in cases where
         // that is not strictly needed.
-        let y = other.mantissa;
-        let f = 10_i64.checked_pow(other.decimals as u32)?;
-        let mut x = self.mantissa.checked_mul(f)?;
-
-        let mut q = x / y;
+        let x = self.mantissa.checked_mul_pow10(other.decimals as u32)?;
+        let y = &other.mantissa;
+        let (mut q, mut r) = x.div_rem(y);
         let mut d = self.decimals;
 
-        // If the division doesn't work, add more decimals and retry. Either we
-        // find a case where it works, or x is missing other factors than 2 or 5
-        // and it will never work, but then we'll hit overflow in at most 19
-        // iterations.
-        while q * y != x {
-            x = x.checked_mul(10)?;
+        // If the division doesn't work, add more decimals and continue the
+        // long division with the remainder. Either we find a case where it
+        // works, or x is missing other factors than 2 or 5 and it will never
+        // work, but then we'll hit overflow of the decimals.
+        while !r.is_zero() {
+            let (digit, r_next) = r.checked_mul_pow10(1)?.div_rem(y);
+            q = q.checked_mul_pow10(1)?.checked_add(&digit)?;
+            r = r_next;
             d = d.checked_add(1)?;
-            q = x / y;
         }
 
         let result = Decimal {
@@ -292,100 +558,316 @@ impl Decimal {
         Some(result)
     }
 
+    /// Divide, rounding the quotient towards zero.
+    ///
+    /// Returns the integer quotient and the remainder, which has the sign of
+    /// `self`. The remainder has as many decimals as the input with the most
+    /// decimals, like for addition.
+    pub fn checked_div_rem(&self, other: &Decimal) -> Option<(Decimal, Decimal)> {
+        debug_assert!(!other.is_zero(), "Division by zero is not allowed.");
+        let (m1, m2, decimals, exponent) = self.align(other)?;
+        let (q, r) = m1.div_rem(&m2);
+        let quotient = Decimal {
+            mantissa: q,
+            decimals: 0,
+            exponent: 0,
+        };
+        let remainder = Decimal {
+            mantissa: r,
+            decimals,
+            exponent,
+        };
+        Some((quotient, remainder))
+    }
+
+    /// Divide, rounding the quotient towards negative infinity.
+    ///
+    /// Like [`Decimal::checked_div_rem`], but the remainder (the modulus) has
+    /// the sign of `other`.
+    pub fn checked_div_mod(&self, other: &Decimal) -> Option<(Decimal, Decimal)> {
+        let (q, r) = self.checked_div_rem(other)?;
+        if r.is_zero() || r.signum() == other.signum() {
+            return Some((q, r));
+        }
+        Some((q.checked_sub(&Decimal::from(1))?, r.checked_add(other)?))
+    }
+
+    /// Raise the number to the power `n`.
+    ///
+    /// Like for multiplication, the number of decimals is multiplied by `n`.
+    pub fn checked_pow(&self, mut n: u64) -> Option<Decimal> {
+        // Exponentiation by squaring.
+        let mut result = Decimal::from(1);
+        let mut base = self.clone();
+        while n > 0 {
+            if n & 1 == 1 {
+                result = result.checked_mul(&base)?;
+            }
+            n >>= 1;
+            if n > 0 {
+                base = base.checked_mul(&base)?;
+            }
+        }
+        Some(result)
+    }
+
+    /// Build the number `m × 10^f`.
+    ///
+    /// This prefers a representation without exponent, so the result is an
+    /// integer when `f` is non-negative, and otherwise has `-f` decimals.
+    /// When neither fits, the result is in scientific notation with one digit
+    /// before the decimal point, the same form as when we parse `1.5e400`.
+    fn from_scaled(mantissa: Mantissa, f: i32) -> Option<Decimal> {
+        if f >= 0 {
+            if let Some(m) = mantissa.checked_mul_pow10(f as u32) {
+                return Some(Decimal::from_mantissa(m));
+            }
+        }
+        if let Ok(decimals) = u8::try_from(-f) {
+            let result = Decimal {
+                mantissa,
+                decimals,
+                exponent: 0,
+            };
+            return Some(result);
+        }
+
+        let mut m = mantissa;
+        let mut f = f;
+        let ten = Mantissa::Small(10);
+        while !m.is_zero() {
+            match m.div_rem(&ten) {
+                (q, r) if r.is_zero() => {
+                    m = q;
+                    f += 1;
+                }
+                _ => break,
+            }
+        }
+        let decimals = u8::try_from(m.num_digits() - 1).unwrap_or(u8::MAX);
+        let result = Decimal {
+            mantissa: m,
+            decimals,
+            exponent: i16::try_from(f + decimals as i32).ok()?,
+        };
+        Some(result)
+    }
+
+    /// Build the number `m × 10^f`, rounded to `n_digits` significant digits.
+    fn from_scaled_rounded(mantissa: Mantissa, f: i32, n_digits: u32) -> Option<Decimal> {
+        let excess = mantissa.num_digits().saturating_sub(n_digits);
+        let mut m = mantissa.div_pow10_round(excess)?;
+        let mut f = f + excess as i32;
+        // Rounding up can add a digit, as in 999 to 1000.
+        if m.num_digits() > n_digits {
+            m = m.div_rem(&Mantissa::Small(10)).0;
+            f += 1;
+        }
+        Decimal::from_scaled(m, f)
+    }
+
+    fn from_mantissa(mantissa: Mantissa) -> Decimal {
+        Decimal {
+            mantissa,
+            decimals: 0,
+            exponent: 0,
+        }
+    }
+
+    /// Divide two numbers, exactly if possible, and otherwise rounded.
+    ///
+    /// When the quotient cannot be represented exactly, it is rounded to
+    /// `n_digits` significant digits.
+    pub fn checked_div_rounded(&self, other: &Decimal, n_digits: u32) -> Option<Decimal> {
+        debug_assert!(!other.is_zero(), "Division by zero is not allowed.");
+        if let Some(result) = self.checked_div_exact(other) {
+            return Some(result);
+        }
+
+        // Scale the numerator such that the quotient has at least one more
+        // digit than we need, then round that.
+        let m1 = &self.mantissa;
+        let m2 = &other.mantissa;
+        let e1 = self.exponent as i32 - self.decimals as i32;
+        let e2 = other.exponent as i32 - other.decimals as i32;
+        let s = (n_digits + 1 + m2.num_digits()).saturating_sub(m1.num_digits());
+        let q = m1.checked_mul_pow10(s)?.div_rem(m2).0;
+        Decimal::from_scaled_rounded(q, e1 - e2 - s as i32, n_digits)
+    }
+
+    pub fn abs(&self) -> Decimal {
+        let mut result = self.clone();
+        if self.signum() < 0 {
+            result.mantissa = result.mantissa.neg();
+        }
+        result
+    }
+
+    /// Round down to an integer, towards negative infinity.
+    pub fn floor(&self) -> Option<Decimal> {
+        if self.exponent as i32 - self.decimals as i32 >= 0 {
+            return Some(self.clone());
+        }
+        self.checked_div_mod(&Decimal::from(1)).map(|(q, _)| q)
+    }
+
+    /// Round up to an integer, towards positive infinity.
+    pub fn ceil(&self) -> Option<Decimal> {
+        self.checked_neg()?.floor()?.checked_neg()
+    }
+
+    /// Return the square root, exactly if possible, and otherwise rounded.
+    ///
+    /// When the square root cannot be represented exactly, it is rounded to
+    /// `n_digits` significant digits. An exact result has at least half as
+    /// many decimals as the input, rounded up. Requires the number to be
+    /// non-negative.
+    pub fn sqrt(&self, n_digits: u32) -> Option<Decimal> {
+        debug_assert!(
+            self.signum() >= 0,
+            "Square root requires a non-negative input."
+        );
+
+        // We compute the integer square root of `m × 10^s`, where `s` is large
+        // enough that the result has more digits than we need, and where
+        // `e - s` is even, so we can halve it.
+        let e = self.exponent as i32 - self.decimals as i32;
+        let mut s = (2 * (n_digits + 1)).saturating_sub(self.mantissa.num_digits()) as i32;
+        if (e - s) % 2 != 0 {
+            s += 1;
+        }
+        let n = self.mantissa.checked_mul_pow10(s as u32)?;
+        let mut q = n.isqrt();
+        let mut f = (e - s) / 2;
+
+        let is_exact = q.checked_mul(&q)? == n;
+        if !is_exact {
+            return Decimal::from_scaled_rounded(q, f, n_digits);
+        }
+
+        // Drop the trailing zeros that we introduced by scaling.
+        let min_decimals = (self.decimals as i32 + 1) / 2;
+        let ten = Mantissa::Small(10);
+        while -f > min_decimals && !q.is_zero() {
+            match q.div_rem(&ten) {
+                (q_div, r) if r.is_zero() => {
+                    q = q_div;
+                    f += 1;
+                }
+                _ => break,
+            }
+        }
+        if q.is_zero() {
+            f = -min_decimals;
+        }
+        Decimal::from_scaled(q, f)
+    }
+
+    /// Return the base-2 logarithm, exactly if possible, and otherwise rounded.
+    ///
+    /// The result is exact for powers of two, including negative powers. For
+    /// other numbers we go through a float, and round the result to
+    /// `n_digits` significant digits, which should be well below the precision
+    /// of a float. Requires the number to be positive.
+    pub fn log2(&self, n_digits: u32) -> Option<Decimal> {
+        debug_assert!(self.signum() > 0, "Logarithm requires a positive input.");
+
+        if let Some(k) = self.to_integer().and_then(|m| m.log2_exact()) {
+            return Some(Decimal::from(k as i64));
+        }
+        let inverse = Decimal::from(1).checked_div_exact(self);
+        if let Some(k) = inverse.and_then(|x| x.to_integer()?.log2_exact()) {
+            return Some(Decimal::from(-(k as i64)));
+        }
+
+        // Take the leading digits of the mantissa as float, the remaining
+        // digits we add to the exponent, so this works for huge numbers too.
+        let digits = self.mantissa.to_string();
+        let n_lead = digits.len().min(17);
+        let lead: f64 = digits[..n_lead].parse().expect("Digits are valid floats.");
+        let e = self.exponent as i32 - self.decimals as i32 + (digits.len() - n_lead) as i32;
+        let x = lead.log2() + e as f64 * 10.0_f64.log2();
+
+        // Format in scientific notation to get the right number of significant
+        // digits, and then parse that back as decimal.
+        let x_str = format!("{:.*e}", n_digits as usize - 1, x);
+        let x_dec: Decimal = Decimal::parse_str(&x_str)?.into();
+        let f = x_dec.exponent as i32 - x_dec.decimals as i32;
+        Decimal::from_scaled(x_dec.mantissa, f)
+    }
+
     /// Round the number to the nearest multiple of 10^{-n_decimals}.
     ///
     /// This moves the exponent into the mantissa, so the result always has the
-    /// exponent set to 0. This means that round is not that useful beyond the
-    /// range of an i64, but that's fine for now. Rounds away from 0.
+    /// exponent set to 0. Rounds away from 0.
     ///
     /// Returns `None` on overflow.
     pub fn round(&self, n_decimals: u8) -> Option<Decimal> {
-        match n_decimals as i32 - self.decimals as i32 + self.exponent as i32 {
+        let d = n_decimals as i32 - self.decimals as i32 + self.exponent as i32;
+        let mantissa = match d {
             // The mantissa is already correct for the requested number of decimals.
-            0 => {
-                let result = Decimal {
-                    mantissa: self.mantissa,
-                    exponent: 0,
-                    decimals: n_decimals,
-                };
-                Some(result)
-            }
+            0 => self.mantissa.clone(),
 
-            // We have to add additional decimals. The maximum power of 2 we can
-            // represent is 2^18, beyond that we will certainly get overflow.
-            d if d > 0 && d <= 18 => {
-                let f = 10_i64.pow(d as u32);
-                let result = Decimal {
-                    mantissa: self.mantissa.checked_mul(f)?,
-                    exponent: 0,
-                    decimals: n_decimals,
-                };
-                Some(result)
-            }
+            // We have to add additional decimals.
+            d if d > 0 => self.mantissa.checked_mul_pow10(d as u32)?,
+
+            // We have to remove so many decimals that none remain. Even half
+            // the factor 10^-d is more than the mantissa, so it rounds to zero.
+            d if -d > self.mantissa.num_digits() as i32 => Mantissa::Small(0),
 
             // d is negative, we have to remove decimals.
-            d if d < 0 && d >= -18 => {
-                let f = 10_i64.pow((-d) as u32);
+            d => {
                 // We want `round`, not `floor`, so add half the range.
                 // Negate if the mantissa is negative so we round away from 0.
-                let b = if self.mantissa > 0 { f / 2 } else { -f / 2 };
-                let result = Decimal {
-                    mantissa: self.mantissa.saturating_add(b) / f,
-                    exponent: 0,
-                    decimals: n_decimals,
-                };
-                Some(result)
-            }
-
-            // We have to remove so many decimals that none remain. In other
-            // words, this factor f that we compute in the branch above, it
-            // doesn't fit in an i64, which means that if we divide the mantissa
-            // by it, we get zero.
-            d if d < -18 => {
-                let result = Decimal {
-                    mantissa: 0,
-                    exponent: 0,
-                    decimals: n_decimals,
-                };
-                Some(result)
+                let f = Mantissa::Small(1).checked_mul_pow10((-d) as u32)?;
+                let half = Mantissa::Small(5 * self.mantissa.signum());
+                let b = half.checked_mul_pow10((-d - 1) as u32)?;
+                self.mantissa.checked_add(&b)?.div_rem(&f).0
             }
+        };
+        let result = Decimal {
+            mantissa,
+            exponent: 0,
+            decimals: n_decimals,
+        };
+        Some(result)
+    }
 
-            d => {
-                // The factor f = 10^d does not fit in an i64, so we would
-                // overflow the mantissa.
-                debug_assert!(d > 18, "All other branches are covered.");
-                None
-            }
-        }
+    /// Whether the number formats without decimal point or exponent.
+    pub fn is_integer_literal(&self) -> bool {
+        self.decimals == 0 && self.exponent == 0
     }
 
     /// Convert to a float. For many decimals this will be a lossy operation.
     pub fn to_f64_lossy(&self) -> f64 {
-        let n = self.mantissa as f64;
+        let n = self.mantissa.to_f64_lossy();
         let exp = 10.0_f64.powi(self.exponent as i32 - self.decimals as i32);
         n * exp
     }
 
-    /// Extract an integer, if the number is an integer.
+    /// Extract an integer, if the number is an integer that fits in an `i64`.
     ///
     /// This cares only about numeric integers, not about formatting. For
     /// example, all of `1`, `0.1e1`, and `1.00` would return `Some(1)`.
     pub fn to_i64(&self) -> Option<i64> {
+        self.to_integer()?.to_i64()
+    }
+
+    /// Extract the integer value as mantissa, if the number is an integer.
+    ///
+    /// Like [`Decimal::to_i64`], but for integers of arbitrary size.
+    pub fn to_integer(&self) -> Option<Mantissa> {
         match self.exponent as i32 - self.decimals as i32 {
-            0 => Some(self.mantissa),
-            exp if exp > 0 => {
-                let f = 10_i64.checked_pow(exp as u32)?;
-                self.mantissa.checked_mul(f)
-            }
+            0 => Some(self.mantissa.clone()),
+            exp if exp > 0 => self.mantissa.checked_mul_pow10(exp as u32),
+            _ if self.mantissa.is_zero() => Some(Mantissa::Small(0)),
             exp => {
                 // If we get to this branch, `exp < 0`, it's a case of e.g.
                 // 1.00 == 100e-2, which is numerically an integer.
-                let f = 10_i64.checked_pow((-exp) as u32)?;
-                let n = self.mantissa / f;
-                if n * f == self.mantissa {
-                    Some(n)
-                } else {
-                    None
+                let f = Mantissa::Small(1).checked_mul_pow10((-exp) as u32)?;
+                match self.mantissa.div_rem(&f) {
+                    (n, r) if r.is_zero() => Some(n),
+                    _ => None,
                 }
             }
         }
@@ -401,56 +883,45 @@ impl Decimal {
     /// make their exponents equal.
     #[inline(always)]
     fn cmp_impl(&self, other: &Decimal) -> Ordering {
-        let m1 = self.mantissa;
-        let m2 = other.mantissa;
+        let m1 = &self.mantissa;
+        let m2 = &other.mantissa;
 
         let e1 = self.exponent as i32 - self.decimals as i32;
         let e2 = other.exponent as i32 - other.decimals as i32;
 
-        match e1 - e2 {
-            // If both exponents are the same, we can just compare the mantissas.
-            0 => m1.cmp(&m2),
-
-            // If the numbers lie on different sides of 0 on the number line,
-            // then we can just compare the mantissas, because scaling by the
-            // exponent does not change which side the numbers lie on.
-            _ if m1.signum() != m2.signum() => m1.cmp(&m2),
-
-            // Case where the first number has the greater exponent. To compare
-            // the two, we can look at the sign of their difference. The
-            // difference can be written as:
-            //
-            //  m1 * 10^(e2 + d) - m2 * 10^e2 = 10^e2 * (m1 * 10^d - m2)
-            //
-            // The factor in front doesn't change the sign, so we can ignore it,
-            // and compare the scaled m1 against m2.
-            d if d > 0 => match 10_i64
-                .checked_pow(d as u32)
-                .and_then(|factor| m1.checked_mul(factor))
-            {
-                Some(m1_scaled) => m1_scaled.cmp(&m2),
-                // We know m1 and m2 have the same sign, so in this case they
-                // are both non-negative. If m1 scaled to the same range as m2
-                // overflows, then it's greater than m2, so the difference is
-                // positive.
-                None if m1 >= 0 => Ordering::Greater,
-                // If m1 and m2 are both negative, the same logic applies, but
-                // the sign of the comparison flips.
-                None => Ordering::Less,
-            },
+        // If both exponents are the same, we can just compare the mantissas.
+        if e1 == e2 {
+            return m1.cmp(m2);
+        }
 
-            // Case where the second number has the greater exponent, symmetric
-            // with the case above.
-            d => {
-                match 10_i64
-                    .checked_pow(-d as u32)
-                    .and_then(|factor| m2.checked_mul(factor))
-                {
-                    Some(m2_scaled) => m1.cmp(&m2_scaled),
-                    None if m1 >= 0 => Ordering::Less,
-                    None => Ordering::Greater,
-                }
-            }
+        // If the numbers lie on different sides of 0 on the number line,
+        // then we can just compare the signs, because scaling by the exponent
+        // does not change which side the numbers lie on. The same holds when
+        // both numbers are zero.
+        let sign = m1.signum();
+        if sign != m2.signum() || sign == 0 {
+            return sign.cmp(&m2.signum());
+        }
+
+        // The numbers have the same sign. If their orders of magnitude differ,
+        // then the one with the greater magnitude is further away from zero.
+        let mag1 = m1.num_digits() as i32 + e1;
+        let mag2 = m2.num_digits() as i32 + e2;
+        if mag1 != mag2 {
+            return match sign {
+                1 => mag1.cmp(&mag2),
+                _ => mag2.cmp(&mag1),
+            };
+        }
+
+        // The orders of magnitude are equal, so we can scale the mantissa with
+        // the greater exponent to the same exponent as the other one. Because
+        // the magnitudes are equal, that does not take more digits than the
+        // other mantissa already has, so this does not overflow.
+        let msg = "Scaling to an equal magnitude does not overflow.";
+        match e1 - e2 {
+            d if d > 0 => m1.checked_mul_pow10(d as u32).expect(msg).cmp(m2),
+            d => m1.cmp(&m2.checked_mul_pow10(-d as u32).expect(msg)),
         }
     }
 }
@@ -477,7 +948,7 @@ impl PartialOrd for Decimal {
 
 #[cfg(test)]
 mod test {
-    use super::{Decimal, ParseResult};
+    use super::{Decimal, Mantissa, ParseResult, MAX_DIGITS};
     use std::cmp::Ordering;
 
     fn assert_parse_decimal(
@@ -487,12 +958,12 @@ mod test {
         expected_exponent: i16,
     ) {
         let result = Decimal::parse_str(num);
-        let is_ok = match result {
+        let is_ok = match &result {
             Some(ParseResult::Decimal(d)) => {
                 // Note, we explicitly check the mantissa and exponent here,
                 // we don't want equivalence (mathematical equality) of the
                 // decimals, we check the representation (structural equality).
-                d.mantissa == expected_mantissa
+                d.mantissa == Mantissa::from(expected_mantissa)
                     && d.decimals == expected_decimals
                     && d.exponent == expected_exponent
             }
@@ -568,7 +1039,7 @@ mod test {
         // Note, we compare the formatted value, not the parsed result, because
         // we want the formatting to match, we don't want just a numeric match!
         assert_eq!(
-            actual.map(|x| x.format()),
+            actual.as_ref().map(|x| x.format()),
             Some(res_str.to_string()),
             // coverage:off
             "Unexpected result for {lhs_str} ({lhs:?}) {op_str} {rhs_str} ({rhs:?}):\
@@ -582,7 +1053,7 @@ mod test {
     /// But also less readable, we don't make this `Decimal::new`.
     fn decimal(mantissa: i64, decimals: u8, exponent: i16) -> Decimal {
         Decimal {
-            mantissa,
+            mantissa: mantissa.into(),
             decimals,
             exponent,
         }
@@ -601,11 +1072,32 @@ mod test {
             Some(ParseResult::Int(i64::MAX))
         ));
         assert!(matches!(
-            Decimal::parse_str(&(i64::MAX as u64 + 1).to_string()),
-            None
+            Decimal::parse_str(&i64::MIN.to_string()),
+            Some(ParseResult::Int(i64::MIN))
         ));
     }
 
+    #[test]
+    fn decimal_parse_str_parses_big_int() {
+        // Integers that do not fit in an i64 parse as decimal, but without
+        // decimal point or exponent, so they still format as integer.
+        for num in [
+            "9223372036854775808",
+            "-9223372036854775809",
+            "340282366920938463463374607431768211455",
+        ] {
+            match Decimal::parse_str(num) {
+                Some(ParseResult::Decimal(d)) => {
+                    assert!(matches!(d.mantissa, Mantissa::Big(..)));
+                    assert_eq!(d.decimals, 0);
+                    assert_eq!(d.exponent, 0);
+                    assert_eq!(d.format(), num);
+                }
+                _ => panic!("Expected '{num}' to parse as big integer."),
+            }
+        }
+    }
+
     #[test]
     fn decimal_parse_str_parses_decimal() {
         assert_parse_decimal("0.0", 0, 1, 0);
@@ -615,30 +1107,24 @@ mod test {
     }
 
     #[test]
-    fn decimal_parse_str_parses_decimal_imprecise() {
+    fn decimal_parse_str_parses_decimal_beyond_i64() {
         // This magic number is the overflow point.
         assert_eq!(i64::MAX, 9223372036854775807);
 
-        // Without the switch to imprecise parsing, we'd overflow the i64
-        // mantissa, and we would not be able to parse this, but with the switch
-        // we can handle arbitrarily long decimals (but not in full precision).
-        // In the case below the overflow happens before the decimal point. We
-        // also test that rounding works as expected. Note that none of these
-        // have both the exponent and decimals at 0, if they did we would format
-        // as integer, but the inputs have a decimal point, so we should not
-        // silently turn them into ints. (Dropping the decimal point is
-        // acceptable in this weird case, but only because we add an exponent
-        // which then sets the number apart from an integer.)
-        assert_parse_decimal("9223372036854775807.0", 9223372036854775807, 1, 1);
-        assert_parse_decimal("9223372036854775808.0", 922337203685477581, 0, 1);
-        assert_parse_decimal("9223372036854775814.0", 922337203685477581, 0, 1);
-        assert_parse_decimal("9223372036854775815.0", 922337203685477582, 0, 1);
-
-        // And here it happens after the decimal point.
-        assert_parse_decimal("0.9223372036854775807", 9223372036854775807, 19, 0);
-        assert_parse_decimal("0.9223372036854775808", 922337203685477581, 18, 0);
-        assert_parse_decimal("0.9223372036854775814", 922337203685477581, 18, 0);
-        assert_parse_decimal("0.9223372036854775815", 922337203685477582, 18, 0);
+        // Mantissas that do not fit in an i64 fall back to a big integer, so
+        // we parse these exactly, both when the overflow happens before the
+        // decimal point, and after it.
+        for num in [
+            "9223372036854775807.0",
+            "9223372036854775808.0",
+            "-9223372036854775815.0",
+            "0.9223372036854775808",
+            "0.92233720368547758080000000000000000001",
+            "1.00000000000000000000000000000000000001e-100",
+        ] {
+            let d: Decimal = Decimal::parse_str(num).unwrap().into();
+            assert_eq!(d.format(), num);
+        }
     }
 
     #[test]
@@ -692,11 +1178,7 @@ mod test {
         for mantissa in (0..999).step_by(7) {
             for decimals in 0..20 {
                 for exponent in (-999..999).step_by(11) {
-                    let d1 = Decimal {
-                        mantissa,
-                        decimals,
-                        exponent,
-                    };
+                    let d1 = decimal(mantissa, decimals, exponent);
                     let s = d1.format();
                     match Decimal::parse_str(&s).unwrap() {
                         ParseResult::Int(..) => {
@@ -810,6 +1292,169 @@ mod test {
         assert_binop("1.000 / 20 == 0.050");
     }
 
+    #[test]
+    fn decimal_div_rem_and_div_mod() {
+        let check = |x: &str, y: &str, div_rem: (&str, &str), div_mod: (&str, &str)| {
+            let x: Decimal = Decimal::parse_str(x).unwrap().into();
+            let y: Decimal = Decimal::parse_str(y).unwrap().into();
+            let fmt = |(q, r): (Decimal, Decimal)| (q.format(), r.format());
+            let expected = |(q, r): (&str, &str)| (q.to_string(), r.to_string());
+            assert_eq!(x.checked_div_rem(&y).map(fmt), Some(expected(div_rem)));
+            assert_eq!(x.checked_div_mod(&y).map(fmt), Some(expected(div_mod)));
+        };
+        check("7", "2", ("3", "1"), ("3", "1"));
+        check("-7", "2", ("-3", "-1"), ("-4", "1"));
+        check("7", "-2", ("-3", "1"), ("-4", "-1"));
+        check("-7", "-2", ("3", "-1"), ("3", "-1"));
+        check("6", "-2", ("-3", "0"), ("-3", "0"));
+        check("7.5", "2", ("3", "1.5"), ("3", "1.5"));
+        check("-7.5", "2", ("-3", "-1.5"), ("-4", "0.5"));
+        check("7", "0.25", ("28", "0.00"), ("28", "0.00"));
+        check("1e3", "7", ("142", "6"), ("142", "6"));
+        check(
+            "18446744073709551617",
+            "4294967296",
+            ("4294967296", "1"),
+            ("4294967296", "1"),
+        );
+    }
+
+    #[test]
+    fn decimal_checked_pow() {
+        let pow = |x: &str, n: u64| {
+            let x: Decimal = Decimal::parse_str(x).unwrap().into();
+            x.checked_pow(n).map(|r| r.format())
+        };
+        assert_eq!(pow("2", 0), Some("1".to_string()));
+        assert_eq!(pow("0", 0), Some("1".to_string()));
+        assert_eq!(pow("2", 10), Some("1024".to_string()));
+        assert_eq!(pow("-3", 3), Some("-27".to_string()));
+        assert_eq!(pow("1.5", 2), Some("2.25".to_string()));
+        assert_eq!(pow("1e2", 3), Some("1e6".to_string()));
+        assert_eq!(pow("2", 64), Some("18446744073709551616".to_string()));
+        assert_eq!(pow("1", u64::MAX), Some("1".to_string()));
+        assert_eq!(pow("2", 5000), None);
+    }
+
+    #[test]
+    fn decimal_floor_ceil_abs() {
+        let f = |x: &str| {
+            let x: Decimal = Decimal::parse_str(x).unwrap().into();
+            let floor = x.floor().unwrap().format();
+            let ceil = x.ceil().unwrap().format();
+            (floor, ceil, x.abs().format())
+        };
+        let s = |a: &str, b: &str, c: &str| (a.to_string(), b.to_string(), c.to_string());
+        assert_eq!(f("2.5"), s("2", "3", "2.5"));
+        assert_eq!(f("-2.5"), s("-3", "-2", "2.5"));
+        assert_eq!(f("7"), s("7", "7", "7"));
+        assert_eq!(f("-0.001"), s("-1", "0", "0.001"));
+        assert_eq!(f("1.5e1"), s("1.5e1", "1.5e1", "1.5e1"));
+        assert_eq!(
+            f("-12345678901234567890.5"),
+            s(
+                "-12345678901234567891",
+                "-12345678901234567890",
+                "12345678901234567890.5"
+            )
+        );
+    }
+
+    #[test]
+    fn decimal_checked_div_rounded() {
+        let div = |x: &str, y: &str| {
+            let x: Decimal = Decimal::parse_str(x).unwrap().into();
+            let y: Decimal = Decimal::parse_str(y).unwrap().into();
+            x.checked_div_rounded(&y, 5).unwrap().format()
+        };
+        assert_eq!(div("1", "4"), "0.25");
+        assert_eq!(div("1", "3"), "0.33333");
+        assert_eq!(div("2", "3"), "0.66667");
+        assert_eq!(div("-2", "3"), "-0.66667");
+        assert_eq!(div("200000", "3"), "66667");
+        assert_eq!(div("2000000", "3"), "666670");
+        assert_eq!(div("9.99999", "1.000001"), "10.000");
+    }
+
+    #[test]
+    fn decimal_sqrt() {
+        let sqrt = |x: &str| {
+            let x: Decimal = Decimal::parse_str(x).unwrap().into();
+            x.sqrt(20).unwrap().format()
+        };
+        assert_eq!(sqrt("0"), "0");
+        assert_eq!(sqrt("1"), "1");
+        assert_eq!(sqrt("144"), "12");
+        assert_eq!(sqrt("2.25"), "1.5");
+        assert_eq!(sqrt("0.0100"), "0.10");
+        assert_eq!(sqrt("1e-4"), "0.01");
+        assert_eq!(sqrt("2"), "1.4142135623730950488");
+        assert_eq!(sqrt("1e10"), "100000");
+        assert_eq!(
+            sqrt("340282366920938463463374607431768211456"),
+            "18446744073709551616"
+        );
+        assert_eq!(sqrt("99"), "9.9498743710661995473");
+        // When the result does not fit without exponent, it should be in the
+        // same form as a literal, not a mantissa with trailing zeros.
+        assert_eq!(sqrt("1e32767"), "3.162277660168379332e16383");
+        assert_eq!(sqrt("1e32766"), "1e16383");
+        assert_eq!(sqrt("2e-32767"), "4.4721359549995793928e-16384");
+    }
+
+    #[test]
+    fn decimal_log2() {
+        let log2 = |x: &str| {
+            let x: Decimal = Decimal::parse_str(x).unwrap().into();
+            x.log2(14).unwrap().format()
+        };
+        assert_eq!(log2("1"), "0");
+        assert_eq!(log2("1024"), "10");
+        assert_eq!(log2("1024.00"), "10");
+        assert_eq!(log2("0.125"), "-3");
+        assert_eq!(log2("340282366920938463463374607431768211456"), "128");
+        assert_eq!(log2("3"), "1.5849625007212");
+        assert_eq!(log2("10"), "3.3219280948874");
+        assert_eq!(log2("0.1"), "-3.3219280948874");
+        assert_eq!(log2("1e300"), "996.57842846621");
+    }
+
+    #[test]
+    fn decimal_arithmetic_beyond_i64() {
+        assert_binop("9223372036854775807 + 1 == 9223372036854775808");
+        assert_binop("-9223372036854775807 - 2 == -9223372036854775809");
+        assert_binop("9223372036854775808 - 1 == 9223372036854775807");
+        assert_binop("4294967296 * 4294967296 == 18446744073709551616");
+        assert_binop("18446744073709551616 / 4294967296 == 4294967296");
+        assert_binop("1.5 * 100000000000000000000 == 150000000000000000000.0");
+        assert_binop("1 / 18446744073709551616 == 0.0000000000000000000542101086242752217003726400434970855712890625");
+        assert_binop("1e30 + 1 == 1000000000000000000000000000001");
+        assert_binop("0.1e30 - 0.1 == 99999999999999999999999999999.9");
+
+        // There is still a limit to the size of the mantissa.
+        let big: Decimal = Decimal::parse_str("1e999").unwrap().into();
+        let one = Decimal::from(1);
+        assert!(big.checked_add(&one).is_some());
+        assert!(big
+            .checked_mul(&Decimal::from(10))
+            .unwrap()
+            .checked_add(&one)
+            .is_none());
+        let max = big.checked_add(&one).unwrap();
+        assert_eq!(max.mantissa.num_digits(), MAX_DIGITS);
+        assert!(max.checked_mul(&max).is_none());
+    }
+
+    #[test]
+    fn decimal_ord_works_beyond_i64() {
+        assert_cmp("9223372036854775808.0 > 9223372036854775807.0");
+        assert_cmp("-9223372036854775808.0 > -9223372036854775809.0");
+        assert_cmp("92233720368547758080.0e-1 = 9223372036854775808.0");
+        assert_cmp("1.00000000000000000000000000001e10 > 1e10");
+        assert_cmp("0.999999999999999999999999999999e10 < 1e10");
+        assert_cmp("-0.999999999999999999999999999999e10 > -1e10");
+    }
+
     #[test]
     fn decimal_to_i64() {
         assert_eq!(decimal(1, 0, 0).to_i64(), Some(1));
@@ -819,6 +1464,32 @@ mod test {
         assert_eq!(decimal(1, 1, 1).to_i64(), Some(1));
         assert_eq!(decimal(1, 1, 0).to_i64(), None);
         assert_eq!(decimal(1, 0, 20).to_i64(), None);
+        assert_eq!(
+            decimal(1, 0, 20).to_integer().map(|m| m.to_string()),
+            Some("100000000000000000000".to_string())
+        );
+    }
+
+    #[test]
+    fn decimal_round_works() {
+        let round = |num: &str, n: u8| {
+            let d: Decimal = Decimal::parse_str(num).unwrap().into();
+            d.round(n).map(|r| r.format())
+        };
+        assert_eq!(round("1.25", 1), Some("1.3".to_string()));
+        assert_eq!(round("-1.25", 1), Some("-1.3".to_string()));
+        assert_eq!(round("1.24", 0), Some("1".to_string()));
+        assert_eq!(round("0.5", 0), Some("1".to_string()));
+        assert_eq!(round("0.05", 0), Some("0".to_string()));
+        assert_eq!(round("1e-100", 2), Some("0.00".to_string()));
+        assert_eq!(
+            round("1e20", 1),
+            Some("100000000000000000000.0".to_string())
+        );
+        assert_eq!(
+            round("123456789012345678901234567890.5", 0),
+            Some("123456789012345678901234567891".to_string())
+        );
     }
 
     #[test]
@@ -0,0 +1,259 @@
+// RCL -- A reasonable configuration language.
+// Copyright 2025 Ruud van Asseldonk
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// A copy of the License has been included in the root of the repository.
+
+//! Base64, hex, and percent encoding of bytes.
+//!
+//! * Base64 uses the standard alphabet from RFC 4648 with `=` padding. The
+//!   decoder also accepts input without padding.
+//! * Hex encoding produces lowercase digits, the decoder accepts both cases.
+//! * Percent encoding follows RFC 3986: every byte except the unreserved
+//!   characters `A-Z`, `a-z`, `0-9`, `-`, `.`, `_`, and `~` is encoded. The
+//!   decoder does not treat `+` as a space, that is a convention of HTML forms.
+
+/// An error in the input to a decoder.
+#[derive(Debug, Eq, PartialEq)]
+pub struct DecodeError {
+    pub message: &'static str,
+
+    /// Byte offset in the input where the problem is.
+    pub offset: usize,
+}
+
+pub type Result<T> = std::result::Result<T, DecodeError>;
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+/// Encode bytes as base64, with padding.
+pub fn base64_encode(bytes: &[u8]) -> String {
+    let mut result = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                let sextet = (n >> (18 - 6 * i)) & 0x3f;
+                result.push(BASE64_ALPHABET[sextet as usize] as char);
+            } else {
+                result.push('=');
+            }
+        }
+    }
+    result
+}
+
+/// Decode base64, with or without padding.
+pub fn base64_decode(input: &str) -> Result<Vec<u8>> {
+    let bytes = input.as_bytes();
+    let data_len = bytes.len() - bytes.iter().rev().take_while(|b| **b == b'=').count();
+    let n_padding = bytes.len() - data_len;
+
+    if n_padding > 0 && bytes.len() % 4 != 0 {
+        return Err(DecodeError {
+            message: "Padded base64 must have a length that is a multiple of 4.",
+            offset: bytes.len(),
+        });
+    }
+    if n_padding > 2 || data_len % 4 == 1 {
+        return Err(DecodeError {
+            message: "Base64 input has an invalid length.",
+            offset: data_len,
+        });
+    }
+
+    let mut result = Vec::with_capacity(data_len / 4 * 3 + 2);
+    let mut acc: u32 = 0;
+    let mut n_bits = 0;
+    for (i, &ch) in bytes[..data_len].iter().enumerate() {
+        let sextet = match ch {
+            b'A'..=b'Z' => ch - b'A',
+            b'a'..=b'z' => ch - b'a' + 26,
+            b'0'..=b'9' => ch - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => {
+                return Err(DecodeError {
+                    message: "Invalid character in base64.",
+                    offset: i,
+                })
+            }
+        };
+        acc = (acc << 6) | sextet as u32;
+        n_bits += 6;
+        if n_bits >= 8 {
+            n_bits -= 8;
+            result.push((acc >> n_bits) as u8);
+            acc &= (1 << n_bits) - 1;
+        }
+    }
+    Ok(result)
+}
+
+/// Encode bytes as lowercase hexadecimal.
+pub fn hex_encode(bytes: &[u8]) -> String {
+    let mut result = String::with_capacity(bytes.len() * 2);
+    for b in bytes {
+        result.push(HEX_DIGITS[(b >> 4) as usize] as char);
+        result.push(HEX_DIGITS[(b & 0xf) as usize] as char);
+    }
+    result
+}
+
+/// Return the value of a hexadecimal digit.
+fn hex_digit(ch: u8) -> Option<u8> {
+    match ch {
+        b'0'..=b'9' => Some(ch - b'0'),
+        b'a'..=b'f' => Some(ch - b'a' + 10),
+        b'A'..=b'F' => Some(ch - b'A' + 10),
+        _ => None,
+    }
+}
+
+/// Decode hexadecimal, in upper or lower case.
+pub fn hex_decode(input: &str) -> Result<Vec<u8>> {
+    let bytes = input.as_bytes();
+    if bytes.len() % 2 != 0 {
+        return Err(DecodeError {
+            message: "Hex input must have an even number of digits.",
+            offset: bytes.len(),
+        });
+    }
+    let mut result = Vec::with_capacity(bytes.len() / 2);
+    for (i, pair) in bytes.chunks_exact(2).enumerate() {
+        for (j, ch) in pair.iter().enumerate() {
+            if hex_digit(*ch).is_none() {
+                return Err(DecodeError {
+                    message: "Invalid hex digit.",
+                    offset: 2 * i + j,
+                });
+            }
+        }
+        let hi = hex_digit(pair[0]).expect("Checked above.");
+        let lo = hex_digit(pair[1]).expect("Checked above.");
+        result.push(hi << 4 | lo);
+    }
+    Ok(result)
+}
+
+/// Percent-encode all bytes except the unreserved characters.
+pub fn url_encode(bytes: &[u8]) -> String {
+    let mut result = String::with_capacity(bytes.len());
+    for &b in bytes {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                result.push(b as char)
+            }
+            _ => {
+                result.push('%');
+                result.push(HEX_DIGITS[(b >> 4) as usize].to_ascii_uppercase() as char);
+                result.push(HEX_DIGITS[(b & 0xf) as usize].to_ascii_uppercase() as char);
+            }
+        }
+    }
+    result
+}
+
+/// Decode percent-encoded input.
+pub fn url_decode(input: &str) -> Result<Vec<u8>> {
+    let bytes = input.as_bytes();
+    let mut result = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] != b'%' {
+            result.push(bytes[i]);
+            i += 1;
+            continue;
+        }
+        let hi = bytes.get(i + 1).and_then(|ch| hex_digit(*ch));
+        let lo = bytes.get(i + 2).and_then(|ch| hex_digit(*ch));
+        match (hi, lo) {
+            (Some(hi), Some(lo)) => result.push(hi << 4 | lo),
+            _ => {
+                return Err(DecodeError {
+                    message: "Expected two hex digits after '%'.",
+                    offset: i,
+                })
+            }
+        }
+        i += 3;
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        base64_decode, base64_encode, hex_decode, hex_encode, url_decode, url_encode, DecodeError,
+    };
+
+    #[test]
+    fn base64_encode_matches_rfc_4648_test_vectors() {
+        let cases = [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ];
+        for (plain, encoded) in cases {
+            assert_eq!(base64_encode(plain.as_bytes()), encoded);
+            assert_eq!(base64_decode(encoded).unwrap(), plain.as_bytes());
+        }
+        assert_eq!(base64_encode(&[0xfb, 0xff]), "+/8=");
+    }
+
+    #[test]
+    fn base64_decode_accepts_missing_padding() {
+        assert_eq!(base64_decode("Zg").unwrap(), b"f");
+        assert_eq!(base64_decode("Zm8").unwrap(), b"fo");
+    }
+
+    #[test]
+    fn base64_decode_rejects_invalid_input() {
+        assert_eq!(
+            base64_decode("Zm9v!A=="),
+            Err(DecodeError {
+                message: "Invalid character in base64.",
+                offset: 4,
+            })
+        );
+        assert!(base64_decode("Z").is_err());
+        assert!(base64_decode("Zg=").is_err());
+        assert!(base64_decode("Z===").is_err());
+    }
+
+    #[test]
+    fn hex_round_trips() {
+        assert_eq!(hex_encode(b"\x00\x7f\xff"), "007fff");
+        assert_eq!(hex_decode("007fFF").unwrap(), b"\x00\x7f\xff");
+        assert!(hex_decode("abc").is_err());
+        assert_eq!(
+            hex_decode("0g"),
+            Err(DecodeError {
+                message: "Invalid hex digit.",
+                offset: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn url_encode_keeps_only_unreserved_characters() {
+        assert_eq!(url_encode(b"a-z_0.9~"), "a-z_0.9~");
+        assert_eq!(url_encode("a b&c=d/é".as_bytes()), "a%20b%26c%3Dd%2F%C3%A9");
+        assert_eq!(url_decode("a%20b%2fc+d").unwrap(), b"a b/c+d");
+        assert!(url_decode("100%").is_err());
+        assert!(url_decode("%zz").is_err());
+    }
+}
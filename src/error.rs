@@ -17,7 +17,7 @@ pub type Result<T> = std::result::Result<T, Box<Error>>;
 
 /// Element of a path through a value.
 // TODO: Record the value itself as well, so we can *show* the thing that's wrong.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum PathElement {
     Key(Value),
     Index(usize),
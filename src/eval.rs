@@ -8,13 +8,13 @@
 //! Evaluation turns ASTs into values.
 
 use std::collections::HashMap;
-use std::collections::{BTreeMap, BTreeSet};
 use std::rc::Rc;
 
 use crate::ast::{BinOp, CallArg, Expr, FormatFragment, Seq, Stmt, UnOp, Yield};
 use crate::error::{Error, IntoError, PathElement, Result};
 use crate::fmt_rcl::{self, format_rcl};
 use crate::loader::Loader;
+use crate::ordered::{OrderedMap, OrderedSet};
 use crate::pprint::{concat, indent, Doc};
 use crate::runtime::{
     self, BuiltinFunction, BuiltinMethod, Env, Function, FunctionCall, MethodCall, MethodInstance,
@@ -316,7 +316,7 @@ impl<'a> Evaluator<'a> {
             }
 
             Expr::SetLit { open, elements } => {
-                let mut out = BTreeSet::new();
+                let mut out = OrderedSet::new();
                 self.inc_eval_depth(*open)?;
                 for seq in elements {
                     self.eval_seq(env, seq, &mut |v| _ = out.insert(v), &mut |_, _| {
@@ -328,7 +328,7 @@ impl<'a> Evaluator<'a> {
             }
 
             Expr::DictLit { open, elements } => {
-                let mut out = BTreeMap::new();
+                let mut out = OrderedMap::new();
                 self.inc_eval_depth(*open)?;
                 for seq in elements {
                     self.eval_seq(
@@ -346,7 +346,7 @@ impl<'a> Evaluator<'a> {
 
             Expr::BoolLit(b) => Ok(Value::Bool(*b)),
 
-            Expr::NumberLit(d) => Ok(Value::Number(*d)),
+            Expr::NumberLit(d) => Ok(Value::Number(d.clone())),
 
             Expr::StringLit(s) => Ok(Value::String(s.clone())),
 
@@ -391,28 +391,68 @@ impl<'a> Evaluator<'a> {
                 let field_name_value = Value::String(field_name.0.clone());
 
                 let builtin = match (&inner, field_name.as_ref()) {
+                    (Value::String(_), "bytes") => Some(&stdlib::STRING_BYTES),
+                    (Value::String(_), "captures") => Some(&stdlib::STRING_CAPTURES),
                     (Value::String(_), "chars") => Some(&stdlib::STRING_CHARS),
                     (Value::String(_), "contains") => Some(&stdlib::STRING_CONTAINS),
                     (Value::String(_), "ends_with") => Some(&stdlib::STRING_ENDS_WITH),
+                    (Value::String(_), "find_all") => Some(&stdlib::STRING_FIND_ALL),
+                    (Value::String(_), "index_of") => Some(&stdlib::STRING_INDEX_OF),
+                    (Value::String(_), "last_index_of") => Some(&stdlib::STRING_LAST_INDEX_OF),
                     (Value::String(_), "len") => Some(&stdlib::STRING_LEN),
+                    (Value::String(_), "matches") => Some(&stdlib::STRING_MATCHES),
+                    (Value::String(_), "pad_end") => Some(&stdlib::STRING_PAD_END),
+                    (Value::String(_), "pad_start") => Some(&stdlib::STRING_PAD_START),
                     (Value::String(_), "parse_int") => Some(&stdlib::STRING_PARSE_INT),
                     (Value::String(_), "parse_number") => Some(&stdlib::STRING_PARSE_NUMBER),
                     (Value::String(_), "remove_prefix") => Some(&stdlib::STRING_REMOVE_PREFIX),
                     (Value::String(_), "remove_suffix") => Some(&stdlib::STRING_REMOVE_SUFFIX),
+                    (Value::String(_), "repeat") => Some(&stdlib::STRING_REPEAT),
                     (Value::String(_), "replace") => Some(&stdlib::STRING_REPLACE),
+                    (Value::String(_), "replace_regex") => Some(&stdlib::STRING_REPLACE_REGEX),
+                    (Value::String(_), "slice") => Some(&stdlib::STRING_SLICE),
                     (Value::String(_), "split") => Some(&stdlib::STRING_SPLIT),
+                    (Value::String(_), "split_at") => Some(&stdlib::STRING_SPLIT_AT),
                     (Value::String(_), "split_lines") => Some(&stdlib::STRING_SPLIT_LINES),
                     (Value::String(_), "starts_with") => Some(&stdlib::STRING_STARTS_WITH),
+                    (Value::String(_), "to_camel_case") => Some(&stdlib::STRING_TO_CAMEL_CASE),
+                    (Value::String(_), "to_kebab_case") => Some(&stdlib::STRING_TO_KEBAB_CASE),
                     (Value::String(_), "to_lowercase") => Some(&stdlib::STRING_TO_LOWERCASE),
+                    (Value::String(_), "to_snake_case") => Some(&stdlib::STRING_TO_SNAKE_CASE),
+                    (Value::String(_), "to_title_case") => Some(&stdlib::STRING_TO_TITLE_CASE),
                     (Value::String(_), "to_uppercase") => Some(&stdlib::STRING_TO_UPPERCASE),
-
+                    (Value::String(_), "trim") => Some(&stdlib::STRING_TRIM),
+                    (Value::String(_), "trim_end") => Some(&stdlib::STRING_TRIM_END),
+                    (Value::String(_), "trim_start") => Some(&stdlib::STRING_TRIM_START),
+
+                    (Value::Number(_), "abs") => Some(&stdlib::NUMBER_ABS),
+                    (Value::Number(_), "ceil") => Some(&stdlib::NUMBER_CEIL),
+                    (Value::Number(_), "clamp") => Some(&stdlib::NUMBER_CLAMP),
+                    (Value::Number(_), "div") => Some(&stdlib::NUMBER_DIV),
+                    (Value::Number(_), "floor") => Some(&stdlib::NUMBER_FLOOR),
+                    (Value::Number(_), "log2") => Some(&stdlib::NUMBER_LOG2),
+                    (Value::Number(_), "max") => Some(&stdlib::NUMBER_MAX),
+                    (Value::Number(_), "min") => Some(&stdlib::NUMBER_MIN),
+                    (Value::Number(_), "mod") => Some(&stdlib::NUMBER_MOD),
+                    (Value::Number(_), "pow") => Some(&stdlib::NUMBER_POW),
+                    (Value::Number(_), "rem") => Some(&stdlib::NUMBER_REM),
                     (Value::Number(_), "round") => Some(&stdlib::NUMBER_ROUND),
+                    (Value::Number(_), "sqrt") => Some(&stdlib::NUMBER_SQRT),
 
                     (Value::Dict(_), "contains") => Some(&stdlib::DICT_CONTAINS),
                     (Value::Dict(_), "except") => Some(&stdlib::DICT_EXCEPT),
+                    (Value::Dict(_), "filter") => Some(&stdlib::DICT_FILTER),
                     (Value::Dict(_), "get") => Some(&stdlib::DICT_GET),
+                    (Value::Dict(_), "invert") => Some(&stdlib::DICT_INVERT),
                     (Value::Dict(_), "keys") => Some(&stdlib::DICT_KEYS),
                     (Value::Dict(_), "len") => Some(&stdlib::DICT_LEN),
+                    (Value::Dict(_), "map_keys") => Some(&stdlib::DICT_MAP_KEYS),
+                    (Value::Dict(_), "map_values") => Some(&stdlib::DICT_MAP_VALUES),
+                    (Value::Dict(_), "merge_deep") => Some(&stdlib::DICT_MERGE_DEEP),
+                    (Value::Dict(_), "merge_deep_with") => Some(&stdlib::DICT_MERGE_DEEP_WITH),
+                    (Value::Dict(_), "merge_with") => Some(&stdlib::DICT_MERGE_WITH),
+                    (Value::Dict(_), "only") => Some(&stdlib::DICT_ONLY),
+                    (Value::Dict(_), "to_list") => Some(&stdlib::DICT_TO_LIST),
                     (Value::Dict(_), "values") => Some(&stdlib::DICT_VALUES),
                     (Value::Dict(fields), _field_name) => {
                         // If it wasn't a builtin, look for a key in the dict.
@@ -437,36 +477,68 @@ impl<'a> Evaluator<'a> {
 
                     (Value::List(_), "all") => Some(&stdlib::LIST_ALL),
                     (Value::List(_), "any") => Some(&stdlib::LIST_ANY),
+                    (Value::List(_), "average") => Some(&stdlib::LIST_AVERAGE),
+                    (Value::List(_), "chunks") => Some(&stdlib::LIST_CHUNKS),
                     (Value::List(_), "contains") => Some(&stdlib::LIST_CONTAINS),
+                    (Value::List(_), "count") => Some(&stdlib::LIST_COUNT),
+                    (Value::List(_), "dedup") => Some(&stdlib::LIST_DEDUP),
                     (Value::List(_), "enumerate") => Some(&stdlib::LIST_ENUMERATE),
                     (Value::List(_), "filter") => Some(&stdlib::LIST_FILTER),
+                    (Value::List(_), "find") => Some(&stdlib::LIST_FIND),
+                    (Value::List(_), "find_index") => Some(&stdlib::LIST_FIND_INDEX),
+                    (Value::List(_), "first") => Some(&stdlib::LIST_FIRST),
                     (Value::List(_), "flat_map") => Some(&stdlib::LIST_FLAT_MAP),
+                    (Value::List(_), "flatten") => Some(&stdlib::LIST_FLATTEN),
                     (Value::List(_), "fold") => Some(&stdlib::LIST_FOLD),
                     (Value::List(_), "group_by") => Some(&stdlib::LIST_GROUP_BY),
+                    (Value::List(_), "index_of") => Some(&stdlib::LIST_INDEX_OF),
                     (Value::List(_), "join") => Some(&stdlib::LIST_JOIN),
                     (Value::List(_), "key_by") => Some(&stdlib::LIST_KEY_BY),
+                    (Value::List(_), "last") => Some(&stdlib::LIST_LAST),
                     (Value::List(_), "len") => Some(&stdlib::LIST_LEN),
                     (Value::List(_), "map") => Some(&stdlib::LIST_MAP),
+                    (Value::List(_), "max") => Some(&stdlib::LIST_MAX),
+                    (Value::List(_), "max_by") => Some(&stdlib::LIST_MAX_BY),
+                    (Value::List(_), "min") => Some(&stdlib::LIST_MIN),
+                    (Value::List(_), "min_by") => Some(&stdlib::LIST_MIN_BY),
+                    (Value::List(_), "partition") => Some(&stdlib::LIST_PARTITION),
                     (Value::List(_), "reverse") => Some(&stdlib::LIST_REVERSE),
+                    (Value::List(_), "skip") => Some(&stdlib::LIST_SKIP),
+                    (Value::List(_), "slice") => Some(&stdlib::LIST_SLICE),
                     (Value::List(_), "sort") => Some(&stdlib::LIST_SORT),
                     (Value::List(_), "sort_by") => Some(&stdlib::LIST_SORT_BY),
                     (Value::List(_), "sum") => Some(&stdlib::LIST_SUM),
+                    (Value::List(_), "take") => Some(&stdlib::LIST_TAKE),
+                    (Value::List(_), "take_while") => Some(&stdlib::LIST_TAKE_WHILE),
                     (Value::List(_), "to_set_dedup") => Some(&stdlib::LIST_TO_SET_DEDUP),
                     (Value::List(_), "to_set_unique") => Some(&stdlib::LIST_TO_SET_UNIQUE),
+                    (Value::List(_), "unique_by") => Some(&stdlib::LIST_UNIQUE_BY),
+                    (Value::List(_), "windows") => Some(&stdlib::LIST_WINDOWS),
+                    (Value::List(_), "zip") => Some(&stdlib::LIST_ZIP),
 
                     (Value::Set(_), "all") => Some(&stdlib::SET_ALL),
                     (Value::Set(_), "any") => Some(&stdlib::SET_ANY),
+                    (Value::Set(_), "average") => Some(&stdlib::SET_AVERAGE),
                     (Value::Set(_), "contains") => Some(&stdlib::SET_CONTAINS),
                     (Value::Set(_), "except") => Some(&stdlib::SET_EXCEPT),
                     (Value::Set(_), "filter") => Some(&stdlib::SET_FILTER),
                     (Value::Set(_), "flat_map_dedup") => Some(&stdlib::SET_FLAT_MAP_DEDUP),
                     (Value::Set(_), "group_by") => Some(&stdlib::SET_GROUP_BY),
+                    (Value::Set(_), "intersect") => Some(&stdlib::SET_INTERSECT),
+                    (Value::Set(_), "is_disjoint") => Some(&stdlib::SET_IS_DISJOINT),
+                    (Value::Set(_), "is_subset_of") => Some(&stdlib::SET_IS_SUBSET_OF),
+                    (Value::Set(_), "is_superset_of") => Some(&stdlib::SET_IS_SUPERSET_OF),
                     (Value::Set(_), "key_by") => Some(&stdlib::SET_KEY_BY),
                     (Value::Set(_), "len") => Some(&stdlib::SET_LEN),
                     (Value::Set(_), "map_dedup") => Some(&stdlib::SET_MAP_DEDUP),
+                    (Value::Set(_), "max") => Some(&stdlib::SET_MAX),
+                    (Value::Set(_), "min") => Some(&stdlib::SET_MIN),
                     (Value::Set(_), "sort") => Some(&stdlib::SET_SORT),
                     (Value::Set(_), "sort_by") => Some(&stdlib::SET_SORT_BY),
                     (Value::Set(_), "sum") => Some(&stdlib::SET_SUM),
+                    (Value::Set(_), "symmetric_difference") => {
+                        Some(&stdlib::SET_SYMMETRIC_DIFFERENCE)
+                    }
                     (Value::Set(_), "to_list") => Some(&stdlib::SET_TO_LIST),
                     (Value::Set(_), "transitive_closure") => Some(&stdlib::SET_TRANSITIVE_CLOSURE),
 
@@ -805,7 +877,7 @@ impl<'a> Evaluator<'a> {
 
     fn eval_index_dict(
         &mut self,
-        dict: &BTreeMap<Value, Value>,
+        dict: &OrderedMap<Value, Value>,
         dict_span: Span,
         index: Value,
         index_span: Span,
@@ -855,7 +927,8 @@ impl<'a> Evaluator<'a> {
                 Ok(Value::Dict(Rc::new(result)))
             }
             (BinOp::Union, Value::Set(xs), Value::Set(ys)) => {
-                let result = xs.union(ys.as_ref()).cloned().collect();
+                let mut result = (*xs).clone();
+                result.extend(ys.iter().cloned());
                 Ok(Value::Set(Rc::new(result)))
             }
             (BinOp::Union, Value::Set(xs), Value::List(ys)) => {
@@ -907,7 +980,7 @@ impl<'a> Evaluator<'a> {
                 }
             },
             (BinOp::Div, Value::Number(x), Value::Number(y)) => {
-                if y.mantissa == 0 {
+                if y.is_zero() {
                     op_span.error("Division by zero.").err()
                 } else {
                     match x.checked_div_exact(&y) {
@@ -0,0 +1,190 @@
+// RCL -- A reasonable configuration language.
+// Copyright 2025 Ruud van Asseldonk
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// A copy of the License has been included in the root of the repository.
+
+//! Encoder that serializes values as CBOR.
+//!
+//! See <https://www.rfc-editor.org/rfc/rfc8949.html> for the specification.
+//! Numbers that are integers and fit in an `i64` become CBOR integers, larger
+//! integers become bignums (tags 2 and 3). Other numbers become decimal
+//! fractions (tag 4), which represent the decimal exactly, unlike a float.
+//! Sets become arrays, like in json.
+
+use crate::bigint::BigInt;
+use crate::decimal::{Decimal, Mantissa};
+use crate::error::{IntoError, PathElement, Result};
+use crate::runtime::Value;
+use crate::source::Span;
+
+/// Major type 0, an unsigned integer.
+const MAJOR_UINT: u8 = 0;
+/// Major type 1, a negative integer `-1 - n`.
+const MAJOR_NINT: u8 = 1;
+/// Major type 2, a byte string.
+const MAJOR_BYTES: u8 = 2;
+/// Major type 3, a UTF-8 text string.
+const MAJOR_TEXT: u8 = 3;
+/// Major type 4, an array of data items.
+const MAJOR_ARRAY: u8 = 4;
+/// Major type 5, a map of pairs of data items.
+const MAJOR_MAP: u8 = 5;
+/// Major type 6, a tagged data item.
+const MAJOR_TAG: u8 = 6;
+
+/// The tag for an unsigned bignum, stored as big-endian byte string.
+const TAG_POSITIVE_BIGNUM: u64 = 2;
+/// The tag for a negative bignum `-1 - n`, stored as big-endian byte string.
+const TAG_NEGATIVE_BIGNUM: u64 = 3;
+/// The tag for a decimal fraction `[exponent, mantissa]`.
+const TAG_DECIMAL_FRACTION: u64 = 4;
+
+const SIMPLE_FALSE: u8 = 0xf4;
+const SIMPLE_TRUE: u8 = 0xf5;
+const SIMPLE_NULL: u8 = 0xf6;
+
+/// Encode a value as CBOR.
+pub fn format_cbor(caller: Span, v: &Value) -> Result<Vec<u8>> {
+    let mut encoder = Encoder::new(caller);
+    encoder.value(v)?;
+    Ok(encoder.out)
+}
+
+/// Helper for encoding values as CBOR.
+///
+/// The encoder tracks the path in the value that we are encoding from, such
+/// that we can report the location of an error, in case an error occurs.
+struct Encoder {
+    /// The source location where CBOR encoding was triggered from.
+    caller: Span,
+
+    /// Where we currently are in the value to be encoded.
+    path: Vec<PathElement>,
+
+    /// The encoded output so far.
+    out: Vec<u8>,
+}
+
+impl Encoder {
+    pub fn new(caller: Span) -> Encoder {
+        Encoder {
+            caller,
+            path: Vec::new(),
+            out: Vec::new(),
+        }
+    }
+
+    /// Report an error at the current value path.
+    fn error<T>(&mut self, message: &'static str) -> Result<T> {
+        // Steal the path from the encoder and move it into the error. We have
+        // to leave an empty path in its place. This is fine, because returning
+        // the error prevents further encoding.
+        let mut path = Vec::new();
+        std::mem::swap(&mut self.path, &mut path);
+        self.caller.error(message).with_path(path).err()
+    }
+
+    /// Write the initial byte for the major type, and the argument `n`.
+    ///
+    /// The argument is encoded in the shortest form, as required for
+    /// deterministically encoded CBOR.
+    fn head(&mut self, major: u8, n: u64) {
+        let major = major << 5;
+        if n < 24 {
+            self.out.push(major | n as u8);
+        } else if n <= u8::MAX as u64 {
+            self.out.push(major | 24);
+            self.out.push(n as u8);
+        } else if n <= u16::MAX as u64 {
+            self.out.push(major | 25);
+            self.out.extend_from_slice(&(n as u16).to_be_bytes());
+        } else if n <= u32::MAX as u64 {
+            self.out.push(major | 26);
+            self.out.extend_from_slice(&(n as u32).to_be_bytes());
+        } else {
+            self.out.push(major | 27);
+            self.out.extend_from_slice(&n.to_be_bytes());
+        }
+    }
+
+    fn int(&mut self, n: i64) {
+        if n >= 0 {
+            self.head(MAJOR_UINT, n as u64);
+        } else {
+            // For negative n, `!n` is `-1 - n`, which is what CBOR stores.
+            self.head(MAJOR_NINT, !n as u64);
+        }
+    }
+
+    /// Encode an integer of arbitrary size, as bignum if needed.
+    fn integer(&mut self, m: &Mantissa) {
+        let (tag, n) = match m {
+            Mantissa::Small(n) => return self.int(*n),
+            Mantissa::Big(n) if n.signum() > 0 => (TAG_POSITIVE_BIGNUM, n.as_ref().clone()),
+            Mantissa::Big(n) => (TAG_NEGATIVE_BIGNUM, n.neg().sub(&BigInt::from(1))),
+        };
+        let bytes = n.magnitude_to_bytes_be();
+        self.head(MAJOR_TAG, tag);
+        self.head(MAJOR_BYTES, bytes.len() as u64);
+        self.out.extend_from_slice(&bytes);
+    }
+
+    fn number(&mut self, d: &Decimal) {
+        match d.to_integer() {
+            Some(n) => self.integer(&n),
+            None => {
+                let exponent = d.exponent as i64 - d.decimals as i64;
+                self.head(MAJOR_TAG, TAG_DECIMAL_FRACTION);
+                self.head(MAJOR_ARRAY, 2);
+                self.int(exponent);
+                self.integer(&d.mantissa);
+            }
+        }
+    }
+
+    fn string(&mut self, s: &str) {
+        self.head(MAJOR_TEXT, s.len() as u64);
+        self.out.extend_from_slice(s.as_bytes());
+    }
+
+    fn array<'a>(&mut self, len: usize, vs: impl Iterator<Item = &'a Value>) -> Result<()> {
+        self.head(MAJOR_ARRAY, len as u64);
+        for (i, v) in vs.enumerate() {
+            self.path.push(PathElement::Index(i));
+            self.value(v)?;
+            self.path.pop().expect("Push and pop are balanced.");
+        }
+        Ok(())
+    }
+
+    fn value(&mut self, v: &Value) -> Result<()> {
+        match v {
+            Value::Null => self.out.push(SIMPLE_NULL),
+            Value::Bool(false) => self.out.push(SIMPLE_FALSE),
+            Value::Bool(true) => self.out.push(SIMPLE_TRUE),
+            Value::Number(d) => self.number(d),
+            Value::String(s) => self.string(s),
+            Value::List(vs) => self.array(vs.len(), vs.iter())?,
+            Value::Set(vs) => self.array(vs.len(), vs.iter())?,
+            Value::Dict(kv) => {
+                self.head(MAJOR_MAP, kv.len() as u64);
+                for (k, v) in kv.iter() {
+                    self.path.push(PathElement::Key(k.clone()));
+                    self.value(k)?;
+                    self.value(v)?;
+                    self.path.pop().expect("Push and pop are balanced.");
+                }
+            }
+            Value::Function(..) => return self.error("Functions cannot be exported as CBOR."),
+            Value::BuiltinFunction(..) => {
+                return self.error("Functions cannot be exported as CBOR.")
+            }
+            Value::BuiltinMethod { .. } => {
+                return self.error("Methods cannot be exported as CBOR.")
+            }
+        }
+        Ok(())
+    }
+}
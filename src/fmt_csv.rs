@@ -0,0 +1,227 @@
+// RCL -- A reasonable configuration language.
+// Copyright 2025 Ruud van Asseldonk
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// A copy of the License has been included in the root of the repository.
+
+//! Formatter that prints lists of rows as comma-separated or tab-separated values.
+//!
+//! The output follows [RFC 4180](https://www.rfc-editor.org/rfc/rfc4180), with
+//! the exception that lines end in `\n` rather than `\r\n`.
+
+use crate::error::{IntoError, PathElement, Result};
+use crate::markup::Markup;
+use crate::ordered::OrderedMap;
+use crate::pprint::{concat, Doc};
+use crate::runtime::Value;
+use crate::source::Span;
+
+/// Render a value as comma-separated values.
+pub fn format_csv(caller: Span, v: &Value) -> Result<Doc> {
+    let mut formatter = Formatter::new(caller, "CSV", ",");
+    formatter.top_level(v)
+}
+
+/// Render a value as tab-separated values.
+pub fn format_tsv(caller: Span, v: &Value) -> Result<Doc> {
+    let mut formatter = Formatter::new(caller, "TSV", "\t");
+    formatter.top_level(v)
+}
+
+/// Helper for formatting values as CSV or TSV.
+///
+/// The formatter tracks the path in the value that we are formatting from, such
+/// that we can report the location of an error, in case an error occurs.
+struct Formatter {
+    /// The source location where formatting was triggered from.
+    caller: Span,
+
+    /// Name of the format, for use in error messages.
+    name: &'static str,
+
+    /// The field separator, a comma or a tab.
+    separator: &'static str,
+
+    /// Where we currently are in the value to be formatted.
+    path: Vec<PathElement>,
+}
+
+impl Formatter {
+    pub fn new(caller: Span, name: &'static str, separator: &'static str) -> Formatter {
+        Formatter {
+            caller,
+            name,
+            separator,
+            path: Vec::new(),
+        }
+    }
+
+    /// Report an error at the current value path.
+    fn error<T>(&mut self, message: Doc<'static>) -> Result<T> {
+        // Steal the path from the formatter and move it into the error. We have
+        // to leave an empty path in its place. This is fine, because returning
+        // the error prevents further formatting.
+        let mut path = Vec::new();
+        std::mem::swap(&mut self.path, &mut path);
+        self.caller.error(message).with_path(path).err()
+    }
+
+    /// Format a string as a field, quoting it if needed.
+    fn field<'a>(&self, s: &'a str) -> Doc<'a> {
+        let needs_quotes =
+            s.contains(self.separator) || s.contains('"') || s.contains('\n') || s.contains('\r');
+
+        if !needs_quotes {
+            return s.into();
+        }
+
+        // Quotes inside a quoted field are escaped by doubling them. The field
+        // may contain newlines, those we preserve verbatim.
+        let escaped = s.replace('"', "\"\"");
+        concat! { "\"" Doc::lines(&escaped).into_owned() "\"" }
+    }
+
+    /// Format a single cell, which must be a primitive value.
+    fn cell<'a>(&mut self, v: &'a Value) -> Result<Doc<'a>> {
+        let result = match v {
+            Value::Null => Doc::Empty,
+            Value::Bool(true) => Doc::from("true").with_markup(Markup::Keyword),
+            Value::Bool(false) => Doc::from("false").with_markup(Markup::Keyword),
+            Value::Number(d) => Doc::from(d.format()).with_markup(Markup::Number),
+            Value::String(s) => self.field(s).with_markup(Markup::String),
+            Value::List(..) | Value::Set(..) | Value::Dict(..) => {
+                let msg = concat! {
+                    "To format as " self.name ", rows must not contain nested collections."
+                };
+                self.error(msg)?
+            }
+            Value::Function(..) | Value::BuiltinFunction(..) => {
+                self.error(concat! { "Functions cannot be exported as " self.name "." })?
+            }
+            Value::BuiltinMethod(..) => {
+                self.error(concat! { "Methods cannot be exported as " self.name "." })?
+            }
+        };
+        Ok(result)
+    }
+
+    /// Format a row that is a list of cells.
+    fn row_list<'a>(&mut self, cells: &'a [Value]) -> Result<Doc<'a>> {
+        let mut parts = Vec::with_capacity(cells.len() * 2 + 1);
+        for (i, cell) in cells.iter().enumerate() {
+            if i > 0 {
+                parts.push(self.separator.into());
+            }
+            self.path.push(PathElement::Index(i));
+            parts.push(self.cell(cell)?);
+            self.path.pop().expect("Push and pop are balanced.");
+        }
+        parts.push(Doc::HardBreak);
+        Ok(Doc::Concat(parts))
+    }
+
+    /// Format a row that is a dict, with one cell per header column.
+    fn row_dict<'a>(
+        &mut self,
+        header: &[&'a Value],
+        row: &'a OrderedMap<Value, Value>,
+    ) -> Result<Doc<'a>> {
+        let mut parts = Vec::with_capacity(header.len() * 2 + 1);
+        for (i, k) in header.iter().enumerate() {
+            if i > 0 {
+                parts.push(self.separator.into());
+            }
+            if let Some(v) = row.get(k) {
+                self.path.push(PathElement::Key((*k).clone()));
+                parts.push(self.cell(v)?);
+                self.path.pop().expect("Push and pop are balanced.");
+            }
+        }
+        parts.push(Doc::HardBreak);
+        Ok(Doc::Concat(parts))
+    }
+
+    /// Format the header line for rows that are dicts.
+    ///
+    /// The header contains every key that occurs in any row, in order of first
+    /// occurrence. Rows that lack a key get an empty cell in that column.
+    fn header<'a>(&mut self, rows: &[&'a Value]) -> Result<(Vec<&'a Value>, Doc<'a>)> {
+        let mut keys: Vec<&'a Value> = Vec::new();
+        for (i, row) in rows.iter().enumerate() {
+            for k in row.expect_dict().keys() {
+                if keys.contains(&k) {
+                    continue;
+                }
+                if !matches!(k, Value::String(..)) {
+                    self.path.push(PathElement::Index(i));
+                    self.path.push(PathElement::Key(k.clone()));
+                    return self.error(concat! {
+                        "To format as " self.name ", keys must be strings."
+                    });
+                }
+                keys.push(k);
+            }
+        }
+
+        let mut parts = Vec::with_capacity(keys.len() * 2 + 1);
+        for (i, k) in keys.iter().enumerate() {
+            if i > 0 {
+                parts.push(self.separator.into());
+            }
+            parts.push(self.field(k.expect_string()).with_markup(Markup::Field));
+        }
+        parts.push(Doc::HardBreak);
+
+        Ok((keys, Doc::Concat(parts)))
+    }
+
+    fn top_level<'a>(&mut self, v: &'a Value) -> Result<Doc<'a>> {
+        let rows: Vec<&'a Value> = match v {
+            Value::List(xs) => xs.iter().collect(),
+            Value::Set(xs) => xs.iter().collect(),
+            _ => {
+                return self.error(concat! {
+                    "To format as " self.name ", the top-level value must be a list."
+                })
+            }
+        };
+
+        // The first row determines whether we have dict rows with a header,
+        // or list rows without one. All other rows must follow suit.
+        let is_dict = matches!(rows.first(), Some(Value::Dict(..)));
+        let mut parts = Vec::with_capacity(rows.len() + 1);
+
+        for (i, row) in rows.iter().enumerate() {
+            self.path.push(PathElement::Index(i));
+            match row {
+                Value::Dict(..) if is_dict => {}
+                Value::List(..) if !is_dict => {}
+                _ => {
+                    return self.error(concat! {
+                        "To format as " self.name ", rows must be either all dicts, or all lists."
+                    })
+                }
+            }
+            self.path.pop().expect("Push and pop are balanced.");
+        }
+
+        if is_dict {
+            let (keys, header) = self.header(&rows)?;
+            parts.push(header);
+            for (i, row) in rows.iter().enumerate() {
+                self.path.push(PathElement::Index(i));
+                parts.push(self.row_dict(&keys, row.expect_dict())?);
+                self.path.pop().expect("Push and pop are balanced.");
+            }
+        } else {
+            for (i, row) in rows.iter().enumerate() {
+                self.path.push(PathElement::Index(i));
+                parts.push(self.row_list(row.expect_list())?);
+                self.path.pop().expect("Push and pop are balanced.");
+            }
+        }
+
+        Ok(Doc::Concat(parts))
+    }
+}
@@ -0,0 +1,146 @@
+// RCL -- A reasonable configuration language.
+// Copyright 2025 Ruud van Asseldonk
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// A copy of the License has been included in the root of the repository.
+
+//! Formatter that prints values as dotenv files.
+//!
+//! A dotenv file contains one `KEY=value` line per environment variable. There
+//! are many dialects that differ in how they handle quotes, so we stick to the
+//! subset that is also valid POSIX shell: values that contain anything other
+//! than a conservative set of safe characters get single-quoted. This means
+//! the output can also be loaded with `source` or `.` in a shell.
+
+use crate::error::{IntoError, PathElement, Result};
+use crate::markup::Markup;
+use crate::ordered::OrderedMap;
+use crate::pprint::{concat, Doc};
+use crate::runtime::Value;
+use crate::source::Span;
+
+/// Render a value as dotenv file.
+pub fn format_dotenv(caller: Span, v: &Value) -> Result<Doc> {
+    let mut formatter = Formatter::new(caller);
+
+    match v {
+        Value::Dict(kv) => formatter.top_level(kv),
+        _ => formatter.error("To format as dotenv, the top-level value must be a dict."),
+    }
+}
+
+/// Return whether the string is a valid environment variable name.
+///
+/// POSIX only requires support for uppercase names, but in practice lowercase
+/// names work fine too, so we allow them.
+pub fn is_env_var_name(s: &str) -> bool {
+    let bytes = s.as_bytes();
+    match bytes.first() {
+        Some(b) if b.is_ascii_alphabetic() || *b == b'_' => bytes[1..]
+            .iter()
+            .all(|b| b.is_ascii_alphanumeric() || *b == b'_'),
+        _ => false,
+    }
+}
+
+/// Quote a string for POSIX shell, if it needs quoting.
+///
+/// Strings that consist only of characters that have no special meaning in
+/// the shell are returned as-is. Anything else gets wrapped in single quotes,
+/// see [`crate::fmt_sh::quote_single`].
+pub fn quote_shell(s: &str) -> String {
+    let is_safe = !s.is_empty()
+        && s.bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"%+,-./:@_".contains(&b));
+
+    if is_safe {
+        s.to_string()
+    } else {
+        crate::fmt_sh::quote_single(s)
+    }
+}
+
+/// Helper for formatting values as dotenv.
+///
+/// The formatter tracks the path in the value that we are formatting from, such
+/// that we can report the location of an error, in case an error occurs.
+struct Formatter {
+    /// The source location where dotenv formatting was triggered from.
+    caller: Span,
+
+    /// Where we currently are in the value to be formatted.
+    path: Vec<PathElement>,
+}
+
+impl Formatter {
+    pub fn new(caller: Span) -> Formatter {
+        Formatter {
+            caller,
+            path: Vec::new(),
+        }
+    }
+
+    /// Report an error at the current value path.
+    fn error<T>(&mut self, message: &'static str) -> Result<T> {
+        // Steal the path from the formatter and move it into the error. We have
+        // to leave an empty path in its place. This is fine, because returning
+        // the error prevents further formatting.
+        let mut path = Vec::new();
+        std::mem::swap(&mut self.path, &mut path);
+        self.caller.error(message).with_path(path).err()
+    }
+
+    /// Format a value, which must be a scalar.
+    fn value<'a>(&mut self, v: &'a Value) -> Result<Doc<'a>> {
+        let result = match v {
+            // An empty value is the closest thing to null that we have.
+            Value::Null => Doc::Empty,
+            Value::Bool(true) => Doc::from("true").with_markup(Markup::Keyword),
+            Value::Bool(false) => Doc::from("false").with_markup(Markup::Keyword),
+            Value::Number(d) => Doc::from(d.format()).with_markup(Markup::Number),
+            Value::String(s) if s.is_empty() => Doc::Empty,
+            Value::String(s) if s.contains('\0') => {
+                self.error("Environment variables cannot hold control character U+0000.")?
+            }
+            Value::String(s) => Doc::lines(&quote_shell(s))
+                .into_owned()
+                .with_markup(Markup::String),
+            Value::List(..) | Value::Set(..) | Value::Dict(..) => self.error(
+                "To format as dotenv, values must be strings, numbers, booleans, or null. \
+                Environment variables cannot hold collections.",
+            )?,
+            Value::Function(..) => self.error("Functions cannot be exported as dotenv.")?,
+            Value::BuiltinFunction(..) => self.error("Functions cannot be exported as dotenv.")?,
+            Value::BuiltinMethod { .. } => self.error("Methods cannot be exported as dotenv.")?,
+        };
+        Ok(result)
+    }
+
+    fn top_level<'a>(&mut self, kv: &'a OrderedMap<Value, Value>) -> Result<Doc<'a>> {
+        let mut lines: Vec<Doc> = Vec::new();
+
+        for (k, v) in kv {
+            self.path.push(PathElement::Key(k.clone()));
+            let name = match k {
+                Value::String(name) if is_env_var_name(name) => name.as_ref(),
+                Value::String(..) => {
+                    return self.error(
+                        "To format as dotenv, keys must be valid environment variable names: \
+                        letters, digits, and underscores, not starting with a digit.",
+                    )
+                }
+                _ => return self.error("To format as dotenv, keys must be strings."),
+            };
+            lines.push(concat! {
+                Doc::from(name).with_markup(Markup::Field)
+                "="
+                self.value(v)?
+                Doc::HardBreak
+            });
+            self.path.pop().expect("Push and pop are balanced.");
+        }
+
+        Ok(Doc::Concat(lines))
+    }
+}
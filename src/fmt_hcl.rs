@@ -0,0 +1,350 @@
+// RCL -- A reasonable configuration language.
+// Copyright 2025 Ruud van Asseldonk
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// A copy of the License has been included in the root of the repository.
+
+//! Formatter that prints values in the native HCL syntax, as used by Terraform.
+//!
+//! The top-level value must be a dict, which becomes the body of the document.
+//! In a body, every key becomes either an attribute or one or more blocks:
+//!
+//! * A non-empty list of dicts becomes one block per element, named after the
+//!   key. A block with a single element is therefore a list with one dict.
+//! * In the top-level body only, a non-empty dict whose values are all blocks
+//!   by these rules becomes labelled blocks, where the keys of the dict are
+//!   the labels, up to two labels deep. For example,
+//!   `{ resource = { aws_instance = { web = [{ ami = "x" }] } } }` becomes
+//!   `resource "aws_instance" "web" { ami = "x" }`.
+//! * Anything else becomes an attribute, where dicts are HCL objects and lists
+//!   and sets are HCL tuples.
+//!
+//! Labelled blocks are limited to the top level, because Terraform's labelled
+//! blocks (`resource`, `variable`, `provider`, etc.) live there, while inside
+//! a block, a dict such as `tags = { a = [{ x = 1 }] }` is far more likely a
+//! map attribute than a labelled block.
+//!
+//! Strings are output as templates, so interpolations like `${var.x}` remain
+//! interpolations, the same as in Terraform's JSON syntax.
+//!
+//! This mapping is similar to how Terraform's JSON syntax represents blocks,
+//! except that it does not depend on a schema.
+
+use crate::error::{IntoError, PathElement, Result};
+use crate::markup::Markup;
+use crate::pprint::{concat, group, indent, Doc};
+use crate::runtime::Value;
+use crate::source::Span;
+
+/// Render a value as HCL.
+pub fn format_hcl(caller: Span, v: &Value) -> Result<Doc> {
+    let mut formatter = Formatter::new(caller);
+
+    match v {
+        Value::Dict(kv) => formatter.body(kv.iter(), MAX_TOP_LEVEL_LABELS),
+        _ => formatter.error("To format as HCL, the top-level value must be a dict."),
+    }
+}
+
+/// Return whether the string is a valid HCL identifier.
+///
+/// HCL allows Unicode identifiers, but we only use the ASCII subset, and quote
+/// anything else.
+fn is_hcl_identifier(s: &str) -> bool {
+    let bytes = s.as_bytes();
+    match bytes.first() {
+        Some(b) if b.is_ascii_alphabetic() || *b == b'_' => bytes[1..]
+            .iter()
+            .all(|b| b.is_ascii_alphanumeric() || *b == b'_' || *b == b'-'),
+        _ => false,
+    }
+}
+
+/// The maximum number of labels on blocks in the top-level body.
+const MAX_TOP_LEVEL_LABELS: u32 = 2;
+
+/// Return whether the value should be formatted as one or more blocks.
+///
+/// Dicts become labelled blocks only while `max_labels` is positive.
+fn is_blocks(v: &Value, max_labels: u32) -> bool {
+    match v {
+        Value::List(xs) => !xs.is_empty() && xs.iter().all(|x| matches!(x, Value::Dict(..))),
+        Value::Set(xs) => !xs.is_empty() && xs.iter().all(|x| matches!(x, Value::Dict(..))),
+        Value::Dict(xs) if max_labels > 0 => {
+            !xs.is_empty() && xs.values().all(|x| is_blocks(x, max_labels - 1))
+        }
+        _ => false,
+    }
+}
+
+/// Escape a string for use inside an HCL string literal.
+///
+/// HCL strings are templates, and we intentionally do not escape the
+/// interpolation `${` and directive `%{` sequences. This matches Terraform's
+/// JSON syntax, where strings are templates too, so references such as
+/// `"${var.region}"` keep working when switching from `json` to `hcl`.
+/// <https://github.com/hashicorp/hcl/blob/main/hclsyntax/spec.md#template-literals>
+fn escape_hcl(s: &str, into: &mut String) {
+    use std::fmt::Write;
+
+    into.reserve(s.len());
+
+    for ch in s.chars() {
+        match ch {
+            '\n' => into.push_str(r#"\n"#),
+            '\r' => into.push_str(r#"\r"#),
+            '\t' => into.push_str(r#"\t"#),
+            '\"' => into.push_str(r#"\""#),
+            '\\' => into.push_str(r#"\\"#),
+            ch if ch.is_control() => write!(into, "\\u{:04x}", ch as u32)
+                .expect("Writing into &mut String does not fail."),
+            ch => into.push(ch),
+        }
+    }
+}
+
+/// Helper for formatting values as HCL.
+///
+/// The formatter tracks the path in the value that we are formatting from, such
+/// that we can report the location of an error, in case an error occurs.
+struct Formatter {
+    /// The source location where HCL formatting was triggered from.
+    caller: Span,
+
+    /// Where we currently are in the value to be formatted.
+    path: Vec<PathElement>,
+}
+
+impl Formatter {
+    pub fn new(caller: Span) -> Formatter {
+        Formatter {
+            caller,
+            path: Vec::new(),
+        }
+    }
+
+    /// Report an error at the current value path.
+    fn error<T>(&mut self, message: &'static str) -> Result<T> {
+        // Steal the path from the formatter and move it into the error. We have
+        // to leave an empty path in its place. This is fine, because returning
+        // the error prevents further formatting.
+        let mut path = Vec::new();
+        std::mem::swap(&mut self.path, &mut path);
+        self.caller.error(message).with_path(path).err()
+    }
+
+    /// Format a string.
+    fn string<'a>(&self, s: &str) -> Doc<'a> {
+        let mut into = String::with_capacity(s.len());
+        escape_hcl(s, &mut into);
+        concat! { "\"" into "\"" }
+    }
+
+    /// Push the key to the path, and return it if it is a string.
+    fn push_key<'a>(&mut self, key: &'a Value) -> Result<&'a str> {
+        self.path.push(PathElement::Key(key.clone()));
+        match key {
+            Value::String(k_str) => Ok(k_str),
+            _ => self.error("To format as HCL, keys must be strings."),
+        }
+    }
+
+    /// Push the key to the path, and format it as attribute name or block type.
+    fn push_name<'a>(&mut self, key: &'a Value) -> Result<Doc<'a>> {
+        let name = self.push_key(key)?;
+        if is_hcl_identifier(name) {
+            Ok(Doc::from(name).with_markup(Markup::Field))
+        } else {
+            self.error(
+                "To format as HCL, attribute names and block types must be identifiers. \
+                To use an arbitrary string as a key, put it in a dict that becomes \
+                an object, or make it a block label.",
+            )
+        }
+    }
+
+    /// Format a list or set as a tuple.
+    fn tuple<'a>(&mut self, vs: impl Iterator<Item = &'a Value>) -> Result<Doc<'a>> {
+        let mut elements = Vec::new();
+        for (i, v) in vs.enumerate() {
+            if !elements.is_empty() {
+                elements.push(",".into());
+                elements.push(Doc::Sep);
+            }
+            self.path.push(PathElement::Index(i));
+            elements.push(self.value(v)?);
+            self.path.pop().expect("Push and pop are balanced.");
+        }
+
+        let result = if elements.is_empty() {
+            // An empty collection we always format without space in between.
+            "[]".into()
+        } else {
+            // Add a trailing comma in tall mode.
+            elements.push(Doc::tall(","));
+
+            group! {
+                "["
+                Doc::SoftBreak
+                indent! { Doc::Concat(elements) }
+                Doc::SoftBreak
+                "]"
+            }
+        };
+
+        Ok(result)
+    }
+
+    /// Format a dict as an object.
+    fn object<'a>(&mut self, vs: impl Iterator<Item = (&'a Value, &'a Value)>) -> Result<Doc<'a>> {
+        let mut elements = Vec::new();
+        for (k, v) in vs {
+            if !elements.is_empty() {
+                elements.push(",".into());
+            }
+            elements.push(Doc::Sep);
+            let k_str = self.push_key(k)?;
+            // In an object, a bare `true`, `false`, or `null` would be an
+            // expression rather than a key, so we quote those.
+            let key = match k_str {
+                "true" | "false" | "null" => self.string(k_str),
+                _ if is_hcl_identifier(k_str) => k_str.into(),
+                _ => self.string(k_str),
+            };
+            elements.push(key.with_markup(Markup::Field));
+            elements.push(" = ".into());
+            elements.push(self.value(v)?);
+            self.path.pop().expect("Push and pop are balanced.");
+        }
+
+        let result = if elements.is_empty() {
+            // An empty dict always formats without spaces.
+            "{}".into()
+        } else {
+            // Add a trailing separator in tall mode, and in wide mode, we want
+            // a space before the closing }.
+            elements.push(Doc::tall(","));
+            elements.push(Doc::Sep);
+
+            group! {
+                "{"
+                indent! { Doc::Concat(elements) }
+                "}"
+            }
+        };
+        Ok(result)
+    }
+
+    fn value<'a>(&mut self, v: &'a Value) -> Result<Doc<'a>> {
+        let result = match v {
+            Value::Null => Doc::from("null").with_markup(Markup::Keyword),
+            Value::Bool(true) => Doc::from("true").with_markup(Markup::Keyword),
+            Value::Bool(false) => Doc::from("false").with_markup(Markup::Keyword),
+            Value::Number(d) => Doc::from(d.format()).with_markup(Markup::Number),
+            Value::String(s) => self.string(s).with_markup(Markup::String),
+            Value::List(vs) => self.tuple(vs.iter())?,
+            // HCL has no set type, we format sets as tuples.
+            Value::Set(vs) => self.tuple(vs.iter())?,
+            Value::Dict(vs) => self.object(vs.iter())?,
+            Value::Function(..) => self.error("Functions cannot be exported as HCL.")?,
+            Value::BuiltinFunction(..) => self.error("Functions cannot be exported as HCL.")?,
+            Value::BuiltinMethod { .. } => self.error("Methods cannot be exported as HCL.")?,
+        };
+        Ok(result)
+    }
+
+    /// Format the blocks for a value for which `is_blocks` holds.
+    ///
+    /// The blocks get appended to `out`, one block per element.
+    fn blocks<'a>(
+        &mut self,
+        block_type: &Doc<'a>,
+        labels: &mut Vec<Doc<'a>>,
+        v: &'a Value,
+        out: &mut Vec<Doc<'a>>,
+    ) -> Result<()> {
+        let elements: Vec<&'a Value> = match v {
+            Value::List(xs) => xs.iter().collect(),
+            Value::Set(xs) => xs.iter().collect(),
+            Value::Dict(xs) => {
+                for (label, inner) in xs.iter() {
+                    let label_str = self.push_key(label)?;
+                    labels.push(self.string(label_str).with_markup(Markup::String));
+                    self.blocks(block_type, labels, inner, out)?;
+                    labels.pop().expect("Push and pop are balanced.");
+                    self.path.pop().expect("Push and pop are balanced.");
+                }
+                return Ok(());
+            }
+            _ => unreachable!("We checked before that the value consists of blocks."),
+        };
+
+        for (i, element) in elements.iter().enumerate() {
+            self.path.push(PathElement::Index(i));
+            let mut header = vec![block_type.clone()];
+            for label in labels.iter() {
+                header.push(" ".into());
+                header.push(label.clone());
+            }
+            let body = element.expect_dict();
+            let block = if body.is_empty() {
+                concat! { Doc::Concat(header) " {}" }
+            } else {
+                concat! {
+                    Doc::Concat(header) " {"
+                    indent! { Doc::HardBreak self.body(body.iter(), 0)? }
+                    Doc::HardBreak
+                    "}"
+                }
+            };
+            out.push(block);
+            self.path.pop().expect("Push and pop are balanced.");
+        }
+
+        Ok(())
+    }
+
+    /// Format a dict as a body, which consists of attributes and blocks.
+    ///
+    /// We separate blocks from their surroundings by a blank line. Blocks in
+    /// the body can have at most `max_labels` labels.
+    fn body<'a>(
+        &mut self,
+        vs: impl Iterator<Item = (&'a Value, &'a Value)>,
+        max_labels: u32,
+    ) -> Result<Doc<'a>> {
+        let mut items = Vec::new();
+        let mut prev_is_block = false;
+
+        for (k, v) in vs {
+            let name = self.push_name(k)?;
+
+            if is_blocks(v, max_labels) {
+                let mut blocks = Vec::new();
+                self.blocks(&name, &mut Vec::new(), v, &mut blocks)?;
+                for block in blocks {
+                    if !items.is_empty() {
+                        items.push(Doc::HardBreak);
+                        items.push(Doc::HardBreak);
+                    }
+                    items.push(block);
+                }
+                prev_is_block = true;
+            } else {
+                if prev_is_block {
+                    items.push(Doc::HardBreak);
+                }
+                if !items.is_empty() {
+                    items.push(Doc::HardBreak);
+                }
+                items.push(concat! { name " = " self.value(v)? });
+                prev_is_block = false;
+            }
+
+            self.path.pop().expect("Push and pop are balanced.");
+        }
+
+        Ok(Doc::Concat(items))
+    }
+}
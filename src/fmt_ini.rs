@@ -0,0 +1,179 @@
+// RCL -- A reasonable configuration language.
+// Copyright 2025 Ruud van Asseldonk
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// A copy of the License has been included in the root of the repository.
+
+//! Formatter that prints values as INI files.
+//!
+//! INI has no formal specification, so we output the subset that most parsers
+//! agree on: `key = value` lines, optionally grouped under `[section]` headers.
+//! Top-level keys with scalar values come first, followed by one section for
+//! every top-level key with a dict value. There is only one level of sections.
+
+use crate::error::{IntoError, PathElement, Result};
+use crate::markup::Markup;
+use crate::ordered::OrderedMap;
+use crate::pprint::{concat, Doc};
+use crate::runtime::Value;
+use crate::source::Span;
+
+/// Render a value as INI.
+pub fn format_ini(caller: Span, v: &Value) -> Result<Doc> {
+    let mut formatter = Formatter::new(caller);
+
+    match v {
+        Value::Dict(kv) => formatter.top_level(kv),
+        _ => formatter.error("To format as INI, the top-level value must be a dict."),
+    }
+}
+
+/// Helper for formatting values as INI.
+///
+/// The formatter tracks the path in the value that we are formatting from, such
+/// that we can report the location of an error, in case an error occurs.
+struct Formatter {
+    /// The source location where INI formatting was triggered from.
+    caller: Span,
+
+    /// Where we currently are in the value to be formatted.
+    path: Vec<PathElement>,
+}
+
+impl Formatter {
+    pub fn new(caller: Span) -> Formatter {
+        Formatter {
+            caller,
+            path: Vec::new(),
+        }
+    }
+
+    /// Report an error at the current value path.
+    fn error<T>(&mut self, message: &'static str) -> Result<T> {
+        // Steal the path from the formatter and move it into the error. We have
+        // to leave an empty path in its place. This is fine, because returning
+        // the error prevents further formatting.
+        let mut path = Vec::new();
+        std::mem::swap(&mut self.path, &mut path);
+        self.caller.error(message).with_path(path).err()
+    }
+
+    /// Format a key, and push it to the path, or return an error if it is invalid.
+    fn push_key<'a>(&mut self, key: &'a Value) -> Result<Doc<'a>> {
+        self.path.push(PathElement::Key(key.clone()));
+        let k_str = match key {
+            Value::String(k_str) => k_str.as_ref(),
+            _ => return self.error("To format as INI, keys must be strings."),
+        };
+        if k_str.trim().is_empty() {
+            return self.error("To format as INI, keys must not be empty.");
+        }
+        if k_str.trim() != k_str {
+            return self.error("To format as INI, keys must not start or end with whitespace.");
+        }
+        if k_str.starts_with([';', '#', '[']) {
+            return self.error("To format as INI, keys must not start with ';', '#', or '['.");
+        }
+        if k_str.contains(['=', ':', '\n', '\r']) {
+            return self.error("To format as INI, keys must not contain '=', ':', or line breaks.");
+        }
+        Ok(Doc::from(k_str).with_markup(Markup::Field))
+    }
+
+    /// Format a section name, and push it to the path.
+    fn push_section<'a>(&mut self, key: &'a Value) -> Result<Doc<'a>> {
+        self.path.push(PathElement::Key(key.clone()));
+        let k_str = match key {
+            Value::String(k_str) => k_str.as_ref(),
+            _ => return self.error("To format as INI, section names must be strings."),
+        };
+        if k_str.is_empty() {
+            return self.error("To format as INI, section names must not be empty.");
+        }
+        if k_str.contains([']', '\n', '\r']) {
+            return self
+                .error("To format as INI, section names must not contain ']' or line breaks.");
+        }
+        Ok(Doc::from(k_str).with_markup(Markup::Field))
+    }
+
+    /// Format a value, which must be a scalar.
+    fn value<'a>(&mut self, v: &'a Value) -> Result<Doc<'a>> {
+        let result = match v {
+            // INI has no null, but an empty value is the closest thing to it.
+            Value::Null => Doc::Empty,
+            Value::Bool(true) => Doc::from("true").with_markup(Markup::Keyword),
+            Value::Bool(false) => Doc::from("false").with_markup(Markup::Keyword),
+            Value::Number(d) => Doc::from(d.format()).with_markup(Markup::Number),
+            Value::String(s) => {
+                // INI has no escape sequences or quoting that parsers agree
+                // on, so we output strings verbatim, which means they must
+                // fit on one line, and leading whitespace would get lost.
+                if s.contains(['\n', '\r']) {
+                    return self.error("To format as INI, strings must not contain line breaks.");
+                }
+                if s.trim() != s.as_ref() {
+                    return self
+                        .error("To format as INI, strings must not start or end with whitespace.");
+                }
+                Doc::from(s.as_ref()).with_markup(Markup::String)
+            }
+            Value::List(..) | Value::Set(..) => {
+                self.error("To format as INI, values must not be lists or sets.")?
+            }
+            Value::Dict(..) => self.error(
+                "To format as INI, only top-level dicts can be sections, \
+                sections cannot be nested.",
+            )?,
+            Value::Function(..) => self.error("Functions cannot be exported as INI.")?,
+            Value::BuiltinFunction(..) => self.error("Functions cannot be exported as INI.")?,
+            Value::BuiltinMethod { .. } => self.error("Methods cannot be exported as INI.")?,
+        };
+        Ok(result)
+    }
+
+    /// Format a key-value pair, on its own line.
+    fn key_value<'a>(&mut self, key: &'a Value, value: &'a Value) -> Result<Doc<'a>> {
+        let key_doc = self.push_key(key)?;
+        let value_doc = self.value(value)?;
+        self.path.pop().expect("We pushed the key before.");
+        let result = match value_doc {
+            Doc::Empty => concat! { key_doc " =" Doc::HardBreak },
+            value_doc => concat! { key_doc " = " value_doc Doc::HardBreak },
+        };
+        Ok(result)
+    }
+
+    fn top_level<'a>(&mut self, kv: &'a OrderedMap<Value, Value>) -> Result<Doc<'a>> {
+        let mut values: Vec<Doc> = Vec::new();
+        let mut sections: Vec<Doc> = Vec::new();
+
+        for (k, v) in kv {
+            match v {
+                Value::Dict(section_inner) => {
+                    let mut section = vec![concat! {
+                        "[" self.push_section(k)? "]" Doc::HardBreak
+                    }];
+                    for (sk, sv) in section_inner.iter() {
+                        section.push(self.key_value(sk, sv)?);
+                    }
+                    self.path.pop().expect("We pushed the key before.");
+                    sections.push(Doc::Concat(section));
+                }
+                _ => values.push(self.key_value(k, v)?),
+            }
+        }
+
+        // We put the section-less values first, then the sections.
+        for section in sections {
+            // Separate sections by a blank line.
+            if !values.is_empty() {
+                values.push(Doc::HardBreak);
+            }
+            values.push(section);
+        }
+
+        Ok(Doc::Concat(values))
+    }
+}
@@ -6,7 +6,12 @@
 // A copy of the License has been included in the root of the repository.
 
 //! Formatter that prints values as json.
+//!
+//! Besides pretty-printed json, the formatter can output compact json without
+//! any whitespace, and canonical json as defined by the JSON Canonicalization
+//! Scheme in <https://www.rfc-editor.org/rfc/rfc8785>.
 
+use crate::decimal::Decimal;
 use crate::error::{IntoError, PathElement, Result};
 use crate::markup::Markup;
 use crate::pprint::{concat, group, indent, Doc};
@@ -20,6 +25,94 @@ pub fn format_json(caller: Span, v: &Value) -> Result<Doc> {
     formatter.value(v)
 }
 
+/// Render a value as json on a single line, without any whitespace.
+pub fn format_json_compact(caller: Span, v: &Value) -> Result<Doc> {
+    let mut formatter = Formatter::with_style(caller, Style::Compact);
+    formatter.value(v)
+}
+
+/// Render a value as canonical json, following RFC 8785.
+pub fn format_json_canonical(caller: Span, v: &Value) -> Result<Doc> {
+    let mut formatter = Formatter::with_style(caller, Style::Canonical);
+    formatter.value(v)
+}
+
+/// How to lay out the json document.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Style {
+    /// Pretty-print, breaking collections over multiple lines where needed.
+    Pretty,
+
+    /// Print everything on one line, without whitespace.
+    Compact,
+
+    /// Print compact json, with keys sorted and numbers formatted as RFC 8785
+    /// prescribes.
+    Canonical,
+}
+
+/// Format a number the way ECMAScript's `Number.prototype.toString` does.
+///
+/// RFC 8785 requires numbers to be serialized as IEEE 754 doubles, formatted
+/// by the ECMAScript algorithm. Returns `None` if the number does not fit in
+/// a double.
+fn format_number_canonical(d: &Decimal) -> Option<String> {
+    // Go through a string rather than `Decimal::to_f64_lossy`, because Rust's
+    // float parser rounds correctly.
+    let exponent = d.exponent as i32 - d.decimals as i32;
+    let x: f64 = format!("{}e{}", d.mantissa, exponent)
+        .parse()
+        .expect("Mantissa and exponent form a valid float literal.");
+    if !x.is_finite() {
+        return None;
+    }
+    if x == 0.0 {
+        // This also covers negative zero, which formats as "0".
+        return Some("0".to_string());
+    }
+
+    // Rust formats floats with the shortest digit string that round-trips,
+    // which is the same digit string that ECMAScript uses. We only need to
+    // place the decimal point differently.
+    let sci = format!("{:e}", x.abs());
+    let (mantissa, exp) = sci
+        .split_once('e')
+        .expect("Scientific notation has an 'e'.");
+    let digits: String = mantissa.chars().filter(|ch| *ch != '.').collect();
+    let exp: i32 = exp.parse().expect("Float exponent is an integer.");
+
+    // In terms of the ECMAScript spec, `k` is the number of digits, and the
+    // value is `digits * 10^(n - k)`.
+    let k = digits.len() as i32;
+    let n = exp + 1;
+    let mut out = String::new();
+    if x < 0.0 {
+        out.push('-');
+    }
+    if k <= n && n <= 21 {
+        out.push_str(&digits);
+        out.extend(std::iter::repeat('0').take((n - k) as usize));
+    } else if 0 < n && n <= 21 {
+        out.push_str(&digits[..n as usize]);
+        out.push('.');
+        out.push_str(&digits[n as usize..]);
+    } else if -6 < n && n <= 0 {
+        out.push_str("0.");
+        out.extend(std::iter::repeat('0').take(-n as usize));
+        out.push_str(&digits);
+    } else {
+        out.push_str(&digits[..1]);
+        if k > 1 {
+            out.push('.');
+            out.push_str(&digits[1..]);
+        }
+        out.push('e');
+        out.push(if n > 0 { '+' } else { '-' });
+        out.push_str(&(n - 1).abs().to_string());
+    }
+    Some(out)
+}
+
 /// Helper for formatting values as json.
 ///
 /// The formatter tracks the path in the value that we are formatting from, such
@@ -30,13 +123,21 @@ pub struct Formatter {
 
     /// Where we currently are in the value to be formatted.
     pub path: Vec<PathElement>,
+
+    /// How to lay out the document.
+    pub style: Style,
 }
 
 impl Formatter {
     pub fn new(caller: Span) -> Formatter {
+        Formatter::with_style(caller, Style::Pretty)
+    }
+
+    pub fn with_style(caller: Span, style: Style) -> Formatter {
         Formatter {
             caller,
             path: Vec::new(),
+            style,
         }
     }
 
@@ -53,37 +154,80 @@ impl Formatter {
     fn string<'a>(&self, s: &str) -> Doc<'a> {
         let mut into = String::with_capacity(s.len());
         // TODO: Escape into a Doc so we can highlight escape sequences.
-        escape_json(s, &mut into);
+        match self.style {
+            // RFC 8785 only escapes the control characters below U+0020, so
+            // unlike regular json output, DEL is output verbatim.
+            Style::Canonical => {
+                for part in s.split_inclusive('\x7f') {
+                    match part.strip_suffix('\x7f') {
+                        Some(before) => {
+                            escape_json(before, &mut into);
+                            into.push('\x7f');
+                        }
+                        None => escape_json(part, &mut into),
+                    }
+                }
+            }
+            _ => escape_json(s, &mut into),
+        }
         concat! { "\"" into "\"" }
     }
 
+    /// Wrap the elements of a collection in the opening and closing delimiter.
+    fn collection<'a>(
+        &self,
+        open: &'static str,
+        elements: Vec<Doc<'a>>,
+        close: &'static str,
+    ) -> Doc<'a> {
+        match self.style {
+            Style::Pretty => group! {
+                open
+                Doc::SoftBreak
+                indent! { Doc::Concat(elements) }
+                Doc::SoftBreak
+                close
+            },
+            Style::Compact | Style::Canonical => concat! { open Doc::Concat(elements) close },
+        }
+    }
+
+    /// Push the separator between two elements of a collection.
+    fn push_separator(&self, elements: &mut Vec<Doc>) {
+        elements.push(",".into());
+        if self.style == Style::Pretty {
+            elements.push(Doc::Sep);
+        }
+    }
+
     fn list<'a>(&mut self, vs: impl Iterator<Item = &'a Value>) -> Result<Doc<'a>> {
         let mut elements = Vec::new();
         for (i, v) in vs.enumerate() {
             if !elements.is_empty() {
-                elements.push(",".into());
-                elements.push(Doc::Sep);
+                self.push_separator(&mut elements);
             }
             self.path.push(PathElement::Index(i));
             elements.push(self.value(v)?);
             self.path.pop().expect("Push and pop are balanced.");
         }
-        let result = group! {
-            "["
-            Doc::SoftBreak
-            indent! { Doc::Concat(elements) }
-            Doc::SoftBreak
-            "]"
-        };
-        Ok(result)
+        Ok(self.collection("[", elements, "]"))
     }
 
     fn dict<'a>(&mut self, vs: impl Iterator<Item = (&'a Value, &'a Value)>) -> Result<Doc<'a>> {
+        let mut pairs: Vec<_> = vs.collect();
+        if self.style == Style::Canonical {
+            // RFC 8785 orders keys by their UTF-16 code units. This differs
+            // from code point order for code points above U+FFFF. Non-string
+            // keys are reported as an error below, their order does not matter.
+            pairs.sort_by(|(k1, _), (k2, _)| match (k1, k2) {
+                (Value::String(s1), Value::String(s2)) => s1.encode_utf16().cmp(s2.encode_utf16()),
+                _ => std::cmp::Ordering::Equal,
+            });
+        }
         let mut elements = Vec::new();
-        for (k, v) in vs {
+        for (k, v) in pairs {
             if !elements.is_empty() {
-                elements.push(",".into());
-                elements.push(Doc::Sep);
+                self.push_separator(&mut elements);
             }
             self.path.push(PathElement::Key(k.clone()));
             match k {
@@ -92,18 +236,14 @@ impl Formatter {
                 }
                 _ => return self.error("To export as json, keys must be strings."),
             };
-            elements.push(": ".into());
+            elements.push(match self.style {
+                Style::Pretty => ": ".into(),
+                Style::Compact | Style::Canonical => ":".into(),
+            });
             elements.push(self.value(v)?);
             self.path.pop().expect("Push and pop are balanced.");
         }
-        let result = group! {
-            "{"
-            Doc::SoftBreak
-            indent! { Doc::Concat(elements) }
-            Doc::SoftBreak
-            "}"
-        };
-        Ok(result)
+        Ok(self.collection("{", elements, "}"))
     }
 
     pub fn value<'a>(&mut self, v: &'a Value) -> Result<Doc<'a>> {
@@ -111,6 +251,14 @@ impl Formatter {
             Value::Null => Doc::from("null").with_markup(Markup::Keyword),
             Value::Bool(true) => Doc::from("true").with_markup(Markup::Keyword),
             Value::Bool(false) => Doc::from("false").with_markup(Markup::Keyword),
+            Value::Number(d) if self.style == Style::Canonical => {
+                match format_number_canonical(d) {
+                    Some(n) => Doc::from(n).with_markup(Markup::Number),
+                    None => self.error(
+                        "To export as canonical json, numbers must fit in a 64-bit float.",
+                    )?,
+                }
+            }
             Value::Number(d) => Doc::from(d.format()).with_markup(Markup::Number),
             Value::String(s) => self.string(s).with_markup(Markup::String),
             Value::List(vs) => self.list(vs.iter())?,
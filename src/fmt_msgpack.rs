@@ -0,0 +1,200 @@
+// RCL -- A reasonable configuration language.
+// Copyright 2025 Ruud van Asseldonk
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// A copy of the License has been included in the root of the repository.
+
+//! Encoder that serializes values as MessagePack.
+//!
+//! See <https://github.com/msgpack/msgpack/blob/master/spec.md> for the
+//! specification. Numbers that are integers and fit in an `i64` become
+//! MessagePack integers. MessagePack has no decimal type, so other numbers
+//! become 64-bit floats, which may lose precision. Sets become arrays.
+
+use crate::decimal::Decimal;
+use crate::error::{IntoError, PathElement, Result};
+use crate::runtime::Value;
+use crate::source::Span;
+
+/// Encode a value as MessagePack.
+pub fn format_msgpack(caller: Span, v: &Value) -> Result<Vec<u8>> {
+    let mut encoder = Encoder::new(caller);
+    encoder.value(v)?;
+    Ok(encoder.out)
+}
+
+/// Helper for encoding values as MessagePack.
+///
+/// The encoder tracks the path in the value that we are encoding from, such
+/// that we can report the location of an error, in case an error occurs.
+struct Encoder {
+    /// The source location where MessagePack encoding was triggered from.
+    caller: Span,
+
+    /// Where we currently are in the value to be encoded.
+    path: Vec<PathElement>,
+
+    /// The encoded output so far.
+    out: Vec<u8>,
+}
+
+impl Encoder {
+    pub fn new(caller: Span) -> Encoder {
+        Encoder {
+            caller,
+            path: Vec::new(),
+            out: Vec::new(),
+        }
+    }
+
+    /// Report an error at the current value path.
+    fn error<T>(&mut self, message: &'static str) -> Result<T> {
+        // Steal the path from the encoder and move it into the error. We have
+        // to leave an empty path in its place. This is fine, because returning
+        // the error prevents further encoding.
+        let mut path = Vec::new();
+        std::mem::swap(&mut self.path, &mut path);
+        self.caller.error(message).with_path(path).err()
+    }
+
+    /// Write the header for a string, array, or map of length `len`.
+    ///
+    /// The `fix` byte is the marker for the short form, which holds lengths
+    /// below `fix_limit`. The `wide` markers are for 8, 16, and 32-bit
+    /// lengths, in that order. Arrays and maps have no 8-bit form.
+    fn head(&mut self, len: usize, fix: u8, fix_limit: usize, wide: [Option<u8>; 3]) -> Result<()> {
+        match (len, wide) {
+            (n, _) if n < fix_limit => self.out.push(fix | n as u8),
+            (n, [Some(marker), _, _]) if n <= u8::MAX as usize => {
+                self.out.push(marker);
+                self.out.push(n as u8);
+            }
+            (n, [_, Some(marker), _]) if n <= u16::MAX as usize => {
+                self.out.push(marker);
+                self.out.extend_from_slice(&(n as u16).to_be_bytes());
+            }
+            (n, [_, _, Some(marker)]) if n <= u32::MAX as usize => {
+                self.out.push(marker);
+                self.out.extend_from_slice(&(n as u32).to_be_bytes());
+            }
+            _ => {
+                return self.error(
+                    "To format as MessagePack, strings and collections \
+                    must have fewer than 2^32 elements.",
+                )
+            }
+        }
+        Ok(())
+    }
+
+    /// Write an integer in the shortest form that can hold it.
+    fn int(&mut self, n: i64) {
+        match n {
+            // Positive and negative fixint.
+            -32..=0x7f => self.out.push(n as u8),
+            0x80..=0xff => self.out.extend_from_slice(&[0xcc, n as u8]),
+            0x100..=0xffff => {
+                self.out.push(0xcd);
+                self.out.extend_from_slice(&(n as u16).to_be_bytes());
+            }
+            0x1_0000..=0xffff_ffff => {
+                self.out.push(0xce);
+                self.out.extend_from_slice(&(n as u32).to_be_bytes());
+            }
+            n if n > 0 => {
+                self.out.push(0xcf);
+                self.out.extend_from_slice(&(n as u64).to_be_bytes());
+            }
+            -0x80..=-33 => self.out.extend_from_slice(&[0xd0, n as u8]),
+            -0x8000..=-0x81 => {
+                self.out.push(0xd1);
+                self.out.extend_from_slice(&(n as i16).to_be_bytes());
+            }
+            -0x8000_0000..=-0x8001 => {
+                self.out.push(0xd2);
+                self.out.extend_from_slice(&(n as i32).to_be_bytes());
+            }
+            n => {
+                self.out.push(0xd3);
+                self.out.extend_from_slice(&n.to_be_bytes());
+            }
+        }
+    }
+
+    fn number(&mut self, d: &Decimal) -> Result<()> {
+        if let Some(n) = d.to_i64() {
+            self.int(n);
+            return Ok(());
+        }
+        if d.is_integer_literal() {
+            // Between i64::MAX and u64::MAX we can still use uint64, beyond
+            // that a float would silently lose precision.
+            return match d.mantissa.to_u128().and_then(|n| u64::try_from(n).ok()) {
+                Some(n) => {
+                    self.out.push(0xcf);
+                    self.out.extend_from_slice(&n.to_be_bytes());
+                    Ok(())
+                }
+                None => self.error(
+                    "To format as MessagePack, integers must fit in a 64-bit signed \
+                    or unsigned integer.",
+                ),
+            };
+        }
+        let f = d.to_f64_lossy();
+        if !f.is_finite() {
+            return self.error("To format as MessagePack, numbers must fit in a 64-bit float.");
+        }
+        self.out.push(0xcb);
+        self.out.extend_from_slice(&f.to_be_bytes());
+        Ok(())
+    }
+
+    fn string(&mut self, s: &str) -> Result<()> {
+        self.head(s.len(), 0xa0, 32, [Some(0xd9), Some(0xda), Some(0xdb)])?;
+        self.out.extend_from_slice(s.as_bytes());
+        Ok(())
+    }
+
+    fn array<'a>(&mut self, len: usize, vs: impl Iterator<Item = &'a Value>) -> Result<()> {
+        self.head(len, 0x90, 16, [None, Some(0xdc), Some(0xdd)])?;
+        for (i, v) in vs.enumerate() {
+            self.path.push(PathElement::Index(i));
+            self.value(v)?;
+            self.path.pop().expect("Push and pop are balanced.");
+        }
+        Ok(())
+    }
+
+    fn value(&mut self, v: &Value) -> Result<()> {
+        match v {
+            Value::Null => self.out.push(0xc0),
+            Value::Bool(false) => self.out.push(0xc2),
+            Value::Bool(true) => self.out.push(0xc3),
+            Value::Number(d) => self.number(d)?,
+            Value::String(s) => self.string(s)?,
+            Value::List(vs) => self.array(vs.len(), vs.iter())?,
+            Value::Set(vs) => self.array(vs.len(), vs.iter())?,
+            Value::Dict(kv) => {
+                self.head(kv.len(), 0x80, 16, [None, Some(0xde), Some(0xdf)])?;
+                for (k, v) in kv.iter() {
+                    self.path.push(PathElement::Key(k.clone()));
+                    self.value(k)?;
+                    self.value(v)?;
+                    self.path.pop().expect("Push and pop are balanced.");
+                }
+            }
+            Value::Function(..) => {
+                return self.error("Functions cannot be exported as MessagePack.")
+            }
+            Value::BuiltinFunction(..) => {
+                return self.error("Functions cannot be exported as MessagePack.")
+            }
+            Value::BuiltinMethod { .. } => {
+                return self.error("Methods cannot be exported as MessagePack.")
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,203 @@
+// RCL -- A reasonable configuration language.
+// Copyright 2025 Ruud van Asseldonk
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// A copy of the License has been included in the root of the repository.
+
+//! Formatter that prints values as Nix expressions.
+//!
+//! This formatter is similar to the one in [`crate::fmt_json`].
+
+use crate::decimal::Decimal;
+use crate::error::{IntoError, PathElement, Result};
+use crate::markup::Markup;
+use crate::pprint::{concat, group, indent, Doc};
+use crate::runtime::Value;
+use crate::source::Span;
+
+/// Render a value as a Nix expression.
+pub fn format_nix(caller: Span, v: &Value) -> Result<Doc> {
+    let mut formatter = Formatter::new(caller);
+    formatter.value(v)
+}
+
+/// Return whether the string can be used unquoted as Nix attribute name.
+///
+/// See also <https://nix.dev/manual/nix/stable/language/identifiers>.
+fn is_nix_identifier(s: &str) -> bool {
+    let bytes = s.as_bytes();
+    let is_ident = match bytes.first() {
+        Some(b) if b.is_ascii_alphabetic() || *b == b'_' => bytes[1..]
+            .iter()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'_' | b'-' | b'\'')),
+        _ => false,
+    };
+    let is_keyword = matches!(
+        s,
+        "assert" | "else" | "if" | "in" | "inherit" | "let" | "or" | "rec" | "then" | "with"
+    );
+    is_ident && !is_keyword
+}
+
+/// Escape a string for use inside a Nix string literal.
+///
+/// Nix strings support interpolation, so aside from quotes and backslashes,
+/// we also need to escape `${`. Other characters, including control
+/// characters, can occur verbatim in the string, except for the ones that
+/// have a dedicated escape sequence. <https://nix.dev/manual/nix/stable/language/string-literals>
+fn escape_nix(s: &str, into: &mut String) {
+    into.reserve(s.len());
+
+    let mut chars = s.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\n' => into.push_str(r#"\n"#),
+            '\r' => into.push_str(r#"\r"#),
+            '\t' => into.push_str(r#"\t"#),
+            '\"' => into.push_str(r#"\""#),
+            '\\' => into.push_str(r#"\\"#),
+            '$' if chars.peek() == Some(&'{') => into.push_str(r#"\$"#),
+            ch => into.push(ch),
+        }
+    }
+}
+
+/// Format a number as Nix integer or float.
+///
+/// Nix distinguishes between integers and floats. Like in the json output, we
+/// preserve the form of the number: numbers without decimal point or exponent
+/// become integers, anything else becomes a float. Nix floats with an exponent
+/// must have a decimal point, so we add one where needed.
+fn number(d: &Decimal) -> String {
+    let mut result = d.format();
+    if let Some(e_pos) = result.find('e') {
+        if !result[..e_pos].contains('.') {
+            result.insert_str(e_pos, ".0");
+        }
+    }
+    result
+}
+
+/// Helper for formatting values as Nix.
+///
+/// The formatter tracks the path in the value that we are formatting from, such
+/// that we can report the location of an error, in case an error occurs.
+struct Formatter {
+    /// The source location where Nix formatting was triggered from.
+    caller: Span,
+
+    /// Where we currently are in the value to be formatted.
+    path: Vec<PathElement>,
+}
+
+impl Formatter {
+    pub fn new(caller: Span) -> Formatter {
+        Formatter {
+            caller,
+            path: Vec::new(),
+        }
+    }
+
+    /// Report an error at the current value path.
+    fn error<T>(&mut self, message: &'static str) -> Result<T> {
+        // Steal the path from the formatter and move it into the error. We have
+        // to leave an empty path in its place. This is fine, because returning
+        // the error prevents further formatting.
+        let mut path = Vec::new();
+        std::mem::swap(&mut self.path, &mut path);
+        self.caller.error(message).with_path(path).err()
+    }
+
+    /// Format a string.
+    fn string<'a>(&self, s: &str) -> Doc<'a> {
+        let mut into = String::with_capacity(s.len());
+        escape_nix(s, &mut into);
+        concat! { "\"" into "\"" }
+    }
+
+    fn list<'a>(&mut self, vs: impl Iterator<Item = &'a Value>) -> Result<Doc<'a>> {
+        let mut elements = Vec::new();
+        for (i, v) in vs.enumerate() {
+            self.path.push(PathElement::Index(i));
+            elements.push(Doc::Sep);
+            let element = match v {
+                // In a list, `[ 1 -2 ]` would be a subtraction, so negative
+                // numbers need parentheses.
+                Value::Number(d) if d.signum() < 0 => {
+                    concat! { "(" self.value(v)? ")" }
+                }
+                _ => self.value(v)?,
+            };
+            elements.push(element);
+            self.path.pop().expect("Push and pop are balanced.");
+        }
+
+        let result = if elements.is_empty() {
+            "[ ]".into()
+        } else {
+            elements.push(Doc::Sep);
+            group! {
+                "["
+                indent! { Doc::Concat(elements) }
+                "]"
+            }
+        };
+        Ok(result)
+    }
+
+    fn attrset<'a>(&mut self, vs: impl Iterator<Item = (&'a Value, &'a Value)>) -> Result<Doc<'a>> {
+        let mut elements = Vec::new();
+        for (k, v) in vs {
+            self.path.push(PathElement::Key(k.clone()));
+            elements.push(Doc::Sep);
+            match k {
+                Value::String(k_str) if is_nix_identifier(k_str) => {
+                    elements.push(Doc::from(k_str.as_ref()).with_markup(Markup::Field))
+                }
+                Value::String(k_str) => {
+                    elements.push(self.string(k_str).with_markup(Markup::Field))
+                }
+                _ => return self.error("To export as Nix, keys must be strings."),
+            };
+            elements.push(" = ".into());
+            elements.push(self.value(v)?);
+            elements.push(";".into());
+            self.path.pop().expect("Push and pop are balanced.");
+        }
+
+        let result = if elements.is_empty() {
+            "{ }".into()
+        } else {
+            elements.push(Doc::Sep);
+            group! {
+                "{"
+                indent! { Doc::Concat(elements) }
+                "}"
+            }
+        };
+        Ok(result)
+    }
+
+    fn value<'a>(&mut self, v: &'a Value) -> Result<Doc<'a>> {
+        let result = match v {
+            Value::Null => Doc::from("null").with_markup(Markup::Keyword),
+            Value::Bool(true) => Doc::from("true").with_markup(Markup::Keyword),
+            Value::Bool(false) => Doc::from("false").with_markup(Markup::Keyword),
+            // Nix integers are 64-bit, larger integer literals fail to parse.
+            Value::Number(d) if d.is_integer_literal() && d.to_i64().is_none() => {
+                self.error("To format as Nix, integers must fit in a 64-bit signed integer.")?
+            }
+            Value::Number(d) => Doc::from(number(d)).with_markup(Markup::Number),
+            Value::String(s) => self.string(s).with_markup(Markup::String),
+            Value::List(vs) => self.list(vs.iter())?,
+            // Nix has no set type, we format sets as lists.
+            Value::Set(vs) => self.list(vs.iter())?,
+            Value::Dict(vs) => self.attrset(vs.iter())?,
+            Value::Function(..) => self.error("Functions cannot be exported as Nix.")?,
+            Value::BuiltinFunction(..) => self.error("Functions cannot be exported as Nix.")?,
+            Value::BuiltinMethod { .. } => self.error("Methods cannot be exported as Nix.")?,
+        };
+        Ok(result)
+    }
+}
@@ -0,0 +1,150 @@
+// RCL -- A reasonable configuration language.
+// Copyright 2025 Ruud van Asseldonk
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// A copy of the License has been included in the root of the repository.
+
+//! Formatter that prints values as Java properties files.
+//!
+//! The output follows the format read by `java.util.Properties.load`. We escape
+//! everything outside of printable ASCII as `\uXXXX`, so the output is valid
+//! regardless of whether the reader expects ISO 8859-1 or UTF-8.
+//! <https://docs.oracle.com/javase/8/docs/api/java/util/Properties.html#load-java.io.Reader->
+
+use crate::error::{IntoError, PathElement, Result};
+use crate::markup::Markup;
+use crate::ordered::OrderedMap;
+use crate::pprint::{concat, Doc};
+use crate::runtime::Value;
+use crate::source::Span;
+
+/// Render a value as Java properties file.
+pub fn format_properties(caller: Span, v: &Value) -> Result<Doc> {
+    let mut formatter = Formatter::new(caller);
+
+    match v {
+        Value::Dict(kv) => formatter.top_level(kv),
+        _ => formatter.error("To format as properties, the top-level value must be a dict."),
+    }
+}
+
+/// Escape a key or value for use in a properties file.
+///
+/// In keys, separators (`=`, `:`, whitespace) and comment characters (`#`,
+/// `!`) must be escaped. In values, only leading whitespace would get lost, but
+/// we also escape a leading separator or comment character, to be safe.
+fn escape_properties(s: &str, is_key: bool, into: &mut String) {
+    use std::fmt::Write;
+
+    into.reserve(s.len());
+
+    for (i, ch) in s.chars().enumerate() {
+        match ch {
+            '\\' => into.push_str(r#"\\"#),
+            '\n' => into.push_str(r#"\n"#),
+            '\r' => into.push_str(r#"\r"#),
+            '\t' => into.push_str(r#"\t"#),
+            '\x0c' => into.push_str(r#"\f"#),
+            ' ' if is_key || i == 0 => into.push_str(r#"\ "#),
+            '=' | ':' | '#' | '!' if is_key || i == 0 => {
+                into.push('\\');
+                into.push(ch);
+            }
+            ' '..='~' => into.push(ch),
+            ch => {
+                // Characters outside the Basic Multilingual Plane need to be
+                // written as a UTF-16 surrogate pair.
+                let mut buf = [0_u16; 2];
+                for unit in ch.encode_utf16(&mut buf) {
+                    write!(into, "\\u{:04X}", unit)
+                        .expect("Writing into &mut String does not fail.");
+                }
+            }
+        }
+    }
+}
+
+/// Helper for formatting values as Java properties.
+///
+/// The formatter tracks the path in the value that we are formatting from, such
+/// that we can report the location of an error, in case an error occurs.
+struct Formatter {
+    /// The source location where properties formatting was triggered from.
+    caller: Span,
+
+    /// Where we currently are in the value to be formatted.
+    path: Vec<PathElement>,
+}
+
+impl Formatter {
+    pub fn new(caller: Span) -> Formatter {
+        Formatter {
+            caller,
+            path: Vec::new(),
+        }
+    }
+
+    /// Report an error at the current value path.
+    fn error<T>(&mut self, message: &'static str) -> Result<T> {
+        // Steal the path from the formatter and move it into the error. We have
+        // to leave an empty path in its place. This is fine, because returning
+        // the error prevents further formatting.
+        let mut path = Vec::new();
+        std::mem::swap(&mut self.path, &mut path);
+        self.caller.error(message).with_path(path).err()
+    }
+
+    /// Format a value, which must be a scalar.
+    fn value<'a>(&mut self, v: &'a Value) -> Result<Doc<'a>> {
+        let result = match v {
+            // Properties have no null, but an empty value is the closest thing.
+            Value::Null => Doc::Empty,
+            Value::Bool(true) => Doc::from("true").with_markup(Markup::Keyword),
+            Value::Bool(false) => Doc::from("false").with_markup(Markup::Keyword),
+            Value::Number(d) => Doc::from(d.format()).with_markup(Markup::Number),
+            Value::String(s) => {
+                let mut into = String::with_capacity(s.len());
+                escape_properties(s, false, &mut into);
+                Doc::from(into).with_markup(Markup::String)
+            }
+            Value::List(..) | Value::Set(..) | Value::Dict(..) => self.error(
+                "To format as properties, values must be strings, numbers, booleans, or null. \
+                Properties files cannot contain nested collections.",
+            )?,
+            Value::Function(..) => self.error("Functions cannot be exported as properties.")?,
+            Value::BuiltinFunction(..) => {
+                self.error("Functions cannot be exported as properties.")?
+            }
+            Value::BuiltinMethod { .. } => {
+                self.error("Methods cannot be exported as properties.")?
+            }
+        };
+        Ok(result)
+    }
+
+    fn top_level<'a>(&mut self, kv: &'a OrderedMap<Value, Value>) -> Result<Doc<'a>> {
+        let mut lines: Vec<Doc> = Vec::new();
+
+        for (k, v) in kv {
+            self.path.push(PathElement::Key(k.clone()));
+            let key = match k {
+                Value::String(k_str) => {
+                    let mut into = String::with_capacity(k_str.len());
+                    escape_properties(k_str, true, &mut into);
+                    into
+                }
+                _ => return self.error("To format as properties, keys must be strings."),
+            };
+            lines.push(concat! {
+                Doc::from(key).with_markup(Markup::Field)
+                "="
+                self.value(v)?
+                Doc::HardBreak
+            });
+            self.path.pop().expect("Push and pop are balanced.");
+        }
+
+        Ok(Doc::Concat(lines))
+    }
+}
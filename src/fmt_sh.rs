@@ -0,0 +1,205 @@
+// RCL -- A reasonable configuration language.
+// Copyright 2025 Ruud van Asseldonk
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// A copy of the License has been included in the root of the repository.
+
+//! Formatter that prints values as shell variable assignments.
+//!
+//! The output can be loaded into a shell with `source` or `.`. Strings are
+//! always single-quoted, in which every character is literal, so the values
+//! are safe to load even when they contain characters that are special to
+//! the shell.
+
+use crate::error::{IntoError, PathElement, Result};
+use crate::fmt_dotenv::is_env_var_name;
+use crate::markup::Markup;
+use crate::ordered::OrderedMap;
+use crate::pprint::{concat, Doc};
+use crate::runtime::Value;
+use crate::source::Span;
+
+/// The shell dialect to target.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Dialect {
+    /// POSIX shell, which has no arrays, only strings.
+    Posix,
+    /// Bash, where lists and sets become indexed arrays.
+    Bash,
+}
+
+/// Render a value as POSIX shell variable assignments.
+pub fn format_sh(caller: Span, v: &Value) -> Result<Doc> {
+    format_shell(Dialect::Posix, caller, v)
+}
+
+/// Render a value as Bash variable assignments.
+pub fn format_bash(caller: Span, v: &Value) -> Result<Doc> {
+    format_shell(Dialect::Bash, caller, v)
+}
+
+fn format_shell(dialect: Dialect, caller: Span, v: &Value) -> Result<Doc> {
+    let mut formatter = Formatter::new(dialect, caller);
+
+    match v {
+        Value::Dict(kv) => formatter.top_level(kv),
+        _ => formatter.error("To format as shell variables, the top-level value must be a dict."),
+    }
+}
+
+/// Wrap a string in single quotes for POSIX shell.
+///
+/// Inside single quotes, every character is literal, except for the single
+/// quote itself, which cannot occur inside single quotes at all. To include
+/// one, we close the quoted part, add an escaped quote, and reopen it, which
+/// turns `'` into `'\''`.
+///
+/// The shell cannot hold U+0000 in a variable, callers must reject it.
+pub fn quote_single(s: &str) -> String {
+    let mut into = String::with_capacity(s.len() + 2);
+    into.push('\'');
+    into.push_str(&s.replace('\'', r#"'\''"#));
+    into.push('\'');
+    into
+}
+
+/// Format a single-quoted string, preserving the line breaks in it verbatim.
+fn string<'a>(s: &str) -> Doc<'a> {
+    let quoted = quote_single(s);
+    let mut parts = Vec::new();
+    for (i, line) in quoted.split('\n').enumerate() {
+        if i > 0 {
+            // Raw breaks do not get indented, so the string stays intact.
+            parts.push(Doc::RawBreak);
+        }
+        parts.push(Doc::from(line.to_string()));
+    }
+    Doc::Concat(parts).with_markup(Markup::String)
+}
+
+/// Helper for formatting values as shell variables.
+///
+/// The formatter tracks the path in the value that we are formatting from, such
+/// that we can report the location of an error, in case an error occurs.
+struct Formatter {
+    /// Whether we can output arrays.
+    dialect: Dialect,
+
+    /// The source location where formatting was triggered from.
+    caller: Span,
+
+    /// Where we currently are in the value to be formatted.
+    path: Vec<PathElement>,
+}
+
+impl Formatter {
+    pub fn new(dialect: Dialect, caller: Span) -> Formatter {
+        Formatter {
+            dialect,
+            caller,
+            path: Vec::new(),
+        }
+    }
+
+    /// Report an error at the current value path.
+    fn error<T>(&mut self, message: &'static str) -> Result<T> {
+        // Steal the path from the formatter and move it into the error. We have
+        // to leave an empty path in its place. This is fine, because returning
+        // the error prevents further formatting.
+        let mut path = Vec::new();
+        std::mem::swap(&mut self.path, &mut path);
+        self.caller.error(message).with_path(path).err()
+    }
+
+    /// Format a scalar value as a shell word.
+    fn scalar<'a>(&mut self, v: &'a Value) -> Result<Doc<'a>> {
+        let result = match v {
+            // The shell has no null, an empty string is the closest thing.
+            Value::Null => Doc::from("''").with_markup(Markup::String),
+            Value::Bool(true) => Doc::from("true").with_markup(Markup::Keyword),
+            Value::Bool(false) => Doc::from("false").with_markup(Markup::Keyword),
+            Value::Number(d) => Doc::from(d.format()).with_markup(Markup::Number),
+            Value::String(s) if s.contains('\0') => {
+                self.error("Shell variables cannot hold control character U+0000.")?
+            }
+            Value::String(s) => string(s),
+            Value::List(..) | Value::Set(..) | Value::Dict(..) => {
+                self.error("To format as shell variables, arrays cannot be nested.")?
+            }
+            Value::Function(..) => {
+                self.error("Functions cannot be exported as shell variables.")?
+            }
+            Value::BuiltinFunction(..) => {
+                self.error("Functions cannot be exported as shell variables.")?
+            }
+            Value::BuiltinMethod { .. } => {
+                self.error("Methods cannot be exported as shell variables.")?
+            }
+        };
+        Ok(result)
+    }
+
+    /// Format an indexed array, for Bash.
+    fn array<'a>(&mut self, vs: impl Iterator<Item = &'a Value>) -> Result<Doc<'a>> {
+        let mut elements = vec![Doc::from("(")];
+        for (i, v) in vs.enumerate() {
+            if i > 0 {
+                elements.push(" ".into());
+            }
+            self.path.push(PathElement::Index(i));
+            elements.push(self.scalar(v)?);
+            self.path.pop().expect("Push and pop are balanced.");
+        }
+        elements.push(")".into());
+        Ok(Doc::Concat(elements))
+    }
+
+    /// Format the right-hand side of an assignment.
+    fn value<'a>(&mut self, v: &'a Value) -> Result<Doc<'a>> {
+        match (self.dialect, v) {
+            (Dialect::Bash, Value::List(vs)) => self.array(vs.iter()),
+            (Dialect::Bash, Value::Set(vs)) => self.array(vs.iter()),
+            (Dialect::Posix, Value::List(..) | Value::Set(..)) => {
+                let err = self
+                    .caller
+                    .error("To format as sh, values must not be lists or sets.")
+                    .with_help("Use '--format=bash' to output lists as Bash arrays.")
+                    .with_path(std::mem::take(&mut self.path));
+                err.err()
+            }
+            (_, Value::Dict(..)) => self.error(
+                "To format as shell variables, values must not be dicts, \
+                only the top-level value can be a dict.",
+            ),
+            _ => self.scalar(v),
+        }
+    }
+
+    fn top_level<'a>(&mut self, kv: &'a OrderedMap<Value, Value>) -> Result<Doc<'a>> {
+        let mut lines: Vec<Doc> = Vec::new();
+
+        for (k, v) in kv {
+            self.path.push(PathElement::Key(k.clone()));
+            let name = match k {
+                Value::String(name) if is_env_var_name(name) => name.as_ref(),
+                Value::String(..) => {
+                    return self.error(
+                        "To format as shell variables, keys must be valid shell identifiers: \
+                        letters, digits, and underscores, not starting with a digit.",
+                    )
+                }
+                _ => return self.error("To format as shell variables, keys must be strings."),
+            };
+            lines.push(concat! {
+                Doc::from(name).with_markup(Markup::Field)
+                "="
+                self.value(v)?
+                Doc::HardBreak
+            });
+            self.path.pop().expect("Push and pop are balanced.");
+        }
+
+        Ok(Doc::Concat(lines))
+    }
+}
@@ -9,10 +9,9 @@
 //!
 //! This formatter is similar to the one in [`crate::fmt_json`].
 
-use std::collections::BTreeMap;
-
 use crate::error::{IntoError, PathElement, Result};
 use crate::markup::Markup;
+use crate::ordered::OrderedMap;
 use crate::pprint::{concat, group, indent, Doc};
 use crate::runtime::Value;
 use crate::source::Span;
@@ -204,6 +203,10 @@ impl Formatter {
             Value::Null => self.error("Null cannot be exported as TOML.")?,
             Value::Bool(true) => Doc::from("true").with_markup(Markup::Keyword),
             Value::Bool(false) => Doc::from("false").with_markup(Markup::Keyword),
+            // TOML integers are 64-bit, larger integer literals are invalid.
+            Value::Number(d) if d.is_integer_literal() && d.to_i64().is_none() => {
+                self.error("To format as TOML, integers must fit in a 64-bit signed integer.")?
+            }
             Value::Number(d) => Doc::from(d.format()).with_markup(Markup::Number),
             Value::String(s) => self.string(s).with_markup(Markup::String),
             Value::List(vs) => self.array(vs.iter())?,
@@ -242,7 +245,7 @@ impl Formatter {
         Ok(())
     }
 
-    fn top_level<'a>(&mut self, kv: &'a BTreeMap<Value, Value>) -> Result<Doc<'a>> {
+    fn top_level<'a>(&mut self, kv: &'a OrderedMap<Value, Value>) -> Result<Doc<'a>> {
         let mut values: Vec<Doc> = Vec::new();
         let mut tables: Vec<Doc> = Vec::new();
         let mut arrays: Vec<Doc> = Vec::new();
@@ -0,0 +1,370 @@
+// RCL -- A reasonable configuration language.
+// Copyright 2025 Ruud van Asseldonk
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// A copy of the License has been included in the root of the repository.
+
+//! Formatters that print values as XML, and as Apple property lists.
+//!
+//! For `xml`, the top-level value must be a dict with a single key, the name
+//! of the root element. The value of a key determines the content of the
+//! element:
+//!
+//! * Strings, numbers, and booleans become text content.
+//! * Null becomes an empty element.
+//! * In a dict, keys that start with `@` become attributes, the key `#text`
+//!   becomes text content, and other keys become child elements.
+//! * A list or set becomes one element per item, all with the same name.
+//!
+//! For `plist`, the value maps directly onto the property list types: dicts
+//! become `<dict>`, lists and sets become `<array>`, and strings, numbers, and
+//! booleans become `<string>`, `<integer>` or `<real>`, and `<true/>` or
+//! `<false/>`.
+
+use crate::error::{IntoError, PathElement, Result};
+use crate::markup::Markup;
+use crate::pprint::{concat, indent, Doc};
+use crate::runtime::Value;
+use crate::source::Span;
+
+const XML_DECLARATION: &str = r#"<?xml version="1.0" encoding="UTF-8"?>"#;
+
+const PLIST_DOCTYPE: &str = r#"<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">"#;
+
+/// Render a value as XML.
+pub fn format_xml(caller: Span, v: &Value) -> Result<Doc> {
+    let mut formatter = Formatter::new(caller);
+
+    let root = match v {
+        Value::Dict(kv) if kv.len() == 1 => {
+            let (k, v) = kv.iter().next().expect("We checked the length.");
+            formatter.elements(k, v)?
+        }
+        _ => {
+            return formatter.error(
+                "To format as XML, the top-level value must be a dict with a single key, \
+                the name of the root element.",
+            )
+        }
+    };
+
+    Ok(concat! { XML_DECLARATION Doc::HardBreak root })
+}
+
+/// Render a value as Apple property list.
+pub fn format_plist(caller: Span, v: &Value) -> Result<Doc> {
+    let mut formatter = Formatter::new(caller);
+    let root = formatter.plist_value(v)?;
+    Ok(concat! {
+        XML_DECLARATION Doc::HardBreak
+        PLIST_DOCTYPE Doc::HardBreak
+        "<plist version=\"1.0\">" Doc::HardBreak
+        root Doc::HardBreak
+        "</plist>"
+    })
+}
+
+/// Return whether the string is a valid XML element or attribute name.
+///
+/// We only accept a subset of the names that XML allows, but this subset
+/// includes all the names that occur in practice, including namespace prefixes.
+/// <https://www.w3.org/TR/xml/#NT-Name>
+fn is_xml_name(s: &str) -> bool {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(ch) if ch.is_alphabetic() || ch == '_' => {
+            chars.all(|ch| ch.is_alphanumeric() || matches!(ch, '_' | '-' | '.' | ':'))
+        }
+        _ => false,
+    }
+}
+
+/// Return whether the character can occur in an XML 1.0 document.
+///
+/// <https://www.w3.org/TR/xml/#charsets>
+fn is_xml_char(ch: char) -> bool {
+    !ch.is_control() || matches!(ch, '\t' | '\n' | '\r') || (ch as u32) >= 0x7f
+}
+
+/// Helper for formatting values as XML.
+///
+/// The formatter tracks the path in the value that we are formatting from, such
+/// that we can report the location of an error, in case an error occurs.
+struct Formatter {
+    /// The source location where XML formatting was triggered from.
+    caller: Span,
+
+    /// Where we currently are in the value to be formatted.
+    path: Vec<PathElement>,
+}
+
+impl Formatter {
+    pub fn new(caller: Span) -> Formatter {
+        Formatter {
+            caller,
+            path: Vec::new(),
+        }
+    }
+
+    /// Report an error at the current value path.
+    fn error<T>(&mut self, message: &'static str) -> Result<T> {
+        // Steal the path from the formatter and move it into the error. We have
+        // to leave an empty path in its place. This is fine, because returning
+        // the error prevents further formatting.
+        let mut path = Vec::new();
+        std::mem::swap(&mut self.path, &mut path);
+        self.caller.error(message).with_path(path).err()
+    }
+
+    /// Escape text content, or an attribute value if `is_attribute` is set.
+    ///
+    /// Line breaks in text content are preserved verbatim as raw breaks, so
+    /// they do not get indented. In attribute values, line breaks would get
+    /// normalized to spaces, so there we use character references.
+    fn text<'a>(&mut self, s: &str, is_attribute: bool) -> Result<Doc<'a>> {
+        let mut parts = Vec::new();
+        let mut into = String::with_capacity(s.len());
+        for ch in s.chars() {
+            match ch {
+                '&' => into.push_str("&amp;"),
+                '<' => into.push_str("&lt;"),
+                '>' => into.push_str("&gt;"),
+                '"' if is_attribute => into.push_str("&quot;"),
+                '\n' if is_attribute => into.push_str("&#10;"),
+                '\r' => into.push_str("&#13;"),
+                '\t' if is_attribute => into.push_str("&#9;"),
+                '\n' => {
+                    parts.push(Doc::from(std::mem::take(&mut into)));
+                    parts.push(Doc::RawBreak);
+                }
+                ch if !is_xml_char(ch) => {
+                    return self.error("Control characters cannot be represented in XML.")
+                }
+                ch => into.push(ch),
+            }
+        }
+        parts.push(Doc::from(into));
+        Ok(Doc::Concat(parts))
+    }
+
+    /// Push the key to the path, and check that it is a valid name.
+    fn push_name<'a>(&mut self, key: &'a Value) -> Result<&'a str> {
+        self.path.push(PathElement::Key(key.clone()));
+        match key {
+            Value::String(name) if is_xml_name(name) => Ok(name),
+            Value::String(..) => self.error(
+                "To format as XML, element names must be valid XML names. \
+                Keys that start with '@' are attributes.",
+            ),
+            _ => self.error("To format as XML, keys must be strings."),
+        }
+    }
+
+    /// Format a scalar value as text content.
+    fn scalar_text<'a>(&mut self, v: &'a Value, is_attribute: bool) -> Result<Doc<'a>> {
+        let result = match v {
+            Value::Bool(true) => Doc::from("true").with_markup(Markup::Keyword),
+            Value::Bool(false) => Doc::from("false").with_markup(Markup::Keyword),
+            Value::Number(d) => Doc::from(d.format()).with_markup(Markup::Number),
+            Value::String(s) => self.text(s, is_attribute)?.with_markup(Markup::String),
+            Value::Null => self.error(
+                "To format as XML, attributes and text content must be strings, \
+                numbers, or booleans, not null.",
+            )?,
+            Value::List(..) | Value::Set(..) | Value::Dict(..) => self.error(
+                "To format as XML, attributes and text content must be strings, \
+                numbers, or booleans. Lists can only occur as the value of an element.",
+            )?,
+            Value::Function(..) => self.error("Functions cannot be exported as XML.")?,
+            Value::BuiltinFunction(..) => self.error("Functions cannot be exported as XML.")?,
+            Value::BuiltinMethod { .. } => self.error("Methods cannot be exported as XML.")?,
+        };
+        Ok(result)
+    }
+
+    /// Format the elements for a key-value pair.
+    ///
+    /// This is a single element, unless the value is a list, in which case
+    /// there is one element per item.
+    fn elements<'a>(&mut self, key: &'a Value, v: &'a Value) -> Result<Doc<'a>> {
+        let name = self.push_name(key)?;
+        let items: Vec<&'a Value> = match v {
+            Value::List(xs) => xs.iter().collect(),
+            Value::Set(xs) => xs.iter().collect(),
+            _ => {
+                let result = self.element(name, v)?;
+                self.path.pop().expect("Push and pop are balanced.");
+                return Ok(result);
+            }
+        };
+
+        let mut parts = Vec::new();
+        for (i, item) in items.iter().enumerate() {
+            self.path.push(PathElement::Index(i));
+            if matches!(item, Value::List(..) | Value::Set(..)) {
+                return self.error(
+                    "To format as XML, lists cannot be nested directly. \
+                    Wrap the inner list in a dict to give its elements a name.",
+                );
+            }
+            if i > 0 {
+                parts.push(Doc::HardBreak);
+            }
+            parts.push(self.element(name, item)?);
+            self.path.pop().expect("Push and pop are balanced.");
+        }
+        self.path.pop().expect("Push and pop are balanced.");
+
+        Ok(Doc::Concat(parts))
+    }
+
+    /// Format a single element with the given name, for a non-list value.
+    fn element<'a>(&mut self, name: &'a str, v: &'a Value) -> Result<Doc<'a>> {
+        let name_doc = || Doc::from(name).with_markup(Markup::Field);
+
+        let kv = match v {
+            Value::Null => return Ok(concat! { "<" name_doc() "/>" }),
+            Value::Dict(kv) => kv,
+            scalar => {
+                let text = self.scalar_text(scalar, false)?;
+                return Ok(concat! { "<" name_doc() ">" text "</" name_doc() ">" });
+            }
+        };
+
+        let mut attributes = Vec::new();
+        let mut text = None;
+        let mut children = Vec::new();
+
+        for (k, child) in kv.iter() {
+            match k {
+                Value::String(k_str) if k_str.as_ref() == "#text" => {
+                    self.path.push(PathElement::Key(k.clone()));
+                    text = Some(self.scalar_text(child, false)?);
+                    self.path.pop().expect("Push and pop are balanced.");
+                }
+                Value::String(k_str) if k_str.starts_with('@') => {
+                    self.path.push(PathElement::Key(k.clone()));
+                    let attr_name = &k_str[1..];
+                    if !is_xml_name(attr_name) {
+                        return self
+                            .error("To format as XML, attribute names must be valid XML names.");
+                    }
+                    let value = self.scalar_text(child, true)?;
+                    attributes.push(concat! {
+                        " " Doc::from(attr_name).with_markup(Markup::Field) "=\"" value "\""
+                    });
+                    self.path.pop().expect("Push and pop are balanced.");
+                }
+                _ => {
+                    if !children.is_empty() {
+                        children.push(Doc::HardBreak);
+                    }
+                    children.push(self.elements(k, child)?);
+                }
+            }
+        }
+
+        let open = concat! { "<" name_doc() Doc::Concat(attributes) };
+
+        let result = match text {
+            Some(..) if !children.is_empty() => {
+                self.path.push(PathElement::Key("#text".into()));
+                return self.error(
+                    "To format as XML, an element cannot have both text content \
+                    and child elements.",
+                );
+            }
+            Some(text) => concat! { open ">" text "</" name_doc() ">" },
+            None if children.is_empty() => concat! { open "/>" },
+            None => concat! {
+                open ">"
+                indent! { Doc::HardBreak Doc::Concat(children) }
+                Doc::HardBreak
+                "</" name_doc() ">"
+            },
+        };
+
+        Ok(result)
+    }
+
+    /// Format a value as property list element.
+    fn plist_value<'a>(&mut self, v: &'a Value) -> Result<Doc<'a>> {
+        let tag = |name: &'static str, content: Doc<'a>| -> Doc<'a> {
+            concat! {
+                "<" Doc::from(name).with_markup(Markup::Keyword) ">"
+                content
+                "</" Doc::from(name).with_markup(Markup::Keyword) ">"
+            }
+        };
+
+        let result = match v {
+            Value::Null => self.error("Null cannot be exported as plist.")?,
+            Value::Bool(true) => Doc::from("<true/>").with_markup(Markup::Keyword),
+            Value::Bool(false) => Doc::from("<false/>").with_markup(Markup::Keyword),
+            // Like in the json output, we preserve the form of the number.
+            // Numbers without decimal point or exponent are integers.
+            Value::Number(d) if d.is_integer_literal() && d.to_i64().is_none() => {
+                self.error("To format as plist, integers must fit in a 64-bit signed integer.")?
+            }
+            Value::Number(d) if d.is_integer_literal() => {
+                tag("integer", Doc::from(d.format()).with_markup(Markup::Number))
+            }
+            Value::Number(d) => tag("real", Doc::from(d.format()).with_markup(Markup::Number)),
+            Value::String(s) => {
+                let text = self.text(s, false)?.with_markup(Markup::String);
+                tag("string", text)
+            }
+            Value::List(xs) => self.plist_array(xs.iter())?,
+            Value::Set(xs) => self.plist_array(xs.iter())?,
+            Value::Dict(kv) if kv.is_empty() => "<dict/>".into(),
+            Value::Dict(kv) => {
+                let mut elements = Vec::new();
+                for (k, v) in kv.iter() {
+                    self.path.push(PathElement::Key(k.clone()));
+                    let key = match k {
+                        Value::String(k_str) => self.text(k_str, false)?.with_markup(Markup::Field),
+                        _ => return self.error("To export as plist, keys must be strings."),
+                    };
+                    elements.push(Doc::HardBreak);
+                    elements.push(tag("key", key));
+                    elements.push(Doc::HardBreak);
+                    elements.push(self.plist_value(v)?);
+                    self.path.pop().expect("Push and pop are balanced.");
+                }
+                concat! {
+                    "<dict>"
+                    indent! { Doc::Concat(elements) }
+                    Doc::HardBreak
+                    "</dict>"
+                }
+            }
+            Value::Function(..) => self.error("Functions cannot be exported as plist.")?,
+            Value::BuiltinFunction(..) => self.error("Functions cannot be exported as plist.")?,
+            Value::BuiltinMethod { .. } => self.error("Methods cannot be exported as plist.")?,
+        };
+        Ok(result)
+    }
+
+    fn plist_array<'a>(&mut self, vs: impl Iterator<Item = &'a Value>) -> Result<Doc<'a>> {
+        let mut elements = Vec::new();
+        for (i, v) in vs.enumerate() {
+            self.path.push(PathElement::Index(i));
+            elements.push(Doc::HardBreak);
+            elements.push(self.plist_value(v)?);
+            self.path.pop().expect("Push and pop are balanced.");
+        }
+
+        let result = if elements.is_empty() {
+            "<array/>".into()
+        } else {
+            concat! {
+                "<array>"
+                indent! { Doc::Concat(elements) }
+                Doc::HardBreak
+                "</array>"
+            }
+        };
+        Ok(result)
+    }
+}
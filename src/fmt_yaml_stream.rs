@@ -14,6 +14,17 @@ use crate::pprint::Doc;
 use crate::runtime::Value;
 use crate::source::Span;
 
+/// Render a value as a single YAML document.
+pub fn format_yaml(caller: Span, v: &Value) -> Result<Doc> {
+    let mut formatter = Formatter::new(caller);
+    let parts = vec![
+        Doc::str("---").with_markup(Markup::Comment),
+        Doc::HardBreak,
+        formatter.value(v)?,
+    ];
+    Ok(Doc::Concat(parts))
+}
+
 /// Render a value in YAML stream format.
 pub fn format_yaml_stream(caller: Span, v: &Value) -> Result<Doc> {
     let elements = match v {
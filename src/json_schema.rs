@@ -0,0 +1,1005 @@
+// RCL -- A reasonable configuration language.
+// Copyright 2025 Ruud van Asseldonk
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// A copy of the License has been included in the root of the repository.
+
+//! Conversion between RCL types and JSON Schema, and validation against it.
+//!
+//! We target JSON Schema draft 2020-12, see <https://json-schema.org/>.
+
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+use crate::decimal::Decimal;
+use crate::error::{Error, IntoError, PathElement, Result};
+use crate::fmt_rcl::format_rcl;
+use crate::markup::Markup;
+use crate::ordered::OrderedMap;
+use crate::pprint::{concat, Doc};
+use crate::regex::Regex;
+use crate::runtime::Value;
+use crate::source::Span;
+use crate::types::{SourcedType, Type};
+
+/// The meta-schema that identifies the JSON Schema dialect we output.
+pub const DRAFT_2020_12: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Build a dict value from string keys.
+fn object<const N: usize>(fields: [(&str, Value); N]) -> Value {
+    let kv: OrderedMap<Value, Value> = fields.into_iter().map(|(k, v)| (k.into(), v)).collect();
+    Value::Dict(Rc::new(kv))
+}
+
+/// Build the schema `{ "type": type_name }`.
+fn of_type(type_name: &str) -> Value {
+    object([("type", type_name.into())])
+}
+
+/// Convert a type into a JSON Schema document, including the `$schema` key.
+///
+/// The span `at` is used to report errors for types that have no source span.
+pub fn type_to_schema_document(at: Span, type_: &SourcedType) -> Result<Value> {
+    let schema = type_to_schema(at, type_)?;
+    let result = match schema {
+        Value::Dict(kv) => {
+            // Put `$schema` first, it identifies the rest of the document.
+            let mut result = OrderedMap::new();
+            result.insert("$schema".into(), DRAFT_2020_12.into());
+            result.extend(kv.iter().map(|(k, v)| (k.clone(), v.clone())));
+            Value::Dict(Rc::new(result))
+        }
+        // For the schemas `true` and `false`, we can't add a key, but they
+        // are self-explanatory anyway.
+        not_dict => not_dict,
+    };
+    Ok(result)
+}
+
+/// Convert a type into a JSON Schema.
+///
+/// The span `at` is used to report errors for types that have no source span.
+fn type_to_schema(at: Span, type_: &SourcedType) -> Result<Value> {
+    let result = match &type_.type_ {
+        // The empty schema accepts any value, the schema `false` accepts none.
+        Type::Any => object([]),
+        Type::Void => Value::Bool(false),
+        Type::Null => of_type("null"),
+        Type::Bool => of_type("boolean"),
+        Type::Number => of_type("number"),
+        Type::String => of_type("string"),
+        Type::List(elem) => object([
+            ("type", "array".into()),
+            ("items", type_to_schema(at, elem)?),
+        ]),
+        Type::Set(elem) => object([
+            ("type", "array".into()),
+            ("items", type_to_schema(at, elem)?),
+            ("uniqueItems", Value::Bool(true)),
+        ]),
+        Type::Dict(dict) => {
+            if !matches!(dict.key.type_, Type::String) {
+                return dict
+                    .key
+                    .source
+                    .span()
+                    .unwrap_or(at)
+                    .error(concat! {
+                        "JSON Schema can only describe dicts with '"
+                        Doc::highlight("String")
+                        "' keys."
+                    })
+                    .with_help("JSON objects have string keys, other keys cannot be exported.")
+                    .err();
+            }
+            object([
+                ("type", "object".into()),
+                ("additionalProperties", type_to_schema(at, &dict.value)?),
+            ])
+        }
+        Type::Union(union) => {
+            let members = union
+                .members
+                .iter()
+                .map(|member| type_to_schema(at, member))
+                .collect::<Result<Vec<_>>>()?;
+            object([("anyOf", Value::List(Rc::new(members)))])
+        }
+        Type::Function(..) => {
+            return type_
+                .source
+                .span()
+                .unwrap_or(at)
+                .error("Functions cannot be described by a JSON Schema.")
+                .err();
+        }
+    };
+    Ok(result)
+}
+
+/// The maximum depth of nested schemas that we follow during validation.
+///
+/// Schemas can reference themselves, so without a limit, a schema such as
+/// `{"$ref": "#"}` would never terminate.
+const MAX_VALIDATION_DEPTH: u32 = 500;
+
+/// Check a value against a JSON Schema, and return all violations.
+///
+/// Violations are reported at `value_span`, with the path into the value. If
+/// the schema itself is invalid, we return an error at `schema_span` instead.
+pub fn validate(
+    schema_span: Span,
+    schema: &Value,
+    value_span: Span,
+    value: &Value,
+) -> Result<Vec<Error>> {
+    let mut validator = Validator {
+        root: schema,
+        schema_span,
+        value_span,
+        schema_path: Vec::new(),
+        value_path: Vec::new(),
+        violations: Vec::new(),
+        depth: 0,
+    };
+    validator.validate(schema, value)?;
+    Ok(validator.violations)
+}
+
+/// Return whether a number is an integer, regardless of how it is written.
+fn is_integer(d: &Decimal) -> bool {
+    d.exponent as i32 - d.decimals as i32 >= 0 || d.to_integer().is_some()
+}
+
+/// Return whether `x` is an integer multiple of `m`.
+fn is_multiple_of(x: &Decimal, m: &Decimal) -> bool {
+    match x.checked_div_exact(m) {
+        Some(q) => is_integer(&q),
+        // Either the quotient has no finite decimal representation, in which
+        // case it is not an integer, or we hit overflow, and then the best we
+        // can do is to fall back to floats.
+        None => {
+            let q = x.to_f64_lossy() / m.to_f64_lossy();
+            q.is_finite() && q.fract() == 0.0
+        }
+    }
+}
+
+/// Return the JSON type name of a value.
+fn json_type_name(v: &Value) -> &'static str {
+    match v {
+        Value::Null => "null",
+        Value::Bool(..) => "boolean",
+        Value::Number(..) => "number",
+        Value::String(..) => "string",
+        Value::List(..) | Value::Set(..) => "array",
+        Value::Dict(..) => "object",
+        Value::Function(..) | Value::BuiltinFunction(..) | Value::BuiltinMethod(..) => "function",
+    }
+}
+
+/// Describe a JSON type name for use in a sentence, with article.
+fn describe_type(type_name: &str) -> &'static str {
+    match type_name {
+        "null" => "null",
+        "boolean" => "a boolean",
+        "integer" => "an integer",
+        "number" => "a number",
+        "string" => "a string",
+        "array" => "an array",
+        "object" => "an object",
+        _ => "a function",
+    }
+}
+
+/// Return whether a value is an instance of the JSON Schema type.
+fn matches_type(type_name: &str, v: &Value) -> bool {
+    match (type_name, v) {
+        ("integer", Value::Number(d)) => is_integer(d),
+        (t, v) => t == json_type_name(v),
+    }
+}
+
+/// Compare values for equality with JSON semantics, where lists and sets are both arrays.
+fn json_eq(a: &Value, b: &Value) -> bool {
+    fn seq_eq<'a>(
+        xs: impl ExactSizeIterator<Item = &'a Value>,
+        ys: impl ExactSizeIterator<Item = &'a Value>,
+    ) -> bool {
+        xs.len() == ys.len() && xs.zip(ys).all(|(x, y)| json_eq(x, y))
+    }
+    match (a, b) {
+        (Value::List(xs), Value::List(ys)) => seq_eq(xs.iter(), ys.iter()),
+        (Value::List(xs), Value::Set(ys)) => seq_eq(xs.iter(), ys.iter()),
+        (Value::Set(xs), Value::List(ys)) => seq_eq(xs.iter(), ys.iter()),
+        (Value::Set(xs), Value::Set(ys)) => seq_eq(xs.iter(), ys.iter()),
+        (Value::Dict(xs), Value::Dict(ys)) => {
+            xs.len() == ys.len()
+                && xs
+                    .iter()
+                    .all(|(k, x)| ys.get(k).map(|y| json_eq(x, y)).unwrap_or(false))
+        }
+        _ => a == b,
+    }
+}
+
+/// Look up a keyword in a schema object.
+fn keyword<'a>(schema: &'a OrderedMap<Value, Value>, key: &str) -> Option<&'a Value> {
+    schema.get(&Value::from(key))
+}
+
+/// Helper for validating a value against a JSON Schema.
+///
+/// The validator tracks the path in the schema and in the value, so that we
+/// can report where a violation happened, and which part of the schema it
+/// violates.
+struct Validator<'a> {
+    /// The root of the schema, which `$ref` pointers are relative to.
+    root: &'a Value,
+
+    /// The source location of the schema document.
+    schema_span: Span,
+
+    /// The source location where the value to validate came from.
+    value_span: Span,
+
+    /// Where we currently are in the schema.
+    schema_path: Vec<PathElement>,
+
+    /// Where we currently are in the value.
+    value_path: Vec<PathElement>,
+
+    /// The violations found so far.
+    violations: Vec<Error>,
+
+    /// The current number of nested schemas, to detect reference loops.
+    depth: u32,
+}
+
+impl<'a> Validator<'a> {
+    /// Report an error in the schema itself, at the current schema path.
+    fn schema_error<T, M>(&mut self, message: M) -> Result<T>
+    where
+        Doc<'static>: From<M>,
+    {
+        let path = std::mem::take(&mut self.schema_path);
+        Error::new(message)
+            .with_origin(self.schema_span)
+            .with_path(path)
+            .err()
+    }
+
+    /// Report an error in the schema itself, with a hint on how to fix it.
+    fn schema_error_help<T>(&mut self, message: &'static str, help: &'static str) -> Result<T> {
+        let path = std::mem::take(&mut self.schema_path);
+        Error::new(message)
+            .with_origin(self.schema_span)
+            .with_path(path)
+            .with_help(help)
+            .err()
+    }
+
+    /// Format the current schema path as JSON pointer.
+    fn schema_pointer(&self) -> String {
+        let mut result = "#".to_string();
+        for elem in self.schema_path.iter() {
+            result.push('/');
+            match elem {
+                PathElement::Key(Value::String(k)) => {
+                    result.push_str(&k.replace('~', "~0").replace('/', "~1"))
+                }
+                PathElement::Key(k) => result.push_str(&format!("{k:?}")),
+                PathElement::Index(i) => result.push_str(&i.to_string()),
+            }
+        }
+        result
+    }
+
+    /// Point to the part of the schema that a violation violates.
+    fn violation_help(&self) -> Doc<'static> {
+        let pointer = Doc::from(self.schema_pointer()).with_markup(Markup::Highlight);
+        concat! { "Required by '" pointer "' in the schema." }
+    }
+
+    /// Record a violation at the current value path.
+    fn violation(&mut self, message: impl Into<Doc<'static>>) {
+        let help = self.violation_help();
+        let err = Error::new(message.into())
+            .with_origin(self.value_span)
+            .with_path(self.value_path.clone())
+            .with_help(help);
+        self.violations.push(err);
+    }
+
+    /// Return whether the value matches the schema, without recording violations.
+    fn is_valid(&mut self, schema: &'a Value, value: &Value) -> Result<bool> {
+        let n = self.violations.len();
+        self.validate(schema, value)?;
+        let is_valid = self.violations.len() == n;
+        self.violations.truncate(n);
+        Ok(is_valid)
+    }
+
+    /// Validate a value against a subschema at `schema[key]`.
+    fn validate_sub(&mut self, key: &str, schema: &'a Value, value: &Value) -> Result<()> {
+        self.schema_path.push(PathElement::Key(key.into()));
+        self.validate(schema, value)?;
+        self.schema_path.pop().expect("Push and pop are balanced.");
+        Ok(())
+    }
+
+    /// Read a keyword that must be a number.
+    fn number_keyword(
+        &mut self,
+        schema: &'a OrderedMap<Value, Value>,
+        key: &'static str,
+    ) -> Result<Option<&'a Decimal>> {
+        match keyword(schema, key) {
+            None => Ok(None),
+            Some(Value::Number(d)) => Ok(Some(d)),
+            Some(..) => {
+                self.schema_path.push(PathElement::Key(key.into()));
+                self.schema_error(concat! { "'" key "' must be a number." })
+            }
+        }
+    }
+
+    /// Read a keyword that must be a non-negative integer.
+    fn count_keyword(
+        &mut self,
+        schema: &'a OrderedMap<Value, Value>,
+        key: &'static str,
+    ) -> Result<Option<usize>> {
+        match keyword(schema, key).map(|v| v.to_i64()) {
+            None => Ok(None),
+            Some(Some(n)) if n >= 0 => Ok(Some(n as usize)),
+            Some(..) => {
+                self.schema_path.push(PathElement::Key(key.into()));
+                self.schema_error(concat! { "'" key "' must be a non-negative integer." })
+            }
+        }
+    }
+
+    /// Read a keyword that must be a list.
+    fn list_keyword(
+        &mut self,
+        schema: &'a OrderedMap<Value, Value>,
+        key: &'static str,
+    ) -> Result<Option<&'a [Value]>> {
+        match keyword(schema, key) {
+            None => Ok(None),
+            Some(Value::List(xs)) => Ok(Some(&xs[..])),
+            Some(..) => {
+                self.schema_path.push(PathElement::Key(key.into()));
+                self.schema_error(concat! { "'" key "' must be a list." })
+            }
+        }
+    }
+
+    /// Read a keyword that must be a dict.
+    fn dict_keyword(
+        &mut self,
+        schema: &'a OrderedMap<Value, Value>,
+        key: &'static str,
+    ) -> Result<Option<&'a OrderedMap<Value, Value>>> {
+        match keyword(schema, key) {
+            None => Ok(None),
+            Some(Value::Dict(kv)) => Ok(Some(kv)),
+            Some(..) => {
+                self.schema_path.push(PathElement::Key(key.into()));
+                self.schema_error(concat! { "'" key "' must be a dict." })
+            }
+        }
+    }
+
+    /// Compile a regular expression, at the current schema path.
+    fn pattern(&mut self, pattern: &Value) -> Result<Regex> {
+        let pattern = match pattern {
+            Value::String(p) => p,
+            _ => return self.schema_error("A pattern must be a string."),
+        };
+        match Regex::new(pattern) {
+            Ok(regex) => Ok(regex),
+            Err(err) => self.schema_error(concat! {
+                "Invalid regular expression '" Doc::highlight(pattern).into_owned() "'. "
+                err.message " At byte offset " err.range.start.to_string() "."
+            }),
+        }
+    }
+
+    fn validate(&mut self, schema: &'a Value, value: &Value) -> Result<()> {
+        self.depth += 1;
+        if self.depth > MAX_VALIDATION_DEPTH {
+            return self
+                .schema_error("The schema is nested too deeply, or it contains a reference loop.");
+        }
+        let result = self.validate_inner(schema, value);
+        self.depth -= 1;
+        result
+    }
+
+    fn validate_inner(&mut self, schema: &'a Value, value: &Value) -> Result<()> {
+        let kv = match schema {
+            Value::Bool(true) => return Ok(()),
+            Value::Bool(false) => {
+                self.violation("The schema does not allow any value here.");
+                return Ok(());
+            }
+            Value::Dict(kv) => kv,
+            _ => return self.schema_error("A schema must be a dict or a boolean."),
+        };
+
+        if let Some(reference) = keyword(kv, "$ref") {
+            self.validate_ref(reference, value)?;
+        }
+        self.validate_type(kv, value)?;
+        self.validate_enum_const(kv, value)?;
+
+        match value {
+            Value::Number(x) => self.validate_number(kv, x)?,
+            Value::String(s) => self.validate_string(kv, s)?,
+            Value::List(xs) => self.validate_array(kv, &xs.iter().collect::<Vec<_>>())?,
+            Value::Set(xs) => self.validate_array(kv, &xs.iter().collect::<Vec<_>>())?,
+            Value::Dict(fields) => self.validate_object(kv, fields, value)?,
+            _ => {}
+        }
+
+        self.validate_combinators(kv, value)
+    }
+
+    fn validate_ref(&mut self, reference: &'a Value, value: &Value) -> Result<()> {
+        self.schema_path.push(PathElement::Key("$ref".into()));
+        let pointer = match reference {
+            Value::String(r) if r.starts_with('#') => &r[1..],
+            Value::String(..) => {
+                return self.schema_error_help(
+                    "Only references within the same schema are supported.",
+                    "References must start with '#', such as '#/$defs/name'.",
+                )
+            }
+            _ => return self.schema_error("'$ref' must be a string."),
+        };
+
+        let mut target = self.root;
+        let mut target_path = Vec::new();
+        if !pointer.is_empty() {
+            if !pointer.starts_with('/') {
+                return self
+                    .schema_error("References must be JSON pointers, such as '#/$defs/name'.");
+            }
+            for token in pointer[1..].split('/') {
+                let token = token.replace("~1", "/").replace("~0", "~");
+                let next = match target {
+                    Value::Dict(kv) => {
+                        let key = Value::from(&token[..]);
+                        let next = kv.get(&key);
+                        target_path.push(PathElement::Key(key));
+                        next
+                    }
+                    Value::List(xs) => {
+                        let i = token.parse::<usize>().ok();
+                        target_path.push(PathElement::Index(i.unwrap_or(0)));
+                        i.and_then(|i| xs.get(i))
+                    }
+                    _ => None,
+                };
+                target = match next {
+                    Some(t) => t,
+                    None => {
+                        return self.schema_error(concat! {
+                            "Reference '" Doc::highlight(pointer).into_owned() "' does not point to a schema."
+                        })
+                    }
+                };
+            }
+        }
+
+        // While we validate the target, the schema path is where the target
+        // is, so that violations point there.
+        let mut path = std::mem::replace(&mut self.schema_path, target_path);
+        self.validate(target, value)?;
+        std::mem::swap(&mut self.schema_path, &mut path);
+        self.schema_path.pop().expect("Push and pop are balanced.");
+        Ok(())
+    }
+
+    fn validate_type(&mut self, schema: &'a OrderedMap<Value, Value>, value: &Value) -> Result<()> {
+        let types: Vec<&'a Value> = match keyword(schema, "type") {
+            None => return Ok(()),
+            Some(Value::List(ts)) => ts.iter().collect(),
+            Some(t) => vec![t],
+        };
+        self.schema_path.push(PathElement::Key("type".into()));
+
+        let mut type_names = Vec::with_capacity(types.len());
+        for t in types {
+            match t {
+                Value::String(t)
+                    if matches!(
+                        t.as_ref(),
+                        "null" | "boolean" | "integer" | "number" | "string" | "array" | "object"
+                    ) =>
+                {
+                    type_names.push(t.as_ref())
+                }
+                _ => {
+                    return self.schema_error_help(
+                        "'type' must be a JSON type name, or a list of them.",
+                        "The types are 'null', 'boolean', 'integer', 'number', \
+                        'string', 'array', and 'object'.",
+                    )
+                }
+            }
+        }
+
+        if !type_names.iter().any(|t| matches_type(t, value)) {
+            let mut msg = vec![Doc::from("Expected ")];
+            for (i, t) in type_names.iter().enumerate() {
+                if i > 0 {
+                    msg.push(" or ".into());
+                }
+                msg.push(describe_type(t).into());
+            }
+            msg.push(", but got ".into());
+            msg.push(describe_type(json_type_name(value)).into());
+            msg.push(".".into());
+            self.violation(Doc::Concat(msg));
+        }
+
+        self.schema_path.pop().expect("Push and pop are balanced.");
+        Ok(())
+    }
+
+    fn validate_enum_const(
+        &mut self,
+        schema: &'a OrderedMap<Value, Value>,
+        value: &Value,
+    ) -> Result<()> {
+        if let Some(options) = self.list_keyword(schema, "enum")? {
+            if !options.iter().any(|option| json_eq(option, value)) {
+                self.schema_path.push(PathElement::Key("enum".into()));
+                let mut msg = vec![Doc::from("Expected one of ")];
+                for (i, option) in options.iter().enumerate() {
+                    if i > 0 {
+                        msg.push(", ".into());
+                    }
+                    msg.push(format_rcl(option).into_owned());
+                }
+                msg.push(", but got ".into());
+                msg.push(format_rcl(value).into_owned());
+                msg.push(".".into());
+                self.violation(Doc::Concat(msg));
+                self.schema_path.pop().expect("Push and pop are balanced.");
+            }
+        }
+        if let Some(expected) = keyword(schema, "const") {
+            if !json_eq(expected, value) {
+                self.schema_path.push(PathElement::Key("const".into()));
+                self.violation(concat! {
+                    "Expected " format_rcl(expected).into_owned()
+                    ", but got " format_rcl(value).into_owned() "."
+                });
+                self.schema_path.pop().expect("Push and pop are balanced.");
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_number(&mut self, schema: &'a OrderedMap<Value, Value>, x: &Decimal) -> Result<()> {
+        let checks: [(&'static str, &'static str, &[Ordering]); 4] = [
+            (
+                "minimum",
+                "a number of at least ",
+                &[Ordering::Equal, Ordering::Greater],
+            ),
+            (
+                "exclusiveMinimum",
+                "a number greater than ",
+                &[Ordering::Greater],
+            ),
+            (
+                "maximum",
+                "a number of at most ",
+                &[Ordering::Less, Ordering::Equal],
+            ),
+            ("exclusiveMaximum", "a number less than ", &[Ordering::Less]),
+        ];
+        for (key, expected, allowed) in checks {
+            if let Some(m) = self.number_keyword(schema, key)? {
+                if !allowed.contains(&x.cmp(m)) {
+                    self.schema_path.push(PathElement::Key(key.into()));
+                    self.violation(concat! {
+                        "Expected " expected m.format() ", but got " x.format() "."
+                    });
+                    self.schema_path.pop().expect("Push and pop are balanced.");
+                }
+            }
+        }
+
+        if let Some(m) = self.number_keyword(schema, "multipleOf")? {
+            self.schema_path.push(PathElement::Key("multipleOf".into()));
+            if m.signum() <= 0 {
+                return self.schema_error("'multipleOf' must be greater than zero.");
+            }
+            if !is_multiple_of(x, m) {
+                self.violation(concat! {
+                    "Expected a multiple of " m.format() ", but got " x.format() "."
+                });
+            }
+            self.schema_path.pop().expect("Push and pop are balanced.");
+        }
+
+        Ok(())
+    }
+
+    fn validate_string(&mut self, schema: &'a OrderedMap<Value, Value>, s: &str) -> Result<()> {
+        // Note, JSON Schema counts length in code points.
+        let n = s.chars().count();
+        if let Some(min) = self.count_keyword(schema, "minLength")? {
+            if n < min {
+                self.schema_path.push(PathElement::Key("minLength".into()));
+                self.violation(concat! {
+                    "Expected a string with a length of at least " min.to_string() ", but got "
+                    n.to_string() "."
+                });
+                self.schema_path.pop().expect("Push and pop are balanced.");
+            }
+        }
+        if let Some(max) = self.count_keyword(schema, "maxLength")? {
+            if n > max {
+                self.schema_path.push(PathElement::Key("maxLength".into()));
+                self.violation(concat! {
+                    "Expected a string with a length of at most " max.to_string() ", but got "
+                    n.to_string() "."
+                });
+                self.schema_path.pop().expect("Push and pop are balanced.");
+            }
+        }
+        if let Some(pattern) = keyword(schema, "pattern") {
+            self.schema_path.push(PathElement::Key("pattern".into()));
+            let regex = self.pattern(pattern)?;
+            if !regex.is_match(s) {
+                self.violation(concat! {
+                    "Expected a string that matches "
+                    format_rcl(pattern).into_owned() ", but got "
+                    format_rcl(&Value::from(s)).into_owned() "."
+                });
+            }
+            self.schema_path.pop().expect("Push and pop are balanced.");
+        }
+        Ok(())
+    }
+
+    fn validate_array(
+        &mut self,
+        schema: &'a OrderedMap<Value, Value>,
+        xs: &[&Value],
+    ) -> Result<()> {
+        let mut n_prefix = 0;
+        if let Some(prefix_items) = self.list_keyword(schema, "prefixItems")? {
+            self.schema_path
+                .push(PathElement::Key("prefixItems".into()));
+            for (i, (sub, x)) in prefix_items.iter().zip(xs.iter()).enumerate() {
+                self.schema_path.push(PathElement::Index(i));
+                self.value_path.push(PathElement::Index(i));
+                self.validate(sub, x)?;
+                self.value_path.pop().expect("Push and pop are balanced.");
+                self.schema_path.pop().expect("Push and pop are balanced.");
+            }
+            self.schema_path.pop().expect("Push and pop are balanced.");
+            n_prefix = prefix_items.len();
+        }
+
+        if let Some(items) = keyword(schema, "items") {
+            for (i, x) in xs.iter().enumerate().skip(n_prefix) {
+                self.value_path.push(PathElement::Index(i));
+                self.validate_sub("items", items, x)?;
+                self.value_path.pop().expect("Push and pop are balanced.");
+            }
+        }
+
+        if let Some(min) = self.count_keyword(schema, "minItems")? {
+            if xs.len() < min {
+                self.schema_path.push(PathElement::Key("minItems".into()));
+                self.violation(concat! {
+                    "Expected at least " min.to_string() " elements, but got "
+                    xs.len().to_string() "."
+                });
+                self.schema_path.pop().expect("Push and pop are balanced.");
+            }
+        }
+        if let Some(max) = self.count_keyword(schema, "maxItems")? {
+            if xs.len() > max {
+                self.schema_path.push(PathElement::Key("maxItems".into()));
+                self.violation(concat! {
+                    "Expected at most " max.to_string() " elements, but got "
+                    xs.len().to_string() "."
+                });
+                self.schema_path.pop().expect("Push and pop are balanced.");
+            }
+        }
+
+        if let Some(Value::Bool(true)) = keyword(schema, "uniqueItems") {
+            self.schema_path
+                .push(PathElement::Key("uniqueItems".into()));
+            'outer: for (j, y) in xs.iter().enumerate() {
+                for (i, x) in xs[..j].iter().enumerate() {
+                    if json_eq(x, y) {
+                        self.value_path.push(PathElement::Index(j));
+                        self.violation(concat! {
+                            "Expected unique elements, but this is a duplicate of the element at index "
+                            i.to_string() "."
+                        });
+                        self.value_path.pop().expect("Push and pop are balanced.");
+                        break 'outer;
+                    }
+                }
+            }
+            self.schema_path.pop().expect("Push and pop are balanced.");
+        }
+
+        if let Some(contains) = keyword(schema, "contains") {
+            let min = self.count_keyword(schema, "minContains")?.unwrap_or(1);
+            let max = self.count_keyword(schema, "maxContains")?;
+            self.schema_path.push(PathElement::Key("contains".into()));
+            let mut n = 0;
+            for x in xs.iter() {
+                if self.is_valid(contains, x)? {
+                    n += 1;
+                }
+            }
+            if n < min {
+                self.violation(concat! {
+                    "Expected at least " min.to_string() " elements that match 'contains', but found "
+                    n.to_string() "."
+                });
+            }
+            if let Some(max) = max {
+                if n > max {
+                    self.violation(concat! {
+                        "Expected at most " max.to_string() " elements that match 'contains', but found "
+                        n.to_string() "."
+                    });
+                }
+            }
+            self.schema_path.pop().expect("Push and pop are balanced.");
+        }
+
+        Ok(())
+    }
+
+    fn validate_object(
+        &mut self,
+        schema: &'a OrderedMap<Value, Value>,
+        fields: &OrderedMap<Value, Value>,
+        value: &Value,
+    ) -> Result<()> {
+        let properties = self.dict_keyword(schema, "properties")?;
+        if let Some(properties) = properties {
+            self.schema_path.push(PathElement::Key("properties".into()));
+            for (k, sub) in properties.iter() {
+                if let Some(v) = fields.get(k) {
+                    self.schema_path.push(PathElement::Key(k.clone()));
+                    self.value_path.push(PathElement::Key(k.clone()));
+                    self.validate(sub, v)?;
+                    self.value_path.pop().expect("Push and pop are balanced.");
+                    self.schema_path.pop().expect("Push and pop are balanced.");
+                }
+            }
+            self.schema_path.pop().expect("Push and pop are balanced.");
+        }
+
+        let mut pattern_properties = Vec::new();
+        if let Some(patterns) = self.dict_keyword(schema, "patternProperties")? {
+            self.schema_path
+                .push(PathElement::Key("patternProperties".into()));
+            for (pattern, sub) in patterns.iter() {
+                self.schema_path.push(PathElement::Key(pattern.clone()));
+                let regex = self.pattern(pattern)?;
+                for (k, v) in fields.iter() {
+                    match k {
+                        Value::String(k_str) if regex.is_match(k_str) => {
+                            self.value_path.push(PathElement::Key(k.clone()));
+                            self.validate(sub, v)?;
+                            self.value_path.pop().expect("Push and pop are balanced.");
+                        }
+                        _ => {}
+                    }
+                }
+                self.schema_path.pop().expect("Push and pop are balanced.");
+                pattern_properties.push(regex);
+            }
+            self.schema_path.pop().expect("Push and pop are balanced.");
+        }
+
+        if let Some(additional) = keyword(schema, "additionalProperties") {
+            self.schema_path
+                .push(PathElement::Key("additionalProperties".into()));
+            for (k, v) in fields.iter() {
+                if properties.map(|p| p.contains_key(k)).unwrap_or(false) {
+                    continue;
+                }
+                let matches_pattern = match k {
+                    Value::String(k) => pattern_properties.iter().any(|r| r.is_match(k)),
+                    _ => false,
+                };
+                if matches_pattern {
+                    continue;
+                }
+                self.value_path.push(PathElement::Key(k.clone()));
+                if let Value::Bool(false) = additional {
+                    self.violation("Unexpected key, the schema does not allow other keys than the listed properties.");
+                } else {
+                    self.validate(additional, v)?;
+                }
+                self.value_path.pop().expect("Push and pop are balanced.");
+            }
+            self.schema_path.pop().expect("Push and pop are balanced.");
+        }
+
+        if let Some(required) = self.list_keyword(schema, "required")? {
+            self.schema_path.push(PathElement::Key("required".into()));
+            for key in required.iter() {
+                match key {
+                    Value::String(k) if !fields.contains_key(key) => {
+                        self.violation(concat! {
+                            "Missing required key '" Doc::highlight(k).into_owned() "'."
+                        });
+                    }
+                    Value::String(..) => {}
+                    _ => return self.schema_error("'required' must be a list of strings."),
+                }
+            }
+            self.schema_path.pop().expect("Push and pop are balanced.");
+        }
+
+        if let Some(min) = self.count_keyword(schema, "minProperties")? {
+            if fields.len() < min {
+                self.schema_path
+                    .push(PathElement::Key("minProperties".into()));
+                self.violation(concat! {
+                    "Expected at least " min.to_string() " keys, but got "
+                    fields.len().to_string() "."
+                });
+                self.schema_path.pop().expect("Push and pop are balanced.");
+            }
+        }
+        if let Some(max) = self.count_keyword(schema, "maxProperties")? {
+            if fields.len() > max {
+                self.schema_path
+                    .push(PathElement::Key("maxProperties".into()));
+                self.violation(concat! {
+                    "Expected at most " max.to_string() " keys, but got "
+                    fields.len().to_string() "."
+                });
+                self.schema_path.pop().expect("Push and pop are balanced.");
+            }
+        }
+
+        if let Some(names) = keyword(schema, "propertyNames") {
+            for k in fields.keys() {
+                self.value_path.push(PathElement::Key(k.clone()));
+                self.validate_sub("propertyNames", names, k)?;
+                self.value_path.pop().expect("Push and pop are balanced.");
+            }
+        }
+
+        if let Some(dependent) = self.dict_keyword(schema, "dependentRequired")? {
+            self.schema_path
+                .push(PathElement::Key("dependentRequired".into()));
+            for (trigger, required) in dependent.iter() {
+                if !fields.contains_key(trigger) {
+                    continue;
+                }
+                self.schema_path.push(PathElement::Key(trigger.clone()));
+                let required = match required {
+                    Value::List(xs) => xs,
+                    _ => return self.schema_error("Dependencies must be lists of strings."),
+                };
+                for key in required.iter() {
+                    match (key, trigger) {
+                        (Value::String(k), Value::String(t)) if !fields.contains_key(key) => {
+                            self.violation(concat! {
+                                "Missing key '" Doc::highlight(k).into_owned()
+                                "', which is required when '" Doc::highlight(t).into_owned()
+                                "' is present."
+                            });
+                        }
+                        (Value::String(..), _) => {}
+                        _ => return self.schema_error("Dependencies must be lists of strings."),
+                    }
+                }
+                self.schema_path.pop().expect("Push and pop are balanced.");
+            }
+            self.schema_path.pop().expect("Push and pop are balanced.");
+        }
+
+        if let Some(dependent) = self.dict_keyword(schema, "dependentSchemas")? {
+            self.schema_path
+                .push(PathElement::Key("dependentSchemas".into()));
+            for (trigger, sub) in dependent.iter() {
+                if fields.contains_key(trigger) {
+                    self.schema_path.push(PathElement::Key(trigger.clone()));
+                    self.validate(sub, value)?;
+                    self.schema_path.pop().expect("Push and pop are balanced.");
+                }
+            }
+            self.schema_path.pop().expect("Push and pop are balanced.");
+        }
+
+        Ok(())
+    }
+
+    fn validate_combinators(
+        &mut self,
+        schema: &'a OrderedMap<Value, Value>,
+        value: &Value,
+    ) -> Result<()> {
+        if let Some(all_of) = self.list_keyword(schema, "allOf")? {
+            self.schema_path.push(PathElement::Key("allOf".into()));
+            for (i, sub) in all_of.iter().enumerate() {
+                self.schema_path.push(PathElement::Index(i));
+                self.validate(sub, value)?;
+                self.schema_path.pop().expect("Push and pop are balanced.");
+            }
+            self.schema_path.pop().expect("Push and pop are balanced.");
+        }
+
+        if let Some(any_of) = self.list_keyword(schema, "anyOf")? {
+            self.schema_path.push(PathElement::Key("anyOf".into()));
+            let mut n_valid = 0;
+            for sub in any_of.iter() {
+                if self.is_valid(sub, value)? {
+                    n_valid += 1;
+                    break;
+                }
+            }
+            if n_valid == 0 {
+                self.violation("Expected a value that matches at least one of the schemas.");
+            }
+            self.schema_path.pop().expect("Push and pop are balanced.");
+        }
+
+        if let Some(one_of) = self.list_keyword(schema, "oneOf")? {
+            self.schema_path.push(PathElement::Key("oneOf".into()));
+            let mut n_valid = 0;
+            for sub in one_of.iter() {
+                if self.is_valid(sub, value)? {
+                    n_valid += 1;
+                }
+            }
+            match n_valid {
+                0 => self.violation(
+                    "Expected a value that matches one of the schemas, but it matches none.",
+                ),
+                1 => {}
+                n => self.violation(concat! {
+                    "Expected a value that matches exactly one of the schemas, but it matches "
+                    n.to_string() "."
+                }),
+            }
+            self.schema_path.pop().expect("Push and pop are balanced.");
+        }
+
+        if let Some(not) = keyword(schema, "not") {
+            self.schema_path.push(PathElement::Key("not".into()));
+            if self.is_valid(not, value)? {
+                self.violation("Expected a value that does not match the schema.");
+            }
+            self.schema_path.pop().expect("Push and pop are balanced.");
+        }
+
+        if let Some(condition) = keyword(schema, "if") {
+            self.schema_path.push(PathElement::Key("if".into()));
+            let is_valid = self.is_valid(condition, value)?;
+            self.schema_path.pop().expect("Push and pop are balanced.");
+            let branch = if is_valid { "then" } else { "else" };
+            if let Some(sub) = keyword(schema, branch) {
+                self.validate_sub(branch, sub, value)?;
+            }
+        }
+
+        Ok(())
+    }
+}
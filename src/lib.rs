@@ -84,29 +84,47 @@ mod cli_utils;
 
 pub mod abstraction;
 pub mod ast;
+pub mod bigint;
 pub mod cli;
 pub mod cmd_build;
 pub mod cmd_eval;
 pub mod cst;
+pub mod csv;
 pub mod decimal;
+pub mod digest;
+pub mod encoding;
 pub mod env;
 pub mod error;
 pub mod eval;
+pub mod fmt_cbor;
 pub mod fmt_cst;
+pub mod fmt_csv;
+pub mod fmt_dotenv;
+pub mod fmt_hcl;
+pub mod fmt_ini;
 pub mod fmt_json;
 pub mod fmt_json_lines;
+pub mod fmt_msgpack;
+pub mod fmt_nix;
+pub mod fmt_properties;
 pub mod fmt_raw;
 pub mod fmt_rcl;
+pub mod fmt_sh;
 pub mod fmt_toml;
 pub mod fmt_type;
+pub mod fmt_xml;
 pub mod fmt_yaml_stream;
 pub mod highlight;
+pub mod json_schema;
 pub mod lexer;
 pub mod loader;
 pub mod markup;
+pub mod net;
+pub mod ordered;
 pub mod parser;
 pub mod patch;
 pub mod pprint;
+pub mod regex;
 pub mod runtime;
 pub mod source;
 pub mod stdlib;
@@ -27,6 +27,7 @@ use crate::runtime::{Env, Value};
 use crate::source::{Doc, DocId, Span};
 use crate::tracer::Tracer;
 use crate::typecheck::{self, TypeChecker};
+use crate::types::SourcedType;
 
 /// An owned document.
 ///
@@ -570,6 +571,15 @@ impl Loader {
         Ok(ast)
     }
 
+    /// Parse the given document as a type expression, and evaluate it.
+    pub fn get_type(&mut self, id: DocId) -> Result<SourcedType> {
+        let doc = self.get_doc(id);
+        let tokens = self.get_tokens(id)?;
+        let cst = parser::parse_type(id, doc.data, &tokens)?;
+        let ast = abstraction::abstract_type(doc.data, &cst)?;
+        typecheck::eval_type_expr(&ast)
+    }
+
     /// Parse and typecheck the document, return the checked Abstract Syntax Tree.
     pub fn get_typechecked_ast(
         &mut self,
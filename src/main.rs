@@ -5,12 +5,13 @@
 // you may not use this file except in compliance with the License.
 // A copy of the License has been included in the root of the repository.
 
-use std::io::{Stdout, Write};
+use std::io::{IsTerminal, Stdout, Write};
 use std::path::Path;
 
 use rcl::cli::{
     self, Cmd, EvalOptions, FormatTarget, GlobalOptions, OutputTarget, StyleOptions, Target,
 };
+use rcl::cmd_eval::Formatted;
 use rcl::error::{Error, Result};
 use rcl::loader::{Loader, SandboxMode};
 use rcl::markup::{MarkupMode, MarkupString};
@@ -70,18 +71,29 @@ impl App {
     /// Write a string to a file.
     fn print_to_file(&self, mode: MarkupMode, data: MarkupString, out_path: &str) -> Result<()> {
         let out_path = self.loader.resolve_cli_output_path(out_path);
+        let result = self.print_to_file_impl(mode, data, out_path.as_ref());
+        self.map_file_error(result, out_path.as_ref())
+    }
+
+    /// Write raw bytes to a file.
+    fn print_bytes_to_file(&self, data: &[u8], out_path: &str) -> Result<()> {
+        let out_path = self.loader.resolve_cli_output_path(out_path);
+        let result = std::fs::write(&out_path, data);
+        self.map_file_error(result, out_path.as_ref())
+    }
 
-        self.print_to_file_impl(mode, data, out_path.as_ref())
-            .map_err(|err| {
-                // The concat! macro is not exported, we'll make do with a vec here.
-                let parts = vec![
-                    "Failed to write to file '".into(),
-                    Doc::path(out_path),
-                    "': ".into(),
-                    err.to_string().into(),
-                ];
-                Error::new(Doc::Concat(parts)).into()
-            })
+    /// Turn an IO error from writing to `out_path` into an RCL error.
+    fn map_file_error(&self, result: std::io::Result<()>, out_path: &Path) -> Result<()> {
+        result.map_err(|err| {
+            // The concat! macro is not exported, we'll make do with a vec here.
+            let parts = vec![
+                "Failed to write to file '".into(),
+                Doc::path(out_path),
+                "': ".into(),
+                err.to_string().into(),
+            ];
+            Error::new(Doc::Concat(parts)).into()
+        })
     }
 
     fn get_markup_for_target(&self, target: &OutputTarget, stdout: &Stdout) -> MarkupMode {
@@ -100,9 +112,36 @@ impl App {
         &self,
         output: OutputTarget,
         style_opts: &StyleOptions,
-        doc: Doc,
+        formatted: Formatted,
     ) -> Result<()> {
         let stdout = std::io::stdout();
+
+        let doc = match formatted {
+            Formatted::Doc(doc) => doc,
+            Formatted::Bytes(data) => {
+                return match output {
+                    OutputTarget::Stdout if stdout.is_terminal() => {
+                        Error::new("Refusing to write binary output to a terminal.")
+                            .with_help(
+                                "Redirect stdout to a file or pipe, \
+                                or use '--output' to write to a file.",
+                            )
+                            .err()
+                    }
+                    OutputTarget::Stdout => {
+                        let mut out = stdout.lock();
+                        if out.write_all(&data).and_then(|_| out.flush()).is_err() {
+                            // Like for text, if we fail to write to stdout,
+                            // there is no point in printing an error.
+                            std::process::exit(1);
+                        }
+                        Ok(())
+                    }
+                    OutputTarget::File(fname) => self.print_bytes_to_file(&data, &fname),
+                };
+            }
+        };
+
         let markup = self.get_markup_for_target(&output, &stdout);
         let cfg = pprint::Config {
             width: Some(style_opts.width),
@@ -140,17 +179,66 @@ impl App {
         value_span: Span,
         value: &Value,
     ) -> Result<()> {
+        let sorted;
+        let value = match eval_opts.sort_keys {
+            true => {
+                sorted = value.sort_keys();
+                &sorted
+            }
+            false => value,
+        };
         let out_doc = rcl::cmd_eval::format_value(eval_opts.format, value_span, value)?;
 
         // Prepend the banner if the user specified one.
-        let out_doc = match eval_opts.banner.as_ref() {
-            Some(banner) => Doc::lines(banner) + Doc::HardBreak + out_doc,
-            None => out_doc,
+        let out_doc = match (eval_opts.banner.as_ref(), out_doc) {
+            (Some(banner), Formatted::Doc(doc)) => {
+                Formatted::Doc(Doc::lines(banner) + Doc::HardBreak + doc)
+            }
+            (Some(..), Formatted::Bytes(..)) => {
+                return Error::new("Binary output formats do not support a banner.").err();
+            }
+            (None, out_doc) => out_doc,
         };
 
         self.print_doc_target(output, style_opts, out_doc)
     }
 
+    /// Check the value against the JSON Schema in the given file.
+    ///
+    /// All violations except for the last one are printed to stderr, the last
+    /// one is returned as the error.
+    fn validate_schema(
+        &mut self,
+        schema_path: &str,
+        value_span: Span,
+        value: &Value,
+    ) -> Result<()> {
+        // JSON is valid RCL, so we can load the schema like any other document,
+        // which also means it is subject to the sandbox, and ends up in the depfile.
+        let doc = self
+            .loader
+            .load_cli_target(&Target::File(schema_path.to_string()))?;
+        let mut tracer = self.get_tracer();
+        let mut type_env = typecheck::prelude();
+        let mut value_env = runtime::prelude();
+        let schema = self
+            .loader
+            .evaluate(&mut type_env, &mut value_env, doc, &mut tracer)?;
+        let schema_span = self.loader.get_span(doc);
+
+        let mut violations = rcl::json_schema::validate(schema_span, &schema, value_span, value)?;
+        match violations.pop() {
+            None => Ok(()),
+            Some(last) => {
+                for err in violations {
+                    let inputs = self.loader.as_inputs();
+                    self.print_doc_stderr(err.report(&inputs) + Doc::HardBreak);
+                }
+                Err(Box::new(last))
+            }
+        }
+    }
+
     fn print_fatal_error(&self, err: Error) -> ! {
         let inputs = self.loader.as_inputs();
         let err_doc = err.report(&inputs);
@@ -192,7 +280,7 @@ impl App {
                 let mut cst = self.loader.get_cst(doc)?;
                 let inputs = self.loader.as_inputs();
                 let res = apply_one(&inputs, doc, &mut cst)?;
-                self.print_doc_target(output, &style_opts, res)?;
+                self.print_doc_target(output, &style_opts, Formatted::Doc(res))?;
                 result.stdout = true;
                 return Ok(result);
             }
@@ -274,6 +362,9 @@ impl App {
                     return Error::new("Generating depfiles is not supported for 'rcl build'.")
                         .err();
                 }
+                if eval_opts.schema.is_some() {
+                    return Error::new("Schema validation is not supported for 'rcl build'.").err();
+                }
 
                 self.initialize_filesystem(eval_opts.sandbox)?;
 
@@ -308,12 +399,16 @@ impl App {
                 let val = self
                     .loader
                     .evaluate(&mut type_env, &mut value_env, doc, &mut tracer)?;
+                let body_span = self.loader.get_span(doc);
+
+                if let Some(schema_path) = eval_opts.schema.as_ref() {
+                    self.validate_schema(schema_path, body_span, &val)?;
+                }
 
                 if let Some(depfile_path) = eval_opts.output_depfile.as_ref() {
                     self.loader.write_depfile(&output, depfile_path)?;
                 }
 
-                let body_span = self.loader.get_span(doc);
                 self.print_value(&eval_opts, &style_opts, output, body_span, &val)
             }
 
@@ -345,15 +440,54 @@ impl App {
                 let val_result =
                     self.loader
                         .evaluate(&mut type_env, &mut value_env, query, &mut tracer)?;
+                let body_span = self.loader.get_span(query);
+
+                if let Some(schema_path) = eval_opts.schema.as_ref() {
+                    self.validate_schema(schema_path, body_span, &val_result)?;
+                }
 
                 if let Some(depfile_path) = eval_opts.output_depfile.as_ref() {
                     self.loader.write_depfile(&output, depfile_path)?;
                 }
 
-                let body_span = self.loader.get_span(query);
                 self.print_value(&eval_opts, &style_opts, output, body_span, &val_result)
             }
 
+            Cmd::Schema {
+                eval_opts,
+                style_opts,
+                fname,
+                type_expr,
+                output,
+            } => {
+                self.initialize_filesystem(eval_opts.sandbox)?;
+
+                let input = self.loader.load_cli_target(&fname)?;
+                let type_doc = self.loader.load_string("type", type_expr);
+                let type_ = self.loader.get_type(type_doc)?;
+                let type_span = self.loader.get_span(type_doc);
+
+                // Convert the type first, so we report unsupported types
+                // before we bother evaluating the input.
+                let schema = rcl::json_schema::type_to_schema_document(type_span, &type_)?;
+
+                let mut tracer = self.get_tracer();
+                let mut type_env = typecheck::prelude();
+                let mut value_env = runtime::prelude();
+                let val =
+                    self.loader
+                        .evaluate(&mut type_env, &mut value_env, input, &mut tracer)?;
+                let body_span = self.loader.get_span(input);
+                val.is_instance_of(body_span, &type_)?;
+
+                if let Some(depfile_path) = eval_opts.output_depfile.as_ref() {
+                    self.loader.write_depfile(&output, depfile_path)?;
+                }
+
+                let out_doc = rcl::fmt_json::format_json(type_span, &schema)?;
+                self.print_doc_target(output, &style_opts, Formatted::Doc(out_doc))
+            }
+
             Cmd::Format {
                 style_opts,
                 target,
@@ -0,0 +1,468 @@
+// RCL -- A reasonable configuration language.
+// Copyright 2025 Ruud van Asseldonk
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// A copy of the License has been included in the root of the repository.
+
+//! Parsing and arithmetic for IP addresses and CIDR prefixes.
+//!
+//! We parse addresses ourselves rather than with `std::net`, so we can report
+//! which part of a malformed address is wrong. For formatting we do rely on
+//! `std::net`, which formats IPv6 addresses in the canonical form of RFC 5952.
+
+use std::fmt;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::ops::Range;
+
+/// An error in an address or prefix, with the byte range it applies to.
+#[derive(Debug, Eq, PartialEq)]
+pub struct AddrError {
+    pub message: &'static str,
+    pub range: Range<usize>,
+}
+
+pub type Result<T> = std::result::Result<T, AddrError>;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Family {
+    V4,
+    V6,
+}
+
+impl Family {
+    /// The number of bits in an address of this family.
+    pub fn width(self) -> u32 {
+        match self {
+            Family::V4 => 32,
+            Family::V6 => 128,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Family::V4 => "IPv4",
+            Family::V6 => "IPv6",
+        }
+    }
+}
+
+/// An IPv4 or IPv6 address.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Addr {
+    pub family: Family,
+
+    /// The address, IPv4 addresses use only the low 32 bits.
+    pub bits: u128,
+}
+
+/// An address with a prefix length, such as `10.0.0.0/8`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Cidr {
+    /// The network address, with all host bits zero.
+    pub network: Addr,
+    pub prefix_len: u32,
+}
+
+/// Return `2^n`, or `None` if that does not fit in a `u128`.
+fn pow2(n: u32) -> Option<u128> {
+    1_u128.checked_shl(n)
+}
+
+fn error<T>(message: &'static str, range: Range<usize>) -> Result<T> {
+    Err(AddrError { message, range })
+}
+
+/// Parse an IPv4 address in dotted decimal notation.
+///
+/// The `offset` is added to error ranges, for when the address is embedded in
+/// an IPv6 address.
+fn parse_v4(input: &str, offset: usize) -> Result<u32> {
+    let mut result: u32 = 0;
+    let mut n_parts = 0;
+    let mut start = 0;
+    for part in input.split('.') {
+        let range = offset + start..offset + start + part.len();
+        start += part.len() + 1;
+        n_parts += 1;
+        if n_parts > 4 {
+            return error("An IPv4 address has only four parts.", range);
+        }
+        if part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit()) {
+            return error("Expected a number between 0 and 255.", range);
+        }
+        if part.len() > 1 && part.starts_with('0') {
+            // Some tools interpret a leading zero as octal, so it's ambiguous.
+            return error("Parts of an IPv4 address cannot have leading zeros.", range);
+        }
+        match part.parse::<u8>() {
+            Ok(n) => result = (result << 8) | n as u32,
+            Err(..) => return error("Expected a number between 0 and 255.", range),
+        }
+    }
+    if n_parts < 4 {
+        return error(
+            "An IPv4 address must have four parts.",
+            offset..offset + input.len(),
+        );
+    }
+    Ok(result)
+}
+
+/// Parse the groups on one side of the `::` in an IPv6 address.
+///
+/// An IPv4 address is allowed as the last group when `allow_v4` is set, and
+/// then counts as two groups.
+fn parse_v6_groups(input: &str, offset: usize, allow_v4: bool) -> Result<Vec<u16>> {
+    let mut groups = Vec::new();
+    if input.is_empty() {
+        return Ok(groups);
+    }
+    let n_parts = input.split(':').count();
+    let mut start = 0;
+    for (i, part) in input.split(':').enumerate() {
+        let range = offset + start..offset + start + part.len();
+        start += part.len() + 1;
+        if allow_v4 && i + 1 == n_parts && part.contains('.') {
+            let v4 = parse_v4(part, range.start)?;
+            groups.push((v4 >> 16) as u16);
+            groups.push(v4 as u16);
+            continue;
+        }
+        if part.is_empty() {
+            // Point at the colon, there is nothing else to point at.
+            let at = range.start.saturating_sub(1);
+            return error("Expected a group of hex digits.", at..at + 1);
+        }
+        if part.len() > 4 || !part.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return error("Expected a group of one to four hex digits.", range);
+        }
+        groups.push(u16::from_str_radix(part, 16).expect("Checked hex digits above."));
+    }
+    Ok(groups)
+}
+
+/// Parse an IPv6 address, in any of the notations of RFC 4291.
+fn parse_v6(input: &str) -> Result<u128> {
+    if let Some(i) = input.find('%') {
+        return error(
+            "Zone indices are not supported in IPv6 addresses.",
+            i..input.len(),
+        );
+    }
+
+    let (head, tail) = match input.find("::") {
+        None => (parse_v6_groups(input, 0, true)?, None),
+        Some(i) => {
+            let tail_str = &input[i + 2..];
+            if let Some(j) = tail_str.find("::") {
+                let at = i + 2 + j;
+                return error("An IPv6 address can contain '::' only once.", at..at + 2);
+            }
+            let head = parse_v6_groups(&input[..i], 0, false)?;
+            let tail = parse_v6_groups(tail_str, i + 2, true)?;
+            (head, Some(tail))
+        }
+    };
+
+    let groups = match tail {
+        None if head.len() == 8 => head,
+        None => {
+            return error(
+                "An IPv6 address must have eight groups, or use '::'.",
+                0..input.len(),
+            )
+        }
+        Some(tail) if head.len() + tail.len() < 8 => {
+            let mut groups = head;
+            groups.resize(8 - tail.len(), 0);
+            groups.extend(tail);
+            groups
+        }
+        Some(..) => {
+            return error(
+                "An IPv6 address with '::' can have at most seven groups.",
+                0..input.len(),
+            )
+        }
+    };
+
+    Ok(groups
+        .iter()
+        .fold(0_u128, |acc, g| (acc << 16) | *g as u128))
+}
+
+impl Addr {
+    pub fn parse(input: &str) -> Result<Addr> {
+        if input.is_empty() {
+            return error("Expected an IP address, but the string is empty.", 0..0);
+        }
+        if let Some(i) = input.find('/') {
+            return error("Expected an IP address, not a CIDR prefix.", i..input.len());
+        }
+        if input.contains(':') {
+            Ok(Addr {
+                family: Family::V6,
+                bits: parse_v6(input)?,
+            })
+        } else {
+            Ok(Addr {
+                family: Family::V4,
+                bits: parse_v4(input, 0)? as u128,
+            })
+        }
+    }
+}
+
+impl fmt::Display for Addr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.family {
+            Family::V4 => Ipv4Addr::from(self.bits as u32).fmt(f),
+            Family::V6 => Ipv6Addr::from(self.bits).fmt(f),
+        }
+    }
+}
+
+impl Cidr {
+    /// Parse a prefix in CIDR notation.
+    ///
+    /// Host bits in the address are allowed and discarded, like Terraform
+    /// does, so `10.1.2.3/16` means `10.1.0.0/16`.
+    pub fn parse(input: &str) -> Result<Cidr> {
+        let slash = match input.find('/') {
+            Some(i) => i,
+            None => return error(
+                "Expected a CIDR prefix such as '10.0.0.0/8', but the prefix length is missing.",
+                0..input.len(),
+            ),
+        };
+        let addr = Addr::parse(&input[..slash])?;
+        let len_str = &input[slash + 1..];
+        let len_range = slash + 1..input.len();
+        let width = addr.family.width();
+        let prefix_len = match len_str.parse::<u32>() {
+            Ok(n) if n <= width && len_str.bytes().all(|b| b.is_ascii_digit()) => n,
+            _ => {
+                let message = match addr.family {
+                    Family::V4 => "Expected a prefix length between 0 and 32.",
+                    Family::V6 => "Expected a prefix length between 0 and 128.",
+                };
+                return error(message, len_range);
+            }
+        };
+        Ok(Cidr::new(addr, prefix_len))
+    }
+
+    /// Build a prefix, discarding any host bits of the address.
+    fn new(addr: Addr, prefix_len: u32) -> Cidr {
+        let n_host_bits = addr.family.width() - prefix_len;
+        let host_mask = pow2(n_host_bits).map(|n| n - 1).unwrap_or(u128::MAX);
+        Cidr {
+            network: Addr {
+                family: addr.family,
+                bits: addr.bits & !host_mask,
+            },
+            prefix_len,
+        }
+    }
+
+    /// The number of bits that are not part of the prefix.
+    pub fn n_host_bits(&self) -> u32 {
+        self.network.family.width() - self.prefix_len
+    }
+
+    /// Return the `netnum`-th subnet that is `new_bits` bits longer.
+    ///
+    /// Returns `None` when the prefix would exceed the address width, or when
+    /// `netnum` does not fit in `new_bits` bits.
+    pub fn subnet(&self, new_bits: u32, netnum: u128) -> Option<Cidr> {
+        if new_bits > self.n_host_bits() {
+            return None;
+        }
+        if pow2(new_bits).map_or(false, |n| netnum >= n) {
+            return None;
+        }
+        let prefix_len = self.prefix_len + new_bits;
+        let n_host_bits = self.network.family.width() - prefix_len;
+        let addr = Addr {
+            family: self.network.family,
+            bits: self.network.bits | netnum.checked_shl(n_host_bits).unwrap_or(0),
+        };
+        Some(Cidr::new(addr, prefix_len))
+    }
+
+    /// Return the `hostnum`-th address in the prefix.
+    ///
+    /// Negative numbers count from the end, so -1 is the last address. Returns
+    /// `None` when the number is outside of the prefix.
+    pub fn host(&self, hostnum: i128) -> Option<Addr> {
+        let size = pow2(self.n_host_bits());
+        let offset = match hostnum {
+            n if n >= 0 => {
+                let n = n as u128;
+                if size.map_or(false, |size| n >= size) {
+                    return None;
+                }
+                n
+            }
+            n => {
+                let back = n.unsigned_abs();
+                match size {
+                    Some(size) if back > size => return None,
+                    Some(size) => size - back,
+                    None => u128::MAX - (back - 1),
+                }
+            }
+        };
+        Some(Addr {
+            family: self.network.family,
+            bits: self.network.bits | offset,
+        })
+    }
+
+    /// Return whether the address is inside the prefix.
+    pub fn contains(&self, addr: &Addr) -> bool {
+        addr.family == self.network.family && Cidr::new(*addr, self.prefix_len) == *self
+    }
+}
+
+impl fmt::Display for Cidr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.network, self.prefix_len)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Addr, AddrError, Cidr};
+
+    fn canonical(input: &str) -> String {
+        Addr::parse(input).unwrap().to_string()
+    }
+
+    fn parse_err(input: &str) -> (&'static str, &str) {
+        let err: AddrError = Addr::parse(input).unwrap_err();
+        (err.message, &input[err.range])
+    }
+
+    #[test]
+    fn parse_formats_canonically() {
+        assert_eq!(canonical("10.0.0.1"), "10.0.0.1");
+        assert_eq!(canonical("0.0.0.0"), "0.0.0.0");
+        assert_eq!(canonical("2001:DB8:0:0:0:0:0:1"), "2001:db8::1");
+        assert_eq!(canonical("2001:db8::0:1"), "2001:db8::1");
+        assert_eq!(canonical("::"), "::");
+        assert_eq!(canonical("::1"), "::1");
+        assert_eq!(canonical("fe80::"), "fe80::");
+        assert_eq!(canonical("::ffff:192.0.2.1"), "::ffff:192.0.2.1");
+        assert_eq!(canonical("1:0:0:2:0:0:0:3"), "1:0:0:2::3");
+    }
+
+    #[test]
+    fn parse_reports_offending_part() {
+        assert_eq!(
+            parse_err("10.0.256.1"),
+            ("Expected a number between 0 and 255.", "256")
+        );
+        assert_eq!(
+            parse_err("10.01.0.1"),
+            ("Parts of an IPv4 address cannot have leading zeros.", "01")
+        );
+        assert_eq!(
+            parse_err("10.0.0"),
+            ("An IPv4 address must have four parts.", "10.0.0")
+        );
+        assert_eq!(
+            parse_err("10.0.0.1.2"),
+            ("An IPv4 address has only four parts.", "2")
+        );
+        assert_eq!(
+            parse_err("2001:db8::1::2"),
+            ("An IPv6 address can contain '::' only once.", "::")
+        );
+        assert_eq!(
+            parse_err("2001:db8g::1"),
+            ("Expected a group of one to four hex digits.", "db8g")
+        );
+        assert_eq!(
+            parse_err("1:2:3:4:5:6:7"),
+            (
+                "An IPv6 address must have eight groups, or use '::'.",
+                "1:2:3:4:5:6:7"
+            )
+        );
+        assert_eq!(
+            parse_err("::ffff:1.2.3.999"),
+            ("Expected a number between 0 and 255.", "999")
+        );
+        assert_eq!(
+            parse_err("10.0.0.0/8"),
+            ("Expected an IP address, not a CIDR prefix.", "/8")
+        );
+    }
+
+    #[test]
+    fn cidr_parse_discards_host_bits() {
+        assert_eq!(
+            Cidr::parse("10.1.2.3/16").unwrap().to_string(),
+            "10.1.0.0/16"
+        );
+        assert_eq!(Cidr::parse("0.0.0.0/0").unwrap().to_string(), "0.0.0.0/0");
+        assert_eq!(
+            Cidr::parse("2001:db8::1/32").unwrap().to_string(),
+            "2001:db8::/32"
+        );
+        assert!(Cidr::parse("10.0.0.0/33").is_err());
+        assert!(Cidr::parse("10.0.0.0").is_err());
+        assert!(Cidr::parse("10.0.0.0/+8").is_err());
+    }
+
+    #[test]
+    fn cidr_subnet_matches_terraform() {
+        let p = Cidr::parse("10.0.0.0/16").unwrap();
+        assert_eq!(p.subnet(8, 2).unwrap().to_string(), "10.0.2.0/24");
+        assert_eq!(p.subnet(0, 0).unwrap().to_string(), "10.0.0.0/16");
+        assert_eq!(p.subnet(16, 65535).unwrap().to_string(), "10.0.255.255/32");
+        assert_eq!(p.subnet(8, 256), None);
+        assert_eq!(p.subnet(17, 0), None);
+
+        let p = Cidr::parse("fd00:fd12:3456:7890::/56").unwrap();
+        assert_eq!(
+            p.subnet(8, 162).unwrap().to_string(),
+            "fd00:fd12:3456:78a2::/64"
+        );
+
+        let all = Cidr::parse("::/0").unwrap();
+        assert_eq!(all.subnet(128, 1).unwrap().to_string(), "::1/128");
+    }
+
+    #[test]
+    fn cidr_host_counts_from_both_ends() {
+        let p = Cidr::parse("10.12.112.0/20").unwrap();
+        assert_eq!(p.host(16).unwrap().to_string(), "10.12.112.16");
+        assert_eq!(p.host(268).unwrap().to_string(), "10.12.113.12");
+        assert_eq!(p.host(-1).unwrap().to_string(), "10.12.127.255");
+        assert_eq!(p.host(-4096).unwrap().to_string(), "10.12.112.0");
+        assert_eq!(p.host(4096), None);
+        assert_eq!(p.host(-4097), None);
+
+        let p = Cidr::parse("fd00:fd12:3456:7890:00a2::/72").unwrap();
+        assert_eq!(p.host(34).unwrap().to_string(), "fd00:fd12:3456:7890::22");
+
+        let all = Cidr::parse("::/0").unwrap();
+        assert_eq!(
+            all.host(-1).unwrap().to_string(),
+            "ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff"
+        );
+    }
+
+    #[test]
+    fn cidr_contains_checks_family_and_prefix() {
+        let p = Cidr::parse("10.0.0.0/8").unwrap();
+        assert!(p.contains(&Addr::parse("10.255.0.1").unwrap()));
+        assert!(!p.contains(&Addr::parse("11.0.0.1").unwrap()));
+        assert!(!p.contains(&Addr::parse("::a00:1").unwrap()));
+        let all = Cidr::parse("0.0.0.0/0").unwrap();
+        assert!(all.contains(&Addr::parse("192.0.2.1").unwrap()));
+    }
+}
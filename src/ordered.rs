@@ -0,0 +1,349 @@
+// RCL -- A reasonable configuration language.
+// Copyright 2025 Ruud van Asseldonk
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// A copy of the License has been included in the root of the repository.
+
+//! Maps and sets that remember insertion order.
+//!
+//! Dicts and sets in RCL preserve the order in which keys were inserted, so
+//! that the output follows the order of the source. Equality and comparison
+//! ignore this order though: two dicts are equal when they contain the same
+//! key-value pairs, regardless of order.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+/// A map that iterates in insertion order.
+///
+/// When a key is inserted that is already present, the value is replaced,
+/// but the key keeps its original position.
+#[derive(Clone)]
+pub struct OrderedMap<K, V> {
+    /// The entries in insertion order.
+    entries: Vec<(K, V)>,
+
+    /// For every key, its index into `entries`.
+    index: BTreeMap<K, usize>,
+}
+
+impl<K: Clone + Ord, V> OrderedMap<K, V> {
+    pub fn new() -> OrderedMap<K, V> {
+        OrderedMap {
+            entries: Vec::new(),
+            index: BTreeMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Insert a key-value pair, return the previous value if there was one.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.index.get(&key) {
+            Some(&i) => Some(std::mem::replace(&mut self.entries[i].1, value)),
+            None => {
+                self.index.insert(key.clone(), self.entries.len());
+                self.entries.push((key, value));
+                None
+            }
+        }
+    }
+
+    /// Remove the key, return its value if it was present.
+    ///
+    /// This takes time linear in the size of the map, because all entries
+    /// after the removed one shift.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let i = self.index.remove(key)?;
+        let (_key, value) = self.entries.remove(i);
+        for j in self.index.values_mut() {
+            if *j > i {
+                *j -= 1;
+            }
+        }
+        Some(value)
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.index.get(key).map(|&i| &self.entries[i].1)
+    }
+
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.index.get(key).map(|&i| &mut self.entries[i].1)
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.index.contains_key(key)
+    }
+
+    /// Iterate the entries in insertion order.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&K, &V)> + ExactSizeIterator {
+        self.entries.iter().map(|entry| (&entry.0, &entry.1))
+    }
+
+    /// Iterate the entries in ascending order of the keys.
+    pub fn iter_sorted(&self) -> impl DoubleEndedIterator<Item = (&K, &V)> + ExactSizeIterator {
+        self.index.iter().map(|(k, &i)| (k, &self.entries[i].1))
+    }
+
+    /// Iterate the keys in insertion order.
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item = &K> + ExactSizeIterator {
+        self.entries.iter().map(|(k, _v)| k)
+    }
+
+    /// Iterate the values in insertion order.
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = &V> + ExactSizeIterator {
+        self.entries.iter().map(|(_k, v)| v)
+    }
+}
+
+impl<K: Clone + Ord, V> Default for OrderedMap<K, V> {
+    fn default() -> Self {
+        OrderedMap::new()
+    }
+}
+
+impl<K: Clone + Ord, V> FromIterator<(K, V)> for OrderedMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut result = OrderedMap::new();
+        result.extend(iter);
+        result
+    }
+}
+
+impl<K: Clone + Ord, V> Extend<(K, V)> for OrderedMap<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+impl<K, V> IntoIterator for OrderedMap<K, V> {
+    type Item = (K, V);
+    type IntoIter = std::vec::IntoIter<(K, V)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl<'a, K: Clone + Ord, V> IntoIterator for &'a OrderedMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = std::iter::Map<std::slice::Iter<'a, (K, V)>, fn(&'a (K, V)) -> (&'a K, &'a V)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.iter().map(|entry| (&entry.0, &entry.1))
+    }
+}
+
+impl<K: Clone + Ord, V: PartialEq> PartialEq for OrderedMap<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter_sorted().eq(other.iter_sorted())
+    }
+}
+
+impl<K: Clone + Ord, V: Eq> Eq for OrderedMap<K, V> {}
+
+impl<K: Clone + Ord, V: Ord> PartialOrd for OrderedMap<K, V> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K: Clone + Ord, V: Ord> Ord for OrderedMap<K, V> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter_sorted().cmp(other.iter_sorted())
+    }
+}
+
+impl<K: std::fmt::Debug, V: std::fmt::Debug> std::fmt::Debug for OrderedMap<K, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map()
+            .entries(self.entries.iter().map(|entry| (&entry.0, &entry.1)))
+            .finish()
+    }
+}
+
+/// A set that iterates in insertion order.
+///
+/// Inserting an element that is already present leaves it at its original
+/// position.
+#[derive(Clone)]
+pub struct OrderedSet<T> {
+    inner: OrderedMap<T, ()>,
+}
+
+impl<T: Clone + Ord> OrderedSet<T> {
+    pub fn new() -> OrderedSet<T> {
+        OrderedSet {
+            inner: OrderedMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Insert the element, return whether it was not yet present.
+    pub fn insert(&mut self, element: T) -> bool {
+        self.inner.insert(element, ()).is_none()
+    }
+
+    /// Remove the element, return whether it was present.
+    pub fn remove(&mut self, element: &T) -> bool {
+        self.inner.remove(element).is_some()
+    }
+
+    pub fn contains(&self, element: &T) -> bool {
+        self.inner.contains_key(element)
+    }
+
+    /// Iterate the elements in insertion order.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator {
+        self.inner.keys()
+    }
+
+    /// Iterate the elements in ascending order.
+    pub fn iter_sorted(&self) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator {
+        self.inner.iter_sorted().map(|(k, _)| k)
+    }
+}
+
+impl<T: Clone + Ord> Default for OrderedSet<T> {
+    fn default() -> Self {
+        OrderedSet::new()
+    }
+}
+
+impl<T: Clone + Ord> FromIterator<T> for OrderedSet<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut result = OrderedSet::new();
+        result.extend(iter);
+        result
+    }
+}
+
+impl<T: Clone + Ord> Extend<T> for OrderedSet<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for x in iter {
+            self.insert(x);
+        }
+    }
+}
+
+impl<T> IntoIterator for OrderedSet<T> {
+    type Item = T;
+    type IntoIter = std::iter::Map<std::vec::IntoIter<(T, ())>, fn((T, ())) -> T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.inner.into_iter().map(|(k, ())| k)
+    }
+}
+
+impl<'a, T: Clone + Ord> IntoIterator for &'a OrderedSet<T> {
+    type Item = &'a T;
+    type IntoIter = std::iter::Map<std::slice::Iter<'a, (T, ())>, fn(&'a (T, ())) -> &'a T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.inner.entries.iter().map(|(k, _)| k)
+    }
+}
+
+impl<T: Clone + Ord> PartialEq for OrderedSet<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.inner == other.inner
+    }
+}
+
+impl<T: Clone + Ord> Eq for OrderedSet<T> {}
+
+impl<T: Clone + Ord> PartialOrd for OrderedSet<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Clone + Ord> Ord for OrderedSet<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.inner.cmp(&other.inner)
+    }
+}
+
+impl<T: std::fmt::Debug> std::fmt::Debug for OrderedSet<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set()
+            .entries(self.inner.entries.iter().map(|(k, _)| k))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{OrderedMap, OrderedSet};
+    use std::cmp::Ordering;
+
+    #[test]
+    fn ordered_map_iterates_in_insertion_order() {
+        let mut m = OrderedMap::new();
+        m.insert("b", 1);
+        m.insert("c", 2);
+        m.insert("a", 3);
+        assert_eq!(m.keys().copied().collect::<Vec<_>>(), ["b", "c", "a"]);
+        assert_eq!(
+            m.iter_sorted().map(|(k, _)| *k).collect::<Vec<_>>(),
+            ["a", "b", "c"]
+        );
+    }
+
+    #[test]
+    fn ordered_map_insert_existing_keeps_position() {
+        let mut m = OrderedMap::new();
+        m.insert("b", 1);
+        m.insert("a", 2);
+        assert_eq!(m.insert("b", 3), Some(1));
+        assert_eq!(m.iter().collect::<Vec<_>>(), [(&"b", &3), (&"a", &2)]);
+    }
+
+    #[test]
+    fn ordered_map_remove_updates_index() {
+        let mut m: OrderedMap<_, _> = [("a", 1), ("b", 2), ("c", 3)].into_iter().collect();
+        assert_eq!(m.remove(&"a"), Some(1));
+        assert_eq!(m.remove(&"a"), None);
+        assert_eq!(m.get(&"b"), Some(&2));
+        assert_eq!(m.get(&"c"), Some(&3));
+        m.insert("a", 4);
+        assert_eq!(m.keys().copied().collect::<Vec<_>>(), ["b", "c", "a"]);
+    }
+
+    #[test]
+    fn ordered_map_equality_ignores_order() {
+        let m1: OrderedMap<_, _> = [("a", 1), ("b", 2)].into_iter().collect();
+        let m2: OrderedMap<_, _> = [("b", 2), ("a", 1)].into_iter().collect();
+        let m3: OrderedMap<_, _> = [("b", 2), ("a", 0)].into_iter().collect();
+        assert_eq!(m1, m2);
+        assert_eq!(m1.cmp(&m2), Ordering::Equal);
+        assert_ne!(m1, m3);
+        assert_eq!(m1.cmp(&m3), Ordering::Greater);
+    }
+
+    #[test]
+    fn ordered_set_equality_ignores_order() {
+        let s1: OrderedSet<_> = [3, 1, 2, 1].into_iter().collect();
+        let s2: OrderedSet<_> = [1, 2, 3].into_iter().collect();
+        assert_eq!(s1.iter().copied().collect::<Vec<_>>(), [3, 1, 2]);
+        assert_eq!(s1, s2);
+        assert_eq!(s1.cmp(&s2), Ordering::Equal);
+    }
+}
@@ -29,6 +29,15 @@ pub fn parse(doc: DocId, input: &str, tokens: &[Lexeme]) -> Result<(Span, Expr)>
     Ok((span, result))
 }
 
+/// Parse an input document that contains a type expression.
+pub fn parse_type(doc: DocId, input: &str, tokens: &[Lexeme]) -> Result<Type> {
+    let mut parser = Parser::new(doc, input, tokens);
+    parser.skip_blanks();
+    let result = parser.parse_type_expr()?;
+    parser.parse_eof()?;
+    Ok(result)
+}
+
 fn to_unop(token: Token) -> Option<UnOp> {
     match token {
         Token::KwNot => Some(UnOp::Not),
@@ -0,0 +1,928 @@
+// RCL -- A reasonable configuration language.
+// Copyright 2025 Ruud van Asseldonk
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// A copy of the License has been included in the root of the repository.
+
+//! A regular expression engine that matches in linear time.
+//!
+//! Patterns are compiled into a program for a Pike VM, which simulates all
+//! possible paths through the pattern in lockstep. It never backtracks, so
+//! matching takes time proportional to the length of the input times the size
+//! of the program. The price for this is that the engine does not support
+//! backreferences and lookaround.
+//!
+//! The supported syntax is a subset of the syntax of Rust's `regex` crate and
+//! of ECMAScript, so patterns that RCL accepts mean the same in most engines,
+//! including JSON Schema validators:
+//!
+//! * Literal characters, and `.` for any character except newline.
+//! * Character classes such as `[a-z_]` and `[^0-9]`, and the escapes `\d`,
+//!   `\w`, `\s` and their negations `\D`, `\W`, `\S`. These are ASCII-only.
+//! * Anchors `^` and `$` for the start and end of the input, and `\b` and `\B`
+//!   for word boundaries.
+//! * Groups `(...)`, non-capturing groups `(?:...)`, and alternation `|`.
+//! * Repetition `*`, `+`, `?`, `{n}`, `{n,}`, `{n,m}`, and their lazy
+//!   versions with a `?` suffix.
+
+use std::ops::Range;
+
+/// The maximum count in a `{n,m}` repetition.
+const MAX_REPETITION: u32 = 1000;
+
+/// The maximum number of instructions in a compiled program.
+///
+/// Nested repetitions can make the program grow exponentially in the length of
+/// the pattern, and the time to match is proportional to the program size, so
+/// we limit it.
+const MAX_PROGRAM_LEN: usize = 20_000;
+
+/// An error in a pattern, with the byte range in the pattern that caused it.
+#[derive(Debug, Eq, PartialEq)]
+pub struct PatternError {
+    pub message: &'static str,
+    pub range: Range<usize>,
+}
+
+type PResult<T> = std::result::Result<T, PatternError>;
+
+/// A set of code points, as sorted inclusive ranges.
+#[derive(Clone, Debug)]
+struct Class {
+    ranges: Vec<(u32, u32)>,
+}
+
+impl Class {
+    fn new(mut ranges: Vec<(u32, u32)>) -> Class {
+        ranges.sort_unstable();
+        let mut result: Vec<(u32, u32)> = Vec::with_capacity(ranges.len());
+        for (lo, hi) in ranges {
+            match result.last_mut() {
+                Some(last) if lo <= last.1.saturating_add(1) => last.1 = last.1.max(hi),
+                _ => result.push((lo, hi)),
+            }
+        }
+        Class { ranges: result }
+    }
+
+    fn negate(&self) -> Class {
+        let mut result = Vec::with_capacity(self.ranges.len() + 1);
+        let mut next = 0;
+        for &(lo, hi) in &self.ranges {
+            if lo > next {
+                result.push((next, lo - 1));
+            }
+            next = hi + 1;
+        }
+        if next <= char::MAX as u32 {
+            result.push((next, char::MAX as u32));
+        }
+        Class { ranges: result }
+    }
+
+    fn contains(&self, ch: char) -> bool {
+        let c = ch as u32;
+        self.ranges.iter().any(|&(lo, hi)| lo <= c && c <= hi)
+    }
+
+    fn digit() -> Class {
+        Class::new(vec![('0' as u32, '9' as u32)])
+    }
+
+    fn word() -> Class {
+        Class::new(vec![
+            ('0' as u32, '9' as u32),
+            ('A' as u32, 'Z' as u32),
+            ('_' as u32, '_' as u32),
+            ('a' as u32, 'z' as u32),
+        ])
+    }
+
+    fn space() -> Class {
+        Class::new(vec![(0x09, 0x0d), (0x20, 0x20)])
+    }
+}
+
+fn is_word_char(ch: Option<char>) -> bool {
+    matches!(ch, Some(c) if c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[derive(Copy, Clone, Debug)]
+enum Assertion {
+    Start,
+    End,
+    WordBoundary,
+    NotWordBoundary,
+}
+
+impl Assertion {
+    fn holds(self, haystack: &str, pos: usize) -> bool {
+        let prev = haystack[..pos].chars().next_back();
+        let next = haystack[pos..].chars().next();
+        match self {
+            Assertion::Start => pos == 0,
+            Assertion::End => pos == haystack.len(),
+            Assertion::WordBoundary => is_word_char(prev) != is_word_char(next),
+            Assertion::NotWordBoundary => is_word_char(prev) == is_word_char(next),
+        }
+    }
+}
+
+/// The syntax tree of a pattern.
+#[derive(Debug)]
+enum Node {
+    Empty,
+    Char(char),
+    Class(Class),
+    Assert(Assertion),
+    Group(Option<usize>, Box<Node>),
+    Concat(Vec<Node>),
+    Alt(Vec<Node>),
+    Repeat {
+        inner: Box<Node>,
+        min: u32,
+        max: Option<u32>,
+        greedy: bool,
+    },
+}
+
+struct Parser<'a> {
+    pattern: &'a str,
+    pos: usize,
+    n_groups: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<char> {
+        self.pattern[self.pos..].chars().next()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let ch = self.peek()?;
+        self.pos += ch.len_utf8();
+        Some(ch)
+    }
+
+    fn error<T>(&self, message: &'static str, start: usize) -> PResult<T> {
+        Err(PatternError {
+            message,
+            range: start..self.pos,
+        })
+    }
+
+    fn parse_alt(&mut self) -> PResult<Node> {
+        let mut alternatives = vec![self.parse_concat()?];
+        while self.peek() == Some('|') {
+            self.next();
+            alternatives.push(self.parse_concat()?);
+        }
+        match alternatives.len() {
+            1 => Ok(alternatives.pop().expect("Has one element.")),
+            _ => Ok(Node::Alt(alternatives)),
+        }
+    }
+
+    fn parse_concat(&mut self) -> PResult<Node> {
+        let mut nodes = Vec::new();
+        loop {
+            match self.peek() {
+                None | Some('|') | Some(')') => break,
+                Some(..) => nodes.push(self.parse_repeat()?),
+            }
+        }
+        match nodes.len() {
+            0 => Ok(Node::Empty),
+            1 => Ok(nodes.pop().expect("Has one element.")),
+            _ => Ok(Node::Concat(nodes)),
+        }
+    }
+
+    fn parse_repeat(&mut self) -> PResult<Node> {
+        let atom = self.parse_atom()?;
+        let start = self.pos;
+        let (min, max) = match self.peek() {
+            Some('*') => {
+                self.next();
+                (0, None)
+            }
+            Some('+') => {
+                self.next();
+                (1, None)
+            }
+            Some('?') => {
+                self.next();
+                (0, Some(1))
+            }
+            Some('{') => self.parse_counts()?,
+            _ => return Ok(atom),
+        };
+        let greedy = match self.peek() {
+            Some('?') => {
+                self.next();
+                false
+            }
+            _ => true,
+        };
+        if let Some('*' | '+' | '?' | '{') = self.peek() {
+            let repeat_start = self.pos;
+            self.next();
+            return self.error(
+                "Nothing to repeat, the preceding item is already repeated.",
+                repeat_start,
+            );
+        }
+        if let Node::Assert(..) = atom {
+            return self.error("Anchors and word boundaries cannot be repeated.", start);
+        }
+        Ok(Node::Repeat {
+            inner: Box::new(atom),
+            min,
+            max,
+            greedy,
+        })
+    }
+
+    /// Parse a number in a `{n,m}` repetition.
+    fn parse_count(&mut self, start: usize) -> PResult<Option<u32>> {
+        let digits_start = self.pos;
+        while let Some('0'..='9') = self.peek() {
+            self.next();
+        }
+        let digits = &self.pattern[digits_start..self.pos];
+        if digits.is_empty() {
+            return Ok(None);
+        }
+        match digits.parse::<u32>() {
+            Ok(n) if n <= MAX_REPETITION => Ok(Some(n)),
+            _ => self.error("Repetition count can be at most 1000.", start),
+        }
+    }
+
+    fn parse_counts(&mut self) -> PResult<(u32, Option<u32>)> {
+        let start = self.pos;
+        self.next();
+        let min = self.parse_count(start)?;
+        let max = match self.peek() {
+            Some(',') => {
+                self.next();
+                self.parse_count(start)?
+            }
+            _ => min,
+        };
+        let min = match (min, self.next()) {
+            (Some(min), Some('}')) => min,
+            _ => {
+                return self.error(
+                    "Invalid repetition, expected '{n}', '{n,}', or '{n,m}'.",
+                    start,
+                )
+            }
+        };
+        match max {
+            Some(max) if max < min => {
+                self.error("Repetition minimum is greater than the maximum.", start)
+            }
+            _ => Ok((min, max)),
+        }
+    }
+
+    fn parse_atom(&mut self) -> PResult<Node> {
+        let start = self.pos;
+        let ch = self
+            .next()
+            .expect("Caller checked that we are not at the end.");
+        let node = match ch {
+            '.' => Node::Class(Class::new(vec![('\n' as u32, '\n' as u32)]).negate()),
+            '^' => Node::Assert(Assertion::Start),
+            '$' => Node::Assert(Assertion::End),
+            '(' => return self.parse_group(start),
+            '[' => return self.parse_class(start),
+            '\\' => return self.parse_escape(start, false),
+            '*' | '+' | '?' | '{' => return self.error("Nothing to repeat.", start),
+            ch => Node::Char(ch),
+        };
+        Ok(node)
+    }
+
+    fn parse_group(&mut self, start: usize) -> PResult<Node> {
+        let index = if self.pattern[self.pos..].starts_with("?:") {
+            self.pos += 2;
+            None
+        } else if self.peek() == Some('?') {
+            self.next();
+            return self.error(
+                "Unsupported group syntax, only '(?:' for non-capturing groups is supported.",
+                start,
+            );
+        } else {
+            self.n_groups += 1;
+            Some(self.n_groups)
+        };
+        let inner = self.parse_alt()?;
+        match self.next() {
+            Some(')') => Ok(Node::Group(index, Box::new(inner))),
+            _ => Err(PatternError {
+                message: "Unclosed group, expected ')'.",
+                range: start..start + 1,
+            }),
+        }
+    }
+
+    /// Parse an escape sequence, after the backslash.
+    ///
+    /// Inside a character class, the `\b` and `\B` escapes are not allowed.
+    fn parse_escape(&mut self, start: usize, in_class: bool) -> PResult<Node> {
+        let ch = match self.next() {
+            Some(ch) => ch,
+            None => return self.error("Incomplete escape sequence at the end.", start),
+        };
+        let node = match ch {
+            'd' => Node::Class(Class::digit()),
+            'D' => Node::Class(Class::digit().negate()),
+            'w' => Node::Class(Class::word()),
+            'W' => Node::Class(Class::word().negate()),
+            's' => Node::Class(Class::space()),
+            'S' => Node::Class(Class::space().negate()),
+            'b' if !in_class => Node::Assert(Assertion::WordBoundary),
+            'B' if !in_class => Node::Assert(Assertion::NotWordBoundary),
+            'n' => Node::Char('\n'),
+            'r' => Node::Char('\r'),
+            't' => Node::Char('\t'),
+            ch if ch.is_ascii_punctuation() => Node::Char(ch),
+            _ => return self.error("Unknown escape sequence.", start),
+        };
+        Ok(node)
+    }
+
+    fn parse_class(&mut self, start: usize) -> PResult<Node> {
+        let negated = match self.peek() {
+            Some('^') => {
+                self.next();
+                true
+            }
+            _ => false,
+        };
+        let mut ranges = Vec::new();
+        let mut is_first = true;
+        loop {
+            let item_start = self.pos;
+            let lo = match self.next() {
+                None => {
+                    return Err(PatternError {
+                        message: "Unclosed character class, expected ']'.",
+                        range: start..start + 1,
+                    })
+                }
+                // A `]` at the start is a literal, not the end of the class.
+                Some(']') if !is_first => break,
+                Some('\\') => match self.parse_escape(item_start, true)? {
+                    Node::Char(ch) => ch,
+                    Node::Class(class) => {
+                        ranges.extend(class.ranges);
+                        is_first = false;
+                        continue;
+                    }
+                    _ => unreachable!("Escapes in classes are chars or classes."),
+                },
+                Some(ch) => ch,
+            };
+            is_first = false;
+
+            // A `-` at the end is a literal, otherwise it denotes a range.
+            let is_range = self.pattern[self.pos..].starts_with('-')
+                && !self.pattern[self.pos..].starts_with("-]");
+            if !is_range {
+                ranges.push((lo as u32, lo as u32));
+                continue;
+            }
+            self.next();
+            let hi = match self.next() {
+                Some('\\') => match self.parse_escape(self.pos - 1, true)? {
+                    Node::Char(ch) => ch,
+                    _ => return self.error("Invalid range end in character class.", item_start),
+                },
+                Some(ch) => ch,
+                None => continue,
+            };
+            if hi < lo {
+                return self.error(
+                    "Invalid range in character class, the start is greater than the end.",
+                    item_start,
+                );
+            }
+            ranges.push((lo as u32, hi as u32));
+        }
+        let class = Class::new(ranges);
+        Ok(Node::Class(if negated { class.negate() } else { class }))
+    }
+}
+
+#[derive(Debug)]
+enum Inst {
+    Char(char),
+    Class(Class),
+    Assert(Assertion),
+    /// Continue at both targets, with a preference for the first one.
+    Split(usize, usize),
+    Jump(usize),
+    Save(usize),
+    Match,
+}
+
+struct Compiler {
+    program: Vec<Inst>,
+}
+
+impl Compiler {
+    fn emit(&mut self, inst: Inst) -> Option<usize> {
+        if self.program.len() >= MAX_PROGRAM_LEN {
+            return None;
+        }
+        self.program.push(inst);
+        Some(self.program.len() - 1)
+    }
+
+    fn compile(&mut self, node: &Node) -> Option<()> {
+        match node {
+            Node::Empty => {}
+            Node::Char(ch) => {
+                self.emit(Inst::Char(*ch))?;
+            }
+            Node::Class(class) => {
+                self.emit(Inst::Class(class.clone()))?;
+            }
+            Node::Assert(assertion) => {
+                self.emit(Inst::Assert(*assertion))?;
+            }
+            Node::Group(None, inner) => self.compile(inner)?,
+            Node::Group(Some(i), inner) => {
+                self.emit(Inst::Save(2 * i))?;
+                self.compile(inner)?;
+                self.emit(Inst::Save(2 * i + 1))?;
+            }
+            Node::Concat(nodes) => {
+                for node in nodes {
+                    self.compile(node)?;
+                }
+            }
+            Node::Alt(alternatives) => {
+                let mut jumps = Vec::new();
+                let (last, init) = alternatives.split_last().expect("Alt has alternatives.");
+                for alternative in init {
+                    let split = self.emit(Inst::Split(0, 0))?;
+                    self.compile(alternative)?;
+                    jumps.push(self.emit(Inst::Jump(0))?);
+                    self.program[split] = Inst::Split(split + 1, self.program.len());
+                }
+                self.compile(last)?;
+                let end = self.program.len();
+                for jump in jumps {
+                    self.program[jump] = Inst::Jump(end);
+                }
+            }
+            Node::Repeat {
+                inner,
+                min,
+                max,
+                greedy,
+            } => {
+                for _ in 0..*min {
+                    self.compile(inner)?;
+                }
+                let split = |a, b| match greedy {
+                    true => Inst::Split(a, b),
+                    false => Inst::Split(b, a),
+                };
+                match max {
+                    None => {
+                        let start = self.emit(Inst::Split(0, 0))?;
+                        self.compile(inner)?;
+                        self.emit(Inst::Jump(start))?;
+                        self.program[start] = split(start + 1, self.program.len());
+                    }
+                    Some(max) => {
+                        let mut splits = Vec::new();
+                        for _ in *min..*max {
+                            splits.push(self.emit(Inst::Split(0, 0))?);
+                            self.compile(inner)?;
+                        }
+                        let end = self.program.len();
+                        for s in splits {
+                            self.program[s] = split(s + 1, end);
+                        }
+                    }
+                }
+            }
+        }
+        Some(())
+    }
+}
+
+/// The capture slots of a thread, a start and end offset per group.
+type Slots = Vec<Option<usize>>;
+
+/// The threads at one position in the input, in order of priority.
+struct Threads {
+    /// Per instruction, whether we already visited it at this position.
+    visited: Vec<bool>,
+    /// The instructions we visited, so we can reset `visited` cheaply.
+    visited_pcs: Vec<usize>,
+    /// The threads that are waiting to consume a character, or that matched.
+    threads: Vec<(usize, Slots)>,
+}
+
+impl Threads {
+    fn new(program_len: usize) -> Threads {
+        Threads {
+            visited: vec![false; program_len],
+            visited_pcs: Vec::new(),
+            threads: Vec::new(),
+        }
+    }
+
+    fn clear(&mut self) {
+        for pc in self.visited_pcs.drain(..) {
+            self.visited[pc] = false;
+        }
+        self.threads.clear();
+    }
+}
+
+/// A compiled regular expression.
+#[derive(Debug)]
+pub struct Regex {
+    program: Vec<Inst>,
+    /// The number of groups, including the implicit group 0 for the full match.
+    n_groups: usize,
+}
+
+impl Regex {
+    pub fn new(pattern: &str) -> PResult<Regex> {
+        let mut parser = Parser {
+            pattern,
+            pos: 0,
+            n_groups: 0,
+        };
+        let node = parser.parse_alt()?;
+        if parser.peek() == Some(')') {
+            let start = parser.pos;
+            parser.next();
+            return parser.error("Unmatched ')'.", start);
+        }
+
+        let mut compiler = Compiler {
+            program: Vec::new(),
+        };
+        let root = Node::Group(Some(0), Box::new(node));
+        if compiler.compile(&root).is_none() || compiler.emit(Inst::Match).is_none() {
+            return Err(PatternError {
+                message: "Pattern is too large, it has too many repetitions.",
+                range: 0..pattern.len(),
+            });
+        }
+        Ok(Regex {
+            program: compiler.program,
+            n_groups: parser.n_groups + 1,
+        })
+    }
+
+    /// The number of groups, including the implicit group 0 for the full match.
+    pub fn n_groups(&self) -> usize {
+        self.n_groups
+    }
+
+    /// Follow all non-consuming instructions from `pc` and record the threads.
+    fn add_thread(&self, list: &mut Threads, haystack: &str, pos: usize, pc: usize, slots: Slots) {
+        // We explore depth-first with an explicit stack, so we visit threads
+        // in order of priority, without risk of overflowing the call stack.
+        let mut stack = vec![(pc, slots)];
+        while let Some((pc, mut slots)) = stack.pop() {
+            if list.visited[pc] {
+                continue;
+            }
+            list.visited[pc] = true;
+            list.visited_pcs.push(pc);
+            match &self.program[pc] {
+                Inst::Jump(target) => stack.push((*target, slots)),
+                Inst::Split(first, second) => {
+                    stack.push((*second, slots.clone()));
+                    stack.push((*first, slots));
+                }
+                Inst::Save(i) => {
+                    slots[*i] = Some(pos);
+                    stack.push((pc + 1, slots));
+                }
+                Inst::Assert(assertion) => {
+                    if assertion.holds(haystack, pos) {
+                        stack.push((pc + 1, slots));
+                    }
+                }
+                Inst::Char(..) | Inst::Class(..) | Inst::Match => list.threads.push((pc, slots)),
+            }
+        }
+    }
+
+    /// Find the leftmost match that starts at or after `start`.
+    ///
+    /// Returns the byte range per group, or `None` for groups that did not
+    /// participate in the match. Among matches that start at the same
+    /// position, we prefer the one that the pattern prefers, like a
+    /// backtracking engine would.
+    pub fn captures_at(&self, haystack: &str, start: usize) -> Option<Vec<Option<Range<usize>>>> {
+        let mut current = Threads::new(self.program.len());
+        let mut next = Threads::new(self.program.len());
+        let mut matched: Option<Slots> = None;
+        let mut pos = start;
+
+        loop {
+            // Start a new attempt at every position, until we find a match.
+            // It has the lowest priority, so it's added after existing threads.
+            if matched.is_none() {
+                self.add_thread(
+                    &mut current,
+                    haystack,
+                    pos,
+                    0,
+                    vec![None; 2 * self.n_groups],
+                );
+            }
+            if current.threads.is_empty() && matched.is_some() {
+                break;
+            }
+
+            let ch = haystack[pos..].chars().next();
+            let next_pos = pos + ch.map(char::len_utf8).unwrap_or(0);
+            for (pc, slots) in current.threads.drain(..) {
+                let is_consumed = match (&self.program[pc], ch) {
+                    (Inst::Match, _) => {
+                        // Threads after this one have a lower priority, so
+                        // we can cut them off.
+                        matched = Some(slots);
+                        break;
+                    }
+                    (Inst::Char(expected), Some(ch)) => *expected == ch,
+                    (Inst::Class(class), Some(ch)) => class.contains(ch),
+                    _ => false,
+                };
+                if is_consumed {
+                    self.add_thread(&mut next, haystack, next_pos, pc + 1, slots);
+                }
+            }
+
+            if ch.is_none() {
+                break;
+            }
+            pos = next_pos;
+            current.clear();
+            std::mem::swap(&mut current, &mut next);
+        }
+
+        matched.map(|slots| {
+            slots
+                .chunks(2)
+                .map(|pair| match (pair[0], pair[1]) {
+                    (Some(begin), Some(end)) => Some(begin..end),
+                    _ => None,
+                })
+                .collect()
+        })
+    }
+
+    /// Return whether the pattern matches anywhere in the haystack.
+    pub fn is_match(&self, haystack: &str) -> bool {
+        self.captures_at(haystack, 0).is_some()
+    }
+
+    /// Return all non-overlapping matches, from left to right.
+    ///
+    /// An empty match directly after a previous match is not included, so
+    /// `a*` matches `baa` twice, at the start and at `aa`, like in most
+    /// other regex engines.
+    pub fn captures_all(&self, haystack: &str) -> Vec<Vec<Option<Range<usize>>>> {
+        let mut result = Vec::new();
+        let mut pos = 0;
+        let mut last_end = None;
+        while pos <= haystack.len() {
+            let captures = match self.captures_at(haystack, pos) {
+                Some(c) => c,
+                None => break,
+            };
+            let range = captures[0].clone().expect("Group 0 always participates.");
+            let is_empty = range.is_empty();
+            if is_empty && last_end == Some(range.end) {
+                pos = range.end + next_char_len(haystack, range.end);
+                continue;
+            }
+            pos = match is_empty {
+                true => range.end + next_char_len(haystack, range.end),
+                false => range.end,
+            };
+            last_end = Some(range.end);
+            result.push(captures);
+        }
+        result
+    }
+}
+
+/// A piece of a replacement string, see [`parse_replacement`].
+#[derive(Debug, Eq, PartialEq)]
+pub enum Replacement<'a> {
+    Literal(&'a str),
+    Group(usize),
+}
+
+/// Parse a replacement string that may refer to groups as `$n` or `${n}`.
+///
+/// A literal `$` is written `$$`. The pattern has `n_groups` groups, including
+/// group 0 for the full match, and references to other groups are an error.
+pub fn parse_replacement(replacement: &str, n_groups: usize) -> PResult<Vec<Replacement>> {
+    let mut result = Vec::new();
+    let mut literal_start = 0;
+    let mut pos = 0;
+    let bytes = replacement.as_bytes();
+    while let Some(offset) = replacement[pos..].find('$') {
+        let start = pos + offset;
+        if start > literal_start {
+            result.push(Replacement::Literal(&replacement[literal_start..start]));
+        }
+        let (digits, end) = match &bytes[start + 1..] {
+            [b'$', ..] => {
+                result.push(Replacement::Literal("$"));
+                pos = start + 2;
+                literal_start = pos;
+                continue;
+            }
+            [b'{', rest @ ..] => match rest.iter().position(|b| *b == b'}') {
+                Some(n) => (&replacement[start + 2..start + 2 + n], start + 3 + n),
+                None => (&replacement[start + 2..start + 2], start + 2),
+            },
+            rest => {
+                let n = rest.iter().take_while(|b| b.is_ascii_digit()).count();
+                (&replacement[start + 1..start + 1 + n], start + 1 + n)
+            }
+        };
+        let is_digits = !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit());
+        let group = match digits.parse::<usize>() {
+            Ok(i) if is_digits && i < n_groups => i,
+            Ok(..) if is_digits => {
+                return Err(PatternError {
+                    message: "Replacement refers to a group that does not exist in the pattern.",
+                    range: start..end,
+                })
+            }
+            _ => return Err(PatternError {
+                message:
+                    "Invalid group reference, expected '$n' or '${n}', or '$$' for a literal '$'.",
+                range: start..end.max(start + 1),
+            }),
+        };
+        result.push(Replacement::Group(group));
+        pos = end;
+        literal_start = end;
+    }
+    if literal_start < replacement.len() {
+        result.push(Replacement::Literal(&replacement[literal_start..]));
+    }
+    Ok(result)
+}
+
+/// Return the length of the char at `pos`, or 1 at the end of the input.
+fn next_char_len(haystack: &str, pos: usize) -> usize {
+    haystack[pos..]
+        .chars()
+        .next()
+        .map(char::len_utf8)
+        .unwrap_or(1)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{PatternError, Regex};
+
+    fn find(pattern: &str, haystack: &str) -> Option<String> {
+        let regex = Regex::new(pattern).unwrap();
+        let captures = regex.captures_at(haystack, 0)?;
+        Some(haystack[captures[0].clone().unwrap()].to_string())
+    }
+
+    fn find_all(pattern: &str, haystack: &str) -> Vec<String> {
+        let regex = Regex::new(pattern).unwrap();
+        regex
+            .captures_all(haystack)
+            .into_iter()
+            .map(|c| haystack[c[0].clone().unwrap()].to_string())
+            .collect()
+    }
+
+    fn error(pattern: &str) -> (&'static str, std::ops::Range<usize>) {
+        let PatternError { message, range } = Regex::new(pattern).unwrap_err();
+        (message, range)
+    }
+
+    #[test]
+    fn regex_matches_literals_and_classes() {
+        assert_eq!(find("abc", "xxabcxx"), Some("abc".into()));
+        assert_eq!(find("a.c", "a\nc abc"), Some("abc".into()));
+        assert_eq!(find("[a-c]+", "xxcabbd"), Some("cabb".into()));
+        assert_eq!(find("[^a-c]+", "abxyz"), Some("xyz".into()));
+        assert_eq!(find("[]a]+", "x]a]"), Some("]a]".into()));
+        assert_eq!(find("[a-]+", "x-a-"), Some("-a-".into()));
+        assert_eq!(find(r"\d+", "v12.3"), Some("12".into()));
+        assert_eq!(find(r"[\d.]+", "v12.3"), Some("12.3".into()));
+        assert_eq!(find(r"\w+", "  foo_1 "), Some("foo_1".into()));
+        assert_eq!(find(r"\S+", "  ab "), Some("ab".into()));
+        assert_eq!(find(r"\.", "a.b"), Some(".".into()));
+        assert_eq!(find("é+", "caféé"), Some("éé".into()));
+        assert_eq!(find("x", "abc"), None);
+    }
+
+    #[test]
+    fn regex_matches_anchors_and_boundaries() {
+        assert_eq!(find("^ab", "abab"), Some("ab".into()));
+        assert_eq!(find("^b", "abab"), None);
+        assert_eq!(find("b$", "abab"), Some("b".into()));
+        assert_eq!(find(r"\bcat\b", "concat cat"), Some("cat".into()));
+        assert_eq!(find_all(r"\bcat\b", "concat cat"), vec!["cat"]);
+        assert_eq!(find_all(r"\Bcat", "concat cat"), vec!["cat"]);
+        assert_eq!(find("^$", ""), Some("".into()));
+    }
+
+    #[test]
+    fn regex_prefers_like_backtracking_engines() {
+        assert_eq!(find("a+", "aaa"), Some("aaa".into()));
+        assert_eq!(find("a+?", "aaa"), Some("a".into()));
+        assert_eq!(find("a|ab", "ab"), Some("a".into()));
+        assert_eq!(find("ab|a", "ab"), Some("ab".into()));
+        assert_eq!(find("a{2,3}", "aaaa"), Some("aaa".into()));
+        assert_eq!(find("a{2,3}?", "aaaa"), Some("aa".into()));
+        assert_eq!(find("a{2}", "aaaa"), Some("aa".into()));
+        assert_eq!(find("a{2,}", "aaaa"), Some("aaaa".into()));
+        assert_eq!(find("(a*)*b", "aab"), Some("aab".into()));
+    }
+
+    #[test]
+    fn regex_reports_captures() {
+        let regex = Regex::new(r"(\d+)\.(\d+)(?:\.(\d+))?(x)?").unwrap();
+        assert_eq!(regex.n_groups(), 5);
+        let captures = regex.captures_at("v1.25", 0).unwrap();
+        assert_eq!(
+            captures,
+            vec![Some(1..5), Some(1..2), Some(3..5), None, None]
+        );
+    }
+
+    #[test]
+    fn regex_finds_all_matches() {
+        assert_eq!(find_all(r"\d+", "a1b22c333"), vec!["1", "22", "333"]);
+        assert_eq!(find_all("a*", "baa"), vec!["", "aa"]);
+        assert_eq!(find_all("x*", "ab"), vec!["", "", ""]);
+        assert_eq!(find_all("", "é"), vec!["", ""]);
+    }
+
+    #[test]
+    fn parse_replacement_handles_group_references() {
+        use super::{parse_replacement, Replacement::*};
+        assert_eq!(
+            parse_replacement("a$1b${0}$$c", 2),
+            Ok(vec![
+                Literal("a"),
+                Group(1),
+                Literal("b"),
+                Group(0),
+                Literal("$"),
+                Literal("c")
+            ]),
+        );
+        assert_eq!(parse_replacement("$12", 13), Ok(vec![Group(12)]));
+        assert_eq!(parse_replacement("$2", 2).unwrap_err().range, 0..2);
+        assert_eq!(parse_replacement("x$", 2).unwrap_err().range, 1..2);
+        assert_eq!(parse_replacement("x$a", 2).unwrap_err().range, 1..2);
+        assert_eq!(parse_replacement("${a}", 2).unwrap_err().range, 0..4);
+        assert_eq!(parse_replacement("${1", 2).unwrap_err().range, 0..2);
+    }
+
+    #[test]
+    fn regex_runs_in_linear_time() {
+        // This pattern is exponential for a backtracking engine.
+        let haystack = "a".repeat(10_000);
+        assert_eq!(find("(a|a)*(a|a)*b", &haystack), None);
+    }
+
+    #[test]
+    fn regex_reports_errors_with_offsets() {
+        assert_eq!(error("ab(cd").1, 2..3);
+        assert_eq!(error("ab)").1, 2..3);
+        assert_eq!(error("a[bc").1, 1..2);
+        assert_eq!(error(r"a\q").1, 1..3);
+        assert_eq!(error("a**").1, 2..3);
+        assert_eq!(error("*a").1, 0..1);
+        assert_eq!(error("a{3,2}").1, 1..6);
+        assert_eq!(error("a{x}").1, 1..3);
+        assert_eq!(error("a{1001}").1, 1..6);
+        assert_eq!(error("[z-a]").1, 1..4);
+        assert_eq!(error("(?=a)").1, 0..2);
+        assert_eq!(error("^*").1, 1..2);
+        assert_eq!(error("((a{1000}){1000}){1000}").1, 0..23);
+    }
+}
@@ -8,7 +8,6 @@
 //! Representations of values and scopes at runtime.
 
 use std::cmp::Ordering;
-use std::collections::{BTreeMap, BTreeSet};
 use std::rc::Rc;
 
 use crate::ast::{CallArg, Expr};
@@ -17,6 +16,7 @@ use crate::error::{IntoError, PathElement, Result};
 use crate::eval::Evaluator;
 use crate::fmt_rcl::format_rcl;
 use crate::fmt_type::format_type;
+use crate::ordered::{OrderedMap, OrderedSet};
 use crate::pprint::{concat, indent, Doc};
 use crate::source::Span;
 use crate::type_diff::{Mismatch, TypeDiff};
@@ -160,11 +160,9 @@ pub enum Value {
 
     List(Rc<Vec<Value>>),
 
-    // TODO: Should preserve insertion order.
-    Set(Rc<BTreeSet<Value>>),
+    Set(Rc<OrderedSet<Value>>),
 
-    // TODO: Should preserve insertion order.
-    Dict(Rc<BTreeMap<Value, Value>>),
+    Dict(Rc<OrderedMap<Value, Value>>),
 
     Function(Rc<Function>),
 
@@ -198,7 +196,7 @@ impl Value {
 
     /// Extract the dict if it is one, panic otherwise.
     #[inline]
-    pub fn expect_dict(&self) -> &BTreeMap<Value, Value> {
+    pub fn expect_dict(&self) -> &OrderedMap<Value, Value> {
         match self {
             Value::Dict(inner) => inner,
             other => panic!("Expected Dict but got {other:?}."),
@@ -216,7 +214,7 @@ impl Value {
 
     /// Extract the list if it is one, panic otherwise.
     #[inline]
-    pub fn expect_set(&self) -> &BTreeSet<Value> {
+    pub fn expect_set(&self) -> &OrderedSet<Value> {
         match self {
             Value::Set(inner) => inner,
             other => panic!("Expected Set but got {other:?}."),
@@ -356,6 +354,23 @@ impl Value {
             Value::BuiltinMethod(m) => 1 + m.receiver.depth(),
         }
     }
+
+    /// Return a copy where all dicts and sets, including nested ones, are sorted.
+    ///
+    /// Dicts and sets preserve insertion order, this is for when the output
+    /// should be sorted instead.
+    pub fn sort_keys(&self) -> Value {
+        match self {
+            Value::List(xs) => Value::List(Rc::new(xs.iter().map(Value::sort_keys).collect())),
+            Value::Set(xs) => Value::Set(Rc::new(xs.iter_sorted().map(Value::sort_keys).collect())),
+            Value::Dict(kv) => Value::Dict(Rc::new(
+                kv.iter_sorted()
+                    .map(|(k, v)| (k.sort_keys(), v.sort_keys()))
+                    .collect(),
+            )),
+            other => other.clone(),
+        }
+    }
 }
 
 impl<'a> From<&'a str> for Value {
@@ -7,18 +7,27 @@
 
 //! Implementation of the standard library.
 
-use std::collections::{BTreeMap, BTreeSet};
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, VecDeque};
+use std::ops::Range;
 use std::rc::Rc;
 
 use crate::ast::CallArg;
-use crate::decimal::Decimal;
-use crate::error::{IntoError, Result};
+use crate::cli::OutputFormat;
+use crate::cmd_eval::Formatted;
+use crate::decimal::{Decimal, Mantissa};
+use crate::digest::{md5, sha1, sha256};
+use crate::error::{Error, IntoError, PathElement, Result};
 use crate::eval::Evaluator;
 use crate::fmt_rcl::format_rcl;
 use crate::markup::Markup;
+use crate::net::{Addr, AddrError, Cidr};
+use crate::ordered::{OrderedMap, OrderedSet};
 use crate::pprint::{concat, indent, Doc};
+use crate::regex::{parse_replacement, PatternError, Regex, Replacement};
 use crate::runtime::{builtin_function, builtin_method, FunctionCall, MethodCall, Value};
 use crate::source::Span;
+use crate::string::locate_in_literal;
 use crate::types::AsTypeName;
 
 builtin_function!(
@@ -59,16 +68,294 @@ fn builtin_std_format_json(_eval: &mut Evaluator, call: FunctionCall) -> Result<
     Ok(Value::String(result.into()))
 }
 
+/// The default width for `std.format`, same as for the CLI.
+const DEFAULT_FORMAT_WIDTH: u32 = 80;
+
+/// Pretty-print a document into a string, in the same way as `rcl evaluate`.
+///
+/// Unlike the CLI, and like `std.format_json`, the result does not end in a
+/// newline.
+fn print_to_string(doc: Doc, width: u32) -> Value {
+    let cfg = crate::pprint::Config { width: Some(width) };
+    let mut result = doc.println(&cfg).to_string_no_markup();
+    // `println` ends the document in exactly one newline, remove it.
+    let newline = result.pop();
+    debug_assert_eq!(newline, Some('\n'));
+    Value::String(result.into())
+}
+
+builtin_function!(
+    "std.format",
+    (value: Any, options: {String: Any}) -> String,
+    const STD_FORMAT,
+    builtin_std_format
+);
+fn builtin_std_format(_eval: &mut Evaluator, call: FunctionCall) -> Result<Value> {
+    let value_arg = &call.args[0];
+    let options_arg = &call.args[1];
+
+    let options = match &options_arg.value {
+        Value::Dict(d) => d,
+        _ => {
+            return options_arg
+                .span
+                .error("Expected a Dict of options here.")
+                .err()
+        }
+    };
+
+    let mut format: Option<OutputFormat> = None;
+    let mut sort_keys = false;
+    let mut width = DEFAULT_FORMAT_WIDTH;
+
+    for (k, v) in options.iter() {
+        let option_name = match k {
+            Value::String(s) => s.as_ref(),
+            _ => "",
+        };
+        match option_name {
+            "format" => {
+                let parsed = match v {
+                    Value::String(s) => crate::cmd_build::parse_format(s),
+                    _ => None,
+                };
+                match parsed {
+                    Some(OutputFormat::Cbor | OutputFormat::Msgpack) => {
+                        return options_arg
+                            .span
+                            .error(concat! {
+                                "Binary format " format_rcl(v).into_owned()
+                                " cannot be formatted as a string."
+                            })
+                            .err()
+                    }
+                    Some(f) => format = Some(f),
+                    None => {
+                        return options_arg
+                            .span
+                            .error(concat! {
+                                "Invalid output format: " format_rcl(v).into_owned() "."
+                            })
+                            .with_help("See 'rcl evaluate --help' for supported output formats.")
+                            .err()
+                    }
+                }
+            }
+            "sort_keys" => match v {
+                Value::Bool(b) => sort_keys = *b,
+                _ => {
+                    return options_arg
+                        .span
+                        .error("Option 'sort_keys' must be a boolean.")
+                        .err()
+                }
+            },
+            "width" => match v.to_i64() {
+                Some(w) if w > 0 && w < 16_000 => width = w as u32,
+                _ => {
+                    return options_arg
+                        .span
+                        .error("Option 'width' must be a positive integer below 16,000.")
+                        .err()
+                }
+            },
+            _ => {
+                return options_arg
+                    .span
+                    .error(concat! { "Unknown option: " format_rcl(k).into_owned() "." })
+                    .with_help("The supported options are 'format', 'sort_keys', and 'width'.")
+                    .err()
+            }
+        }
+    }
+
+    let format = match format {
+        Some(f) => f,
+        None => return options_arg.span.error("Option 'format' is required.").err(),
+    };
+
+    let sorted;
+    let value = match sort_keys {
+        true => {
+            sorted = value_arg.value.sort_keys();
+            &sorted
+        }
+        false => &value_arg.value,
+    };
+
+    match crate::cmd_eval::format_value(format, value_arg.span, value)? {
+        Formatted::Doc(doc) => Ok(print_to_string(doc, width)),
+        Formatted::Bytes(..) => unreachable!("Binary formats are rejected above."),
+    }
+}
+
+builtin_function!(
+    "std.parse_csv",
+    (text: String, options: {String: Any}) -> [Any],
+    const STD_PARSE_CSV,
+    builtin_std_parse_csv
+);
+fn builtin_std_parse_csv(_eval: &mut Evaluator, call: FunctionCall) -> Result<Value> {
+    let text_arg = &call.args[0];
+    let options_arg = &call.args[1];
+
+    let text = match &text_arg.value {
+        Value::String(s) => s.as_ref(),
+        _ => return text_arg.span.error("Expected a String here.").err(),
+    };
+    let options = match &options_arg.value {
+        Value::Dict(d) => d,
+        _ => {
+            return options_arg
+                .span
+                .error("Expected a Dict of options here.")
+                .err()
+        }
+    };
+
+    let mut has_header = false;
+    let mut separator = ',';
+
+    for (k, v) in options.iter() {
+        let option_name = match k {
+            Value::String(s) => s.as_ref(),
+            _ => "",
+        };
+        match (option_name, v) {
+            ("header", Value::Bool(b)) => has_header = *b,
+            ("header", _) => {
+                return options_arg
+                    .span
+                    .error("Option 'header' must be a boolean.")
+                    .err()
+            }
+            ("separator", Value::String(s)) if s.chars().count() == 1 => {
+                separator = s.chars().next().expect("We checked the length.");
+            }
+            ("separator", _) => {
+                return options_arg
+                    .span
+                    .error("Option 'separator' must be a string of one character.")
+                    .err()
+            }
+            _ => {
+                return options_arg
+                    .span
+                    .error(concat! { "Unknown option: " format_rcl(k).into_owned() "." })
+                    .with_help("The supported options are 'header' and 'separator'.")
+                    .err()
+            }
+        }
+    }
+
+    let records = match crate::csv::parse(text, separator) {
+        Ok(records) => records,
+        Err(err) => {
+            return text_arg
+                .span
+                .error(concat! {
+                    "Invalid CSV on line "
+                    Doc::string(err.line.to_string()).with_markup(Markup::Number)
+                    ". "
+                    err.message
+                })
+                .err()
+        }
+    };
+
+    let str_value = |s: String| Value::String(s.into());
+
+    if !has_header {
+        let rows = records
+            .into_iter()
+            .map(|(_line, fields)| {
+                Value::List(Rc::new(fields.into_iter().map(str_value).collect()))
+            })
+            .collect();
+        return Ok(Value::List(Rc::new(rows)));
+    }
+
+    let mut records = records.into_iter();
+    let header: Vec<Value> = match records.next() {
+        None => return Ok(Value::List(Rc::new(Vec::new()))),
+        Some((_line, fields)) => fields.into_iter().map(str_value).collect(),
+    };
+
+    for (i, name) in header.iter().enumerate() {
+        if header[..i].contains(name) {
+            return text_arg
+                .span
+                .error(concat! {
+                    "Duplicate column in CSV header: " format_rcl(name).into_owned() "."
+                })
+                .err();
+        }
+    }
+
+    let mut rows = Vec::new();
+    for (line, fields) in records {
+        if fields.len() != header.len() {
+            return text_arg
+                .span
+                .error(concat! {
+                    "Invalid CSV on line "
+                    Doc::string(line.to_string()).with_markup(Markup::Number)
+                    ". Expected "
+                    Doc::string(header.len().to_string()).with_markup(Markup::Number)
+                    " fields to match the header, but found "
+                    Doc::string(fields.len().to_string()).with_markup(Markup::Number)
+                    "."
+                })
+                .err();
+        }
+        let row: OrderedMap<Value, Value> = header
+            .iter()
+            .cloned()
+            .zip(fields.into_iter().map(str_value))
+            .collect();
+        rows.push(Value::Dict(Rc::new(row)));
+    }
+
+    Ok(Value::List(Rc::new(rows)))
+}
+
+/// Extract an integer from a call argument, or return an error if it's not an integer.
+///
+/// Returns `None` for integers that are too large to represent as a mantissa,
+/// such as `1e2000`, callers should report those as out of range.
+fn expect_arg_integer(arg: &CallArg<Value>, arg_name: &'static str) -> Result<Option<Mantissa>> {
+    if let Value::Number(d) = &arg.value {
+        match d.to_integer() {
+            Some(n) => return Ok(Some(n)),
+            None if d.exponent as i32 > d.decimals as i32 => return Ok(None),
+            None => {}
+        }
+    }
+    arg.span
+        .error(concat! {
+            "Expected "
+            arg_name
+            " to be integer, but got "
+            format_rcl(&arg.value).into_owned()
+            "."
+        })
+        .err()
+}
+
 /// Extract an i64 from a call argument, or return an error if it's not an integer.
 fn expect_arg_i64(arg: &CallArg<Value>, arg_name: &'static str) -> Result<i64> {
-    match arg.value.to_i64() {
+    match expect_arg_integer(arg, arg_name)?.and_then(|n| n.to_i64()) {
         Some(n) => Ok(n),
         None => arg
             .span
             .error(concat! {
                 "Expected "
                 arg_name
-                " to be integer, but got "
+                " to be between "
+                i64::MIN.to_string()
+                " and "
+                i64::MAX.to_string()
+                ", but got "
                 format_rcl(&arg.value).into_owned()
                 "."
             })
@@ -76,6 +363,370 @@ fn expect_arg_i64(arg: &CallArg<Value>, arg_name: &'static str) -> Result<i64> {
     }
 }
 
+/// Read an argument that must be an integer of at least `min`, as `usize`.
+fn expect_arg_usize(arg: &CallArg<Value>, arg_name: &'static str, min: i64) -> Result<usize> {
+    let n = expect_arg_i64(arg, arg_name)?;
+    if n < min {
+        return arg
+            .span
+            .error(concat! {
+                "Expected " arg_name " to be at least " min.to_string()
+                ", but got " n.to_string() "."
+            })
+            .err();
+    }
+    // On 32-bit targets, not every i64 fits in a usize.
+    match usize::try_from(n) {
+        Ok(n) => Ok(n),
+        Err(..) => arg
+            .span
+            .error(concat! {
+                "Expected " arg_name " to be at most " usize::MAX.to_string()
+                ", but got " n.to_string() "."
+            })
+            .err(),
+    }
+}
+
+/// Read an argument that must be a non-negative integer.
+fn expect_arg_count(arg: &CallArg<Value>, arg_name: &'static str) -> Result<usize> {
+    expect_arg_usize(arg, arg_name, 0)
+}
+
+/// Read an argument that must be a positive integer.
+fn expect_arg_size(arg: &CallArg<Value>, arg_name: &'static str) -> Result<usize> {
+    expect_arg_usize(arg, arg_name, 1)
+}
+
+builtin_function!(
+    "std.dict_from_pairs",
+    (pairs: [[Any]]) -> {Any: Any},
+    const STD_DICT_FROM_PAIRS,
+    builtin_std_dict_from_pairs
+);
+fn builtin_std_dict_from_pairs(_eval: &mut Evaluator, call: FunctionCall) -> Result<Value> {
+    let pairs_arg = &call.args[0];
+    let pairs = match &pairs_arg.value {
+        Value::List(pairs) => pairs,
+        _ => return pairs_arg.span.error("Pairs must be a list.").err(),
+    };
+    let mut result = OrderedMap::new();
+    for pair in pairs.iter() {
+        match pair {
+            Value::List(kv) if kv.len() == 2 => {
+                result.insert(kv[0].clone(), kv[1].clone());
+            }
+            not_pair => {
+                return pairs_arg
+                    .span
+                    .error(concat! {
+                        "Expected a list of key-value pairs, but found "
+                        format_rcl(not_pair).into_owned()
+                        "."
+                    })
+                    .with_help("A pair is a list of two elements, a key and a value.")
+                    .err();
+            }
+        }
+    }
+    Ok(Value::Dict(Rc::new(result)))
+}
+
+/// Read an argument that must be a string, for the hash and encoding functions.
+fn expect_arg_string(arg: &CallArg<Value>) -> Result<&str> {
+    match &arg.value {
+        Value::String(s) => Ok(s.as_ref()),
+        not_string => arg
+            .span
+            .error(concat! {
+                "Expected a string, but got "
+                format_rcl(not_string).into_owned()
+                "."
+            })
+            .err(),
+    }
+}
+
+/// Extract a number from a call argument, or report an error at the argument.
+///
+/// The typechecker cannot rule out non-numbers for arguments of type `Any`,
+/// so methods with `Number` arguments must still check at runtime.
+fn expect_arg_number(arg: &CallArg<Value>) -> Result<&Decimal> {
+    match &arg.value {
+        Value::Number(d) => Ok(d),
+        not_number => arg
+            .span
+            .error(concat! {
+                "Expected a number, but got "
+                format_rcl(not_number).into_owned()
+                "."
+            })
+            .err(),
+    }
+}
+
+/// Shared implementation for `std.{md5,sha1,sha256}`.
+fn builtin_std_digest_impl(call: FunctionCall, digest: fn(&[u8]) -> Vec<u8>) -> Result<Value> {
+    let input = expect_arg_string(&call.args[0])?;
+    let hash = crate::encoding::hex_encode(&digest(input.as_bytes()));
+    Ok(Value::String(hash.into()))
+}
+
+builtin_function!("std.md5", (input: String) -> String, const STD_MD5, builtin_std_md5);
+fn builtin_std_md5(_eval: &mut Evaluator, call: FunctionCall) -> Result<Value> {
+    builtin_std_digest_impl(call, |bytes| md5(bytes).to_vec())
+}
+
+builtin_function!("std.sha1", (input: String) -> String, const STD_SHA1, builtin_std_sha1);
+fn builtin_std_sha1(_eval: &mut Evaluator, call: FunctionCall) -> Result<Value> {
+    builtin_std_digest_impl(call, |bytes| sha1(bytes).to_vec())
+}
+
+builtin_function!(
+    "std.sha256",
+    (input: String) -> String,
+    const STD_SHA256,
+    builtin_std_sha256
+);
+fn builtin_std_sha256(_eval: &mut Evaluator, call: FunctionCall) -> Result<Value> {
+    builtin_std_digest_impl(call, |bytes| sha256(bytes).to_vec())
+}
+
+/// Shared implementation for `std.{base64,hex,url}_encode`.
+fn builtin_std_encode_impl(call: FunctionCall, encode: fn(&[u8]) -> String) -> Result<Value> {
+    let input = expect_arg_string(&call.args[0])?;
+    Ok(Value::String(encode(input.as_bytes()).into()))
+}
+
+/// Shared implementation for `std.{base64,hex,url}_decode`.
+fn builtin_std_decode_impl(
+    eval: &Evaluator,
+    call: FunctionCall,
+    format_name: &'static str,
+    decode: fn(&str) -> crate::encoding::Result<Vec<u8>>,
+) -> Result<Value> {
+    let arg = &call.args[0];
+    let input = expect_arg_string(arg)?;
+    let bytes = match decode(input) {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            // Point at the offending character if we can locate it in the
+            // source. When the problem is at the end of the input, there is
+            // no character to point at.
+            let range = input[err.offset..]
+                .chars()
+                .next()
+                .map(|ch| err.offset..err.offset + ch.len_utf8());
+            let literal = arg.span.resolve(eval.loader.get_doc(arg.span.doc()).data);
+            let span = range
+                .clone()
+                .and_then(|r| locate_in_literal(literal, arg.span, input, r));
+            return match (span, range) {
+                (Some(span), _) => span.error(err.message).err(),
+                (None, Some(..)) => arg
+                    .span
+                    .error(err.message)
+                    .with_body(concat! {
+                        "At byte offset " err.offset.to_string() " in the input."
+                    })
+                    .err(),
+                (None, None) => arg.span.error(err.message).err(),
+            };
+        }
+    };
+    match String::from_utf8(bytes) {
+        Ok(s) => Ok(Value::String(s.into())),
+        Err(..) => arg
+            .span
+            .error(concat! { "The decoded " format_name " is not valid UTF-8." })
+            .with_help("Only strings can be decoded, RCL has no type for arbitrary bytes.")
+            .err(),
+    }
+}
+
+builtin_function!(
+    "std.base64_encode",
+    (input: String) -> String,
+    const STD_BASE64_ENCODE,
+    builtin_std_base64_encode
+);
+fn builtin_std_base64_encode(_eval: &mut Evaluator, call: FunctionCall) -> Result<Value> {
+    builtin_std_encode_impl(call, crate::encoding::base64_encode)
+}
+
+builtin_function!(
+    "std.base64_decode",
+    (input: String) -> String,
+    const STD_BASE64_DECODE,
+    builtin_std_base64_decode
+);
+fn builtin_std_base64_decode(eval: &mut Evaluator, call: FunctionCall) -> Result<Value> {
+    builtin_std_decode_impl(eval, call, "base64", crate::encoding::base64_decode)
+}
+
+builtin_function!(
+    "std.hex_encode",
+    (input: String) -> String,
+    const STD_HEX_ENCODE,
+    builtin_std_hex_encode
+);
+fn builtin_std_hex_encode(_eval: &mut Evaluator, call: FunctionCall) -> Result<Value> {
+    builtin_std_encode_impl(call, crate::encoding::hex_encode)
+}
+
+builtin_function!(
+    "std.hex_decode",
+    (input: String) -> String,
+    const STD_HEX_DECODE,
+    builtin_std_hex_decode
+);
+fn builtin_std_hex_decode(eval: &mut Evaluator, call: FunctionCall) -> Result<Value> {
+    builtin_std_decode_impl(eval, call, "hex", crate::encoding::hex_decode)
+}
+
+builtin_function!(
+    "std.url_encode",
+    (input: String) -> String,
+    const STD_URL_ENCODE,
+    builtin_std_url_encode
+);
+fn builtin_std_url_encode(_eval: &mut Evaluator, call: FunctionCall) -> Result<Value> {
+    builtin_std_encode_impl(call, crate::encoding::url_encode)
+}
+
+builtin_function!(
+    "std.url_decode",
+    (input: String) -> String,
+    const STD_URL_DECODE,
+    builtin_std_url_decode
+);
+fn builtin_std_url_decode(eval: &mut Evaluator, call: FunctionCall) -> Result<Value> {
+    builtin_std_decode_impl(eval, call, "url", crate::encoding::url_decode)
+}
+
+/// Report an error in an IP address or CIDR prefix argument.
+fn net_arg_error(eval: &Evaluator, arg: &CallArg<Value>, err: AddrError) -> Error {
+    match err.range.is_empty() {
+        true => arg.span.error(err.message),
+        false => string_arg_error(eval, arg, err.message, err.range),
+    }
+}
+
+/// Parse an argument that must be an IP address.
+fn parse_addr_arg(eval: &Evaluator, arg: &CallArg<Value>) -> Result<Addr> {
+    let input = expect_arg_string(arg)?;
+    Addr::parse(input).map_err(|err| net_arg_error(eval, arg, err).into())
+}
+
+/// Parse an argument that must be a CIDR prefix.
+fn parse_cidr_arg(eval: &Evaluator, arg: &CallArg<Value>) -> Result<Cidr> {
+    let input = expect_arg_string(arg)?;
+    Cidr::parse(input).map_err(|err| net_arg_error(eval, arg, err).into())
+}
+
+builtin_function!(
+    "std.parse_ip",
+    (address: String) -> String,
+    const STD_PARSE_IP,
+    builtin_std_parse_ip
+);
+fn builtin_std_parse_ip(eval: &mut Evaluator, call: FunctionCall) -> Result<Value> {
+    let addr = parse_addr_arg(eval, &call.args[0])?;
+    Ok(Value::String(addr.to_string().into()))
+}
+
+builtin_function!(
+    "std.cidr_subnet",
+    (prefix: String, newbits: Number, netnum: Number) -> String,
+    const STD_CIDR_SUBNET,
+    builtin_std_cidr_subnet
+);
+fn builtin_std_cidr_subnet(eval: &mut Evaluator, call: FunctionCall) -> Result<Value> {
+    let prefix = parse_cidr_arg(eval, &call.args[0])?;
+    let new_bits = expect_arg_count(&call.args[1], "newbits")?;
+    let netnum_arg = &call.args[2];
+    let netnum = expect_arg_integer(netnum_arg, "netnum")?;
+    if let Some(n) = netnum.as_ref().filter(|n| n.signum() < 0) {
+        return netnum_arg
+            .span
+            .error(concat! { "Expected netnum to be at least 0, but got " n.to_string() "." })
+            .err();
+    }
+
+    if new_bits > prefix.n_host_bits() as usize {
+        return call.args[1]
+            .span
+            .error(concat! {
+                "Cannot extend the /" prefix.prefix_len.to_string() " prefix by "
+                new_bits.to_string() " bits, an " prefix.network.family.name()
+                " prefix is at most " prefix.network.family.width().to_string()
+                " bits long."
+            })
+            .err();
+    }
+    // Numbers beyond u128 do not fit in any prefix, so they get the same error.
+    let subnet = netnum
+        .and_then(|n| n.to_u128())
+        .and_then(|n| prefix.subnet(new_bits as u32, n));
+    match subnet {
+        Some(subnet) => Ok(Value::String(subnet.to_string().into())),
+        None => netnum_arg
+            .span
+            .error(concat! {
+                "Network number " format_rcl(&netnum_arg.value).into_owned()
+                " does not fit in " new_bits.to_string() " bits."
+            })
+            .err(),
+    }
+}
+
+builtin_function!(
+    "std.cidr_host",
+    (prefix: String, hostnum: Number) -> String,
+    const STD_CIDR_HOST,
+    builtin_std_cidr_host
+);
+fn builtin_std_cidr_host(eval: &mut Evaluator, call: FunctionCall) -> Result<Value> {
+    let prefix = parse_cidr_arg(eval, &call.args[0])?;
+    let hostnum_arg = &call.args[1];
+    let hostnum = expect_arg_integer(hostnum_arg, "hostnum")?;
+    match hostnum
+        .and_then(|n| n.to_i128())
+        .and_then(|n| prefix.host(n))
+    {
+        Some(addr) => Ok(Value::String(addr.to_string().into())),
+        None => hostnum_arg
+            .span
+            .error(concat! {
+                "Host number " format_rcl(&hostnum_arg.value).into_owned() " does not fit in the "
+                prefix.n_host_bits().to_string() " host bits of "
+                prefix.to_string() "."
+            })
+            .err(),
+    }
+}
+
+builtin_function!(
+    "std.cidr_contains",
+    (prefix: String, address: String) -> Bool,
+    const STD_CIDR_CONTAINS,
+    builtin_std_cidr_contains
+);
+fn builtin_std_cidr_contains(eval: &mut Evaluator, call: FunctionCall) -> Result<Value> {
+    let prefix = parse_cidr_arg(eval, &call.args[0])?;
+    let address_arg = &call.args[1];
+    // The address can also be a prefix, then we check that it lies entirely
+    // inside the outer prefix.
+    let contains = match expect_arg_string(address_arg)?.contains('/') {
+        true => {
+            let inner = parse_cidr_arg(eval, address_arg)?;
+            inner.prefix_len >= prefix.prefix_len && prefix.contains(&inner.network)
+        }
+        false => prefix.contains(&parse_addr_arg(eval, address_arg)?),
+    };
+    Ok(Value::Bool(contains))
+}
+
 builtin_function!(
     "std.range",
     (lower: Number, upper: Number) -> [Number],
@@ -119,18 +770,49 @@ fn builtin_std_range(_eval: &mut Evaluator, call: FunctionCall) -> Result<Value>
 
 /// Initialize the standard library.
 pub fn initialize() -> Value {
-    let mut builtins: BTreeMap<Value, Value> = BTreeMap::new();
+    let mut builtins: OrderedMap<Value, Value> = OrderedMap::new();
 
-    builtins.insert("empty_set".into(), Value::Set(Rc::new(BTreeSet::new())));
+    builtins.insert(
+        "base64_decode".into(),
+        Value::BuiltinFunction(&STD_BASE64_DECODE),
+    );
+    builtins.insert(
+        "base64_encode".into(),
+        Value::BuiltinFunction(&STD_BASE64_ENCODE),
+    );
+    builtins.insert(
+        "cidr_contains".into(),
+        Value::BuiltinFunction(&STD_CIDR_CONTAINS),
+    );
+    builtins.insert("cidr_host".into(), Value::BuiltinFunction(&STD_CIDR_HOST));
+    builtins.insert(
+        "cidr_subnet".into(),
+        Value::BuiltinFunction(&STD_CIDR_SUBNET),
+    );
+    builtins.insert(
+        "dict_from_pairs".into(),
+        Value::BuiltinFunction(&STD_DICT_FROM_PAIRS),
+    );
+    builtins.insert("empty_set".into(), Value::Set(Rc::new(OrderedSet::new())));
+    builtins.insert("format".into(), Value::BuiltinFunction(&STD_FORMAT));
     builtins.insert(
         "format_json".into(),
         Value::BuiltinFunction(&STD_FORMAT_JSON),
     );
+    builtins.insert("hex_decode".into(), Value::BuiltinFunction(&STD_HEX_DECODE));
+    builtins.insert("hex_encode".into(), Value::BuiltinFunction(&STD_HEX_ENCODE));
+    builtins.insert("md5".into(), Value::BuiltinFunction(&STD_MD5));
+    builtins.insert("parse_csv".into(), Value::BuiltinFunction(&STD_PARSE_CSV));
+    builtins.insert("parse_ip".into(), Value::BuiltinFunction(&STD_PARSE_IP));
     builtins.insert("range".into(), Value::BuiltinFunction(&STD_RANGE));
     builtins.insert(
         "read_file_utf8".into(),
         Value::BuiltinFunction(&STD_READ_FILE_UTF8),
     );
+    builtins.insert("sha1".into(), Value::BuiltinFunction(&STD_SHA1));
+    builtins.insert("sha256".into(), Value::BuiltinFunction(&STD_SHA256));
+    builtins.insert("url_decode".into(), Value::BuiltinFunction(&STD_URL_DECODE));
+    builtins.insert("url_encode".into(), Value::BuiltinFunction(&STD_URL_ENCODE));
 
     Value::Dict(Rc::new(builtins))
 }
@@ -259,43 +941,547 @@ fn builtin_set_except(_eval: &mut Evaluator, call: MethodCall) -> Result<Value>
     Ok(Value::Set(Rc::new(result)))
 }
 
-/// Shared implementation for applying a `get_key` function to each element of a collection.
-fn builtin_loop_get_key_impl<'a, I: Iterator<Item = &'a Value>, F: FnMut(Value, &Value)>(
-    eval: &mut Evaluator,
-    call: MethodCall,
+/// Read an argument that must be a set, for the set algebra methods.
+fn expect_arg_set<'a>(
+    arg: &'a CallArg<Value>,
     name: &'static str,
-    elements: I,
-    mut handle_key_value: F,
-) -> Result<()> {
-    let get_key = &call.call.args[0].value;
-    let get_key_span = call.call.args[0].span;
+) -> Result<&'a OrderedSet<Value>> {
+    match &arg.value {
+        Value::Set(xs) => Ok(xs),
+        not_set => arg
+            .span
+            .error(concat! {
+                "Expected a set to " name ", but got "
+                format_rcl(not_set).into_owned()
+                "."
+            })
+            .with_help(concat! {
+                "Use '" Doc::highlight("to_set_dedup") "' to convert a list to a set."
+            })
+            .err(),
+    }
+}
 
-    for x in elements {
-        // The call that we construct here is internal, there is no span in the
-        // source code that we could point at. Point at the argument so we still
-        // have something to highlight.
-        let args = [CallArg {
-            span: get_key_span,
-            value: x.clone(),
-        }];
-        let call = FunctionCall {
-            call_open: get_key_span,
-            call_close: get_key_span,
-            args: &args,
-        };
-        let key = eval
-            .eval_call(get_key_span, get_key, call)
-            .map_err(|mut err| {
-                // If the call includes a call frame for this call, then replace
-                // it with a more descriptive message, since the span is a bit
-                // misleading.
-                err.replace_call_frame(
-                    get_key_span,
-                    concat! { "In internal call to key selector from '" Doc::highlight(name) "'." },
-                );
-                err
-            })?;
+builtin_method!(
+    "Set.intersect",
+    (other: {Any}) -> {Any},
+    const SET_INTERSECT,
+    builtin_set_intersect
+);
+fn builtin_set_intersect(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let set = call.receiver.expect_set();
+    let other = expect_arg_set(&call.call.args[0], "intersect with")?;
+    let result = set.iter().filter(|x| other.contains(x)).cloned().collect();
+    Ok(Value::Set(Rc::new(result)))
+}
+
+builtin_method!(
+    "Set.symmetric_difference",
+    (other: {Any}) -> {Any},
+    const SET_SYMMETRIC_DIFFERENCE,
+    builtin_set_symmetric_difference
+);
+fn builtin_set_symmetric_difference(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let set = call.receiver.expect_set();
+    let other = expect_arg_set(&call.call.args[0], "compare with")?;
+    let result = set
+        .iter()
+        .filter(|x| !other.contains(x))
+        .chain(other.iter().filter(|y| !set.contains(y)))
+        .cloned()
+        .collect();
+    Ok(Value::Set(Rc::new(result)))
+}
+
+builtin_method!(
+    "Set.is_subset_of",
+    (other: {Any}) -> Bool,
+    const SET_IS_SUBSET_OF,
+    builtin_set_is_subset_of
+);
+fn builtin_set_is_subset_of(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let set = call.receiver.expect_set();
+    let other = expect_arg_set(&call.call.args[0], "compare with")?;
+    Ok(Value::Bool(set.iter().all(|x| other.contains(x))))
+}
+
+builtin_method!(
+    "Set.is_superset_of",
+    (other: {Any}) -> Bool,
+    const SET_IS_SUPERSET_OF,
+    builtin_set_is_superset_of
+);
+fn builtin_set_is_superset_of(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let set = call.receiver.expect_set();
+    let other = expect_arg_set(&call.call.args[0], "compare with")?;
+    Ok(Value::Bool(other.iter().all(|y| set.contains(y))))
+}
+
+builtin_method!(
+    "Set.is_disjoint",
+    (other: {Any}) -> Bool,
+    const SET_IS_DISJOINT,
+    builtin_set_is_disjoint
+);
+fn builtin_set_is_disjoint(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let set = call.receiver.expect_set();
+    let other = expect_arg_set(&call.call.args[0], "compare with")?;
+    Ok(Value::Bool(!set.iter().any(|x| other.contains(x))))
+}
+
+/// Call a function argument of a method from inside the method.
+///
+/// The `fn_description` describes the role of the function in the error
+/// message, for example "predicate", and `name` is the name of the method.
+fn builtin_call_internal<const N: usize>(
+    eval: &mut Evaluator,
+    fn_arg: &CallArg<Value>,
+    fn_description: &'static str,
+    name: &'static str,
+    args: [Value; N],
+) -> Result<Value> {
+    let fn_span = fn_arg.span;
+
+    // The call that we construct here is internal, there is no span in the
+    // source code that we could point at. Point at the argument so we still
+    // have something to highlight.
+    let args = args.map(|value| CallArg {
+        span: fn_span,
+        value,
+    });
+    let call = FunctionCall {
+        call_open: fn_span,
+        call_close: fn_span,
+        args: &args,
+    };
+    eval.eval_call(fn_span, &fn_arg.value, call)
+        .map_err(|mut err| {
+            // If the call includes a call frame for this call, then replace
+            // it with a more descriptive message, since the span is a bit
+            // misleading.
+            err.replace_call_frame(
+                fn_span,
+                concat! { "In internal call to " fn_description " from '" Doc::highlight(name) "'." },
+            );
+            err
+        })
+}
+
+builtin_method!(
+    "Dict.map_values",
+    (map_value: (fn (value: Any) -> Any)) -> {Any: Any},
+    const DICT_MAP_VALUES,
+    builtin_dict_map_values
+);
+fn builtin_dict_map_values(eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let dict = call.receiver.expect_dict();
+    let mut result = OrderedMap::new();
+    for (k, v) in dict.iter() {
+        let mapped = builtin_call_internal(
+            eval,
+            &call.call.args[0],
+            "mapping function",
+            "Dict.map_values",
+            [v.clone()],
+        )?;
+        result.insert(k.clone(), mapped);
+    }
+    Ok(Value::Dict(Rc::new(result)))
+}
+
+builtin_method!(
+    "Dict.map_keys",
+    (map_key: (fn (key: Any) -> Any)) -> {Any: Any},
+    const DICT_MAP_KEYS,
+    builtin_dict_map_keys
+);
+fn builtin_dict_map_keys(eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let dict = call.receiver.expect_dict();
+    let map_key_arg = &call.call.args[0];
+    let mut result = OrderedMap::new();
+    for (k, v) in dict.iter() {
+        let mapped = builtin_call_internal(
+            eval,
+            map_key_arg,
+            "mapping function",
+            "Dict.map_keys",
+            [k.clone()],
+        )?;
+        if result.contains_key(&mapped) {
+            return map_key_arg
+                .span
+                .error(concat! {
+                    "Expected unique keys, but the mapping function returned a duplicate: "
+                    format_rcl(&mapped).into_owned()
+                })
+                .err();
+        }
+        result.insert(mapped, v.clone());
+    }
+    Ok(Value::Dict(Rc::new(result)))
+}
+
+builtin_method!(
+    "Dict.filter",
+    (predicate: (fn (key: Any, value: Any) -> Bool)) -> {Any: Any},
+    const DICT_FILTER,
+    builtin_dict_filter
+);
+fn builtin_dict_filter(eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let dict = call.receiver.expect_dict();
+    let predicate_arg = &call.call.args[0];
+    let mut result = OrderedMap::new();
+    for (k, v) in dict.iter() {
+        let keep = builtin_call_internal(
+            eval,
+            predicate_arg,
+            "predicate",
+            "Dict.filter",
+            [k.clone(), v.clone()],
+        )?;
+        if expect_predicate_result(predicate_arg.span, keep)? {
+            result.insert(k.clone(), v.clone());
+        }
+    }
+    Ok(Value::Dict(Rc::new(result)))
+}
+
+builtin_method!(
+    "Dict.to_list",
+    () -> [[Any]],
+    const DICT_TO_LIST,
+    builtin_dict_to_list
+);
+fn builtin_dict_to_list(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let dict = call.receiver.expect_dict();
+    let result = dict
+        .iter()
+        .map(|(k, v)| Value::List(Rc::new(vec![k.clone(), v.clone()])))
+        .collect();
+    Ok(Value::List(Rc::new(result)))
+}
+
+builtin_method!(
+    "Dict.invert",
+    () -> {Any: Any},
+    const DICT_INVERT,
+    builtin_dict_invert
+);
+fn builtin_dict_invert(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let dict = call.receiver.expect_dict();
+    let mut result = OrderedMap::new();
+    for (k, v) in dict.iter() {
+        if result.insert(v.clone(), k.clone()).is_some() {
+            return call
+                .receiver_span
+                .error(concat! {
+                    "Expected unique values to invert dict, but got a duplicate: "
+                    format_rcl(v).into_owned()
+                })
+                .with_help(concat! {
+                    "Use '" Doc::highlight("group_by") "' on '"
+                    Doc::highlight("to_list") "' to collect the keys per value."
+                })
+                .err();
+        }
+    }
+    Ok(Value::Dict(Rc::new(result)))
+}
+
+builtin_method!(
+    "Dict.only",
+    (keys: {Any}) -> {Any: Any},
+    const DICT_ONLY,
+    builtin_dict_only
+);
+fn builtin_dict_only(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let dict = call.receiver.expect_dict();
+    let keys_arg = &call.call.args[0];
+    let keys = expect_arg_set(keys_arg, "select keys")?;
+    let result = dict
+        .iter()
+        .filter(|(k, _v)| keys.contains(k))
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect();
+    Ok(Value::Dict(Rc::new(result)))
+}
+
+builtin_method!(
+    "Dict.merge_with",
+    (other: {Any: Any}, resolve: (fn (key: Any, left: Any, right: Any) -> Any)) -> {Any: Any},
+    const DICT_MERGE_WITH,
+    builtin_dict_merge_with
+);
+fn builtin_dict_merge_with(eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let dict = call.receiver.expect_dict();
+    let other = match &call.call.args[0].value {
+        Value::Dict(other) => other,
+        _ => {
+            return call.call.args[0]
+                .span
+                .error("Expected a dict to merge with.")
+                .err()
+        }
+    };
+    let mut result = dict.clone();
+    for (k, right) in other.iter() {
+        let merged = match dict.get(k) {
+            None => right.clone(),
+            Some(left) => builtin_call_internal(
+                eval,
+                &call.call.args[1],
+                "conflict resolver",
+                "Dict.merge_with",
+                [k.clone(), left.clone(), right.clone()],
+            )?,
+        };
+        result.insert(k.clone(), merged);
+    }
+    Ok(Value::Dict(Rc::new(result)))
+}
+
+/// How [`DeepMerge`] combines two lists at the same path.
+enum ListStrategy {
+    /// The list on the right replaces the list on the left.
+    Replace,
+    /// The result is the left list followed by the right list.
+    Append,
+    /// Elements are dicts, and elements with the same value for the key merge.
+    /// Lists without any dicts are replaced.
+    ByKey(Value),
+}
+
+/// Return the name of the type of a value, for use in error messages.
+fn value_type_name(v: &Value) -> &'static str {
+    match v {
+        Value::Null => "Null",
+        Value::Bool(..) => "Bool",
+        Value::Number(..) => "Number",
+        Value::String(..) => "String",
+        Value::List(..) => "List",
+        Value::Set(..) => "Set",
+        Value::Dict(..) => "Dict",
+        Value::Function(..) | Value::BuiltinFunction(..) | Value::BuiltinMethod(..) => "Function",
+    }
+}
+
+/// State for recursively merging two values, used by `Dict.merge_deep`.
+struct DeepMerge {
+    /// The span of the `other` argument, to report errors at.
+    span: Span,
+
+    /// How to merge lists.
+    lists: ListStrategy,
+
+    /// Where we currently are in the values being merged.
+    path: Vec<PathElement>,
+}
+
+impl DeepMerge {
+    fn error<T>(&mut self, message: Doc<'static>) -> Result<T> {
+        let path = std::mem::take(&mut self.path);
+        self.span.error(message).with_path(path).err()
+    }
+
+    fn merge(&mut self, left: &Value, right: &Value) -> Result<Value> {
+        match (left, right) {
+            (Value::Dict(xs), Value::Dict(ys)) => {
+                let mut result = xs.as_ref().clone();
+                for (k, y) in ys.iter() {
+                    let merged = match xs.get(k) {
+                        None => y.clone(),
+                        Some(x) => {
+                            self.path.push(PathElement::Key(k.clone()));
+                            let merged = self.merge(x, y)?;
+                            self.path.pop().expect("Push and pop are balanced.");
+                            merged
+                        }
+                    };
+                    result.insert(k.clone(), merged);
+                }
+                Ok(Value::Dict(Rc::new(result)))
+            }
+            (Value::List(xs), Value::List(ys)) => match &self.lists {
+                ListStrategy::Replace => Ok(right.clone()),
+                ListStrategy::Append => {
+                    let mut result = xs.to_vec();
+                    result.extend(ys.iter().cloned());
+                    Ok(Value::List(Rc::new(result)))
+                }
+                ListStrategy::ByKey(key) => {
+                    // Lists that do not contain any dicts, such as lists of
+                    // strings, have no keys to merge by, we replace those.
+                    let is_dict = |x: &Value| matches!(x, Value::Dict(..));
+                    if !xs.iter().chain(ys.iter()).any(is_dict) {
+                        return Ok(right.clone());
+                    }
+                    let key = key.clone();
+                    self.merge_lists_by_key(&key, xs, ys)
+                }
+            },
+            _ if value_type_name(left) != value_type_name(right) => self.error(concat! {
+                "Cannot merge a value of type "
+                value_type_name(left).format_type()
+                " with a value of type "
+                value_type_name(right).format_type()
+                "."
+            }),
+            _ => Ok(right.clone()),
+        }
+    }
+
+    /// Return the value of `key` in the list element `x`, or report an error.
+    fn element_key<'a>(&mut self, key: &Value, i: usize, x: &'a Value) -> Result<&'a Value> {
+        if let Value::Dict(kvs) = x {
+            if let Some(k) = kvs.get(key) {
+                return Ok(k);
+            }
+        }
+        self.path.push(PathElement::Index(i));
+        self.error(concat! {
+            "Expected list elements to be dicts with key "
+            format_rcl(key).into_owned()
+            ", but found "
+            format_rcl(x).into_owned()
+            "."
+        })
+    }
+
+    fn merge_lists_by_key(&mut self, key: &Value, xs: &[Value], ys: &[Value]) -> Result<Value> {
+        let mut result = xs.to_vec();
+        let mut positions = OrderedMap::new();
+        for (i, x) in xs.iter().enumerate() {
+            let k = self.element_key(key, i, x)?;
+            if !positions.contains_key(k) {
+                positions.insert(k.clone(), i);
+            }
+        }
+        for (j, y) in ys.iter().enumerate() {
+            let k = self.element_key(key, j, y)?;
+            match positions.get(k) {
+                Some(&i) => {
+                    self.path.push(PathElement::Index(i));
+                    let x = result[i].clone();
+                    result[i] = self.merge(&x, y)?;
+                    self.path.pop().expect("Push and pop are balanced.");
+                }
+                None => {
+                    positions.insert(k.clone(), result.len());
+                    result.push(y.clone());
+                }
+            }
+        }
+        Ok(Value::List(Rc::new(result)))
+    }
+}
+
+/// Parse the options of `Dict.merge_deep_with`.
+fn parse_merge_deep_options(options_arg: &CallArg<Value>) -> Result<ListStrategy> {
+    let options = match &options_arg.value {
+        Value::Dict(d) => d,
+        _ => {
+            return options_arg
+                .span
+                .error("Expected a Dict of options here.")
+                .err()
+        }
+    };
+
+    let mut lists = "replace";
+    let mut key: Option<&Value> = None;
+
+    for (k, v) in options.iter() {
+        let option_name = match k {
+            Value::String(s) => s.as_ref(),
+            _ => "",
+        };
+        match option_name {
+            "lists" => {
+                match v {
+                    Value::String(s) if matches!(s.as_ref(), "replace" | "append" | "by_key") => {
+                        lists = s.as_ref()
+                    }
+                    _ => return options_arg
+                        .span
+                        .error(concat! { "Invalid list strategy: " format_rcl(v).into_owned() "." })
+                        .with_help(
+                            "The supported strategies are 'replace', 'append', and 'by_key'.",
+                        )
+                        .err(),
+                }
+            }
+            "key" => key = Some(v),
+            _ => {
+                return options_arg
+                    .span
+                    .error(concat! { "Unknown option: " format_rcl(k).into_owned() "." })
+                    .with_help("The supported options are 'lists' and 'key'.")
+                    .err()
+            }
+        }
+    }
+
+    match (lists, key) {
+        ("by_key", Some(key)) => Ok(ListStrategy::ByKey(key.clone())),
+        ("by_key", None) => options_arg
+            .span
+            .error("Option 'key' is required when 'lists' is \"by_key\".")
+            .err(),
+        (_, Some(..)) => options_arg
+            .span
+            .error("Option 'key' can only be used when 'lists' is \"by_key\".")
+            .err(),
+        ("append", None) => Ok(ListStrategy::Append),
+        _ => Ok(ListStrategy::Replace),
+    }
+}
+
+/// Shared implementation for `Dict.{merge_deep,merge_deep_with}`.
+fn builtin_dict_merge_deep_impl(call: MethodCall, lists: ListStrategy) -> Result<Value> {
+    let other_arg = &call.call.args[0];
+    if !matches!(other_arg.value, Value::Dict(..)) {
+        return other_arg.span.error("Expected a dict to merge with.").err();
+    }
+    let mut merger = DeepMerge {
+        span: other_arg.span,
+        lists,
+        path: Vec::new(),
+    };
+    merger.merge(call.receiver, &other_arg.value)
+}
+
+builtin_method!(
+    "Dict.merge_deep",
+    (other: {Any: Any}) -> {Any: Any},
+    const DICT_MERGE_DEEP,
+    builtin_dict_merge_deep
+);
+fn builtin_dict_merge_deep(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    builtin_dict_merge_deep_impl(call, ListStrategy::Replace)
+}
+
+builtin_method!(
+    "Dict.merge_deep_with",
+    (other: {Any: Any}, options: {String: Any}) -> {Any: Any},
+    const DICT_MERGE_DEEP_WITH,
+    builtin_dict_merge_deep_with
+);
+fn builtin_dict_merge_deep_with(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let lists = parse_merge_deep_options(&call.call.args[1])?;
+    builtin_dict_merge_deep_impl(call, lists)
+}
 
+/// Shared implementation for applying a `get_key` function to each element of a collection.
+fn builtin_loop_get_key_impl<'a, I: Iterator<Item = &'a Value>, F: FnMut(Value, &Value)>(
+    eval: &mut Evaluator,
+    call: MethodCall,
+    name: &'static str,
+    elements: I,
+    mut handle_key_value: F,
+) -> Result<()> {
+    for x in elements {
+        let key =
+            builtin_call_internal(eval, &call.call.args[0], "key selector", name, [x.clone()])?;
         handle_key_value(key, x);
     }
 
@@ -307,12 +1493,19 @@ fn builtin_group_by_impl<'a, I: IntoIterator<Item = &'a Value>>(
     call: MethodCall,
     name: &'static str,
     elements: I,
-) -> Result<BTreeMap<Value, Vec<Value>>> {
-    let mut groups: BTreeMap<Value, Vec<Value>> = BTreeMap::new();
+) -> Result<OrderedMap<Value, Vec<Value>>> {
+    let mut groups: OrderedMap<Value, Vec<Value>> = OrderedMap::new();
 
-    builtin_loop_get_key_impl(eval, call, name, elements.into_iter(), |key, value| {
-        groups.entry(key).or_default().push(value.clone());
-    })?;
+    builtin_loop_get_key_impl(
+        eval,
+        call,
+        name,
+        elements.into_iter(),
+        |key, value| match groups.get_mut(&key) {
+            Some(group) => group.push(value.clone()),
+            None => _ = groups.insert(key, vec![value.clone()]),
+        },
+    )?;
 
     Ok(groups)
 }
@@ -357,7 +1550,7 @@ fn builtin_key_by_impl<'a, I: IntoIterator<Item = &'a Value>>(
 ) -> Result<Value> {
     let method_span = call.method_span;
     let groups = builtin_group_by_impl(eval, call, name, elements)?;
-    let mut result = BTreeMap::new();
+    let mut result = OrderedMap::new();
     for (k, mut vs) in groups.into_iter() {
         if vs.len() > 1 {
             return method_span
@@ -479,9 +1672,7 @@ builtin_method!(
 );
 fn builtin_set_sort(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
     let set = call.receiver.expect_set();
-    // Note, sets are currently implemented as sorted sets, so there is
-    // no additional sorting to be done here, just collecting in a vec.
-    let sorted: Vec<_> = set.iter().cloned().collect();
+    let sorted: Vec<_> = set.iter_sorted().cloned().collect();
     Ok(Value::List(Rc::new(sorted)))
 }
 
@@ -500,34 +1691,9 @@ fn builtin_generic_map_impl<
     elements: I,
     mut accept: F,
 ) -> Result<()> {
-    let map_element = &call.call.args[0].value;
-    let map_element_span = call.call.args[0].span;
-
     for x in elements {
-        // The call that we construct here is internal, there is no span in the
-        // source code that we could point at. Point at the argument so we still
-        // have something to highlight.
-        let args = [CallArg {
-            span: map_element_span,
-            value: x.clone(),
-        }];
-        let call = FunctionCall {
-            call_open: map_element_span,
-            call_close: map_element_span,
-            args: &args,
-        };
-        let mapped_value = eval
-            .eval_call(map_element_span, map_element, call)
-            .map_err(|mut err| {
-                // If the call includes a call frame for this call, then replace
-                // it with a more descriptive message, since the span is a bit
-                // misleading.
-                err.replace_call_frame(
-                    map_element_span,
-                    concat! { "In internal call to " fn_description " from '" Doc::highlight(name) "'." },
-                );
-                err
-            })?;
+        let mapped_value =
+            builtin_call_internal(eval, &call.call.args[0], fn_description, name, [x.clone()])?;
         accept(x, mapped_value)?;
     }
     Ok(())
@@ -562,26 +1728,30 @@ fn builtin_filter_impl<'a, I: IntoIterator<Item = &'a Value>, F: FnMut(Value)>(
 ) -> Result<()> {
     let predicate_span = call.call.args[0].span;
     builtin_generic_map_impl(eval, call, "predicate", name, elements, |orig, result| {
-        match result {
-            Value::Bool(true) => accept(orig.clone()),
-            Value::Bool(false) => {}
-            not_bool => {
-                return predicate_span
-                    .error("Type mismatch.")
-                    .with_body(concat! {
-                        "Expected the predicate to return "
-                        "Bool".format_type()
-                        ", but it returned "
-                        format_rcl(&not_bool).into_owned()
-                        "."
-                    })
-                    .err();
-            }
+        if expect_predicate_result(predicate_span, result)? {
+            accept(orig.clone());
         }
         Ok(())
     })
 }
 
+/// Check that a predicate called through [`builtin_generic_map_impl`] returned a bool.
+fn expect_predicate_result(predicate_span: Span, result: Value) -> Result<bool> {
+    match result {
+        Value::Bool(b) => Ok(b),
+        not_bool => predicate_span
+            .error("Type mismatch.")
+            .with_body(concat! {
+                "Expected the predicate to return "
+                "Bool".format_type()
+                ", but it returned "
+                format_rcl(&not_bool).into_owned()
+                "."
+            })
+            .err(),
+    }
+}
+
 fn builtin_flat_map_impl<'a, I: IntoIterator<Item = &'a Value>, F: FnMut(Value)>(
     eval: &mut Evaluator,
     call: MethodCall,
@@ -675,7 +1845,7 @@ builtin_method!(
 );
 fn builtin_set_map_dedup(eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
     let set = call.receiver.expect_set();
-    let mut result = BTreeSet::new();
+    let mut result = OrderedSet::new();
     builtin_map_impl(eval, call, "Set.map_dedup", set, |v| {
         result.insert(v);
     })?;
@@ -691,7 +1861,7 @@ builtin_method!(
 );
 fn builtin_set_flat_map_dedup(eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
     let set = call.receiver.expect_set();
-    let mut result = BTreeSet::new();
+    let mut result = OrderedSet::new();
     builtin_flat_map_impl(eval, call, "Set.flat_map_dedup", set, |v| {
         result.insert(v);
     })?;
@@ -707,7 +1877,7 @@ builtin_method!(
 );
 fn builtin_set_filter(eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
     let set = call.receiver.expect_set();
-    let mut result = BTreeSet::new();
+    let mut result = OrderedSet::new();
     builtin_filter_impl(eval, call, "Set.filter", set, |v| {
         result.insert(v);
     })?;
@@ -735,7 +1905,7 @@ builtin_method!(
 );
 fn builtin_list_to_set_unique(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
     let list = call.receiver.expect_list();
-    let mut result = BTreeSet::new();
+    let mut result = OrderedSet::new();
 
     for elem in list.iter() {
         let is_new = result.insert(elem.clone());
@@ -764,7 +1934,7 @@ builtin_method!(
 );
 fn builtin_list_to_set_dedup(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
     let list = call.receiver.expect_list();
-    let mut result = BTreeSet::new();
+    let mut result = OrderedSet::new();
     for elem in list.iter() {
         result.insert(elem.clone());
     }
@@ -777,7 +1947,7 @@ fn builtin_list_to_set_dedup(_eval: &mut Evaluator, call: MethodCall) -> Result<
 /// closure, can generate values that have a "size" that grows at runtime,
 /// beyond anything proportional to the input. E.g. transitive closure with
 /// expansion `x => [[x]]`. Those deeply nested objects are problematic to put
-/// in a `BTreeSet`, because the `Ord` implementation has to descend all the
+/// in a set, because the `Ord` implementation has to descend all the
 /// way into the value, and for larger sets like what transitive closure builds,
 /// this slows everything to a crawl. The fuzzer *will* find pathological cases
 /// like this and hang on them, so we ban them.
@@ -804,57 +1974,43 @@ builtin_method!(
     builtin_set_transitive_closure
 );
 fn builtin_set_transitive_closure(eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
-    let mut closed = BTreeSet::new();
-    let mut frontier = call.receiver.expect_set().clone();
+    // We expand elements in breadth-first order, so the result lists the
+    // elements of the input first, then their direct expansions, etc.
+    let mut closed = OrderedSet::new();
+    let mut frontier: VecDeque<Value> = call.receiver.expect_set().iter().cloned().collect();
 
-    let expand = &call.call.args[0].value;
     let expand_span = call.call.args[0].span;
     let method_span = call.method_span;
 
-    while let Some(elem) = frontier.pop_first() {
+    while let Some(elem) = frontier.pop_front() {
+        // The frontier may contain duplicates, we only expand every element once.
+        if closed.contains(&elem) {
+            continue;
+        }
+
         // Prevent hangs from deeply nested values that are expensive to put in
-        // the BTreeSet.
+        // the set.
         check_value_depth(method_span, "A value in the transitive closure", &elem)?;
 
-        // See also the comments in `builtin_generic_map_impl` for how we handle
-        // spans of internal calls.
-        let args = [CallArg {
-            span: expand_span,
-            value: elem.clone(),
-        }];
-        let call = FunctionCall {
-            call_open: expand_span,
-            call_close: expand_span,
-            args: &args,
-        };
-        let expansion = eval
-            .eval_call(expand_span, expand, call)
-            .map_err(|mut err| {
-                // If the call includes a call frame for this call, then replace
-                // it with a more descriptive message, since the span is a bit
-                // misleading.
-                err.replace_call_frame(
-                    expand_span,
-                    concat! {
-                        "In internal call to the expansion function from '"
-                        Doc::highlight("transitive_closure")
-                        "'."
-                    },
-                );
-                err
-            })?;
+        let expansion = builtin_call_internal(
+            eval,
+            &call.call.args[0],
+            "the expansion function",
+            "transitive_closure",
+            [elem.clone()],
+        )?;
 
         closed.insert(elem);
 
         match expansion {
             Value::List(xs) => xs.iter().for_each(|x| {
                 if !closed.contains(x) {
-                    frontier.insert(x.clone());
+                    frontier.push_back(x.clone());
                 }
             }),
             Value::Set(xs) => xs.iter().for_each(|x| {
                 if !closed.contains(x) {
-                    frontier.insert(x.clone());
+                    frontier.push_back(x.clone());
                 }
             }),
             not_collection => {
@@ -913,6 +2069,98 @@ fn builtin_set_sum(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
     builtin_sum_impl(call, set)
 }
 
+/// Which statistic to compute in [`builtin_statistic_impl`].
+enum Statistic {
+    Average,
+    Max,
+    Min,
+}
+
+/// Shared implementation for `{List,Set}.{average,max,min}`.
+fn builtin_statistic_impl<'a>(
+    call: MethodCall,
+    xs: impl IntoIterator<Item = &'a Value>,
+    statistic: Statistic,
+) -> Result<Value> {
+    let mut numbers = Vec::new();
+    for x in xs {
+        match x {
+            Value::Number(n) => numbers.push(n),
+            not_number => {
+                let err = concat! {
+                    "Expected numbers, but found " format_rcl(not_number).into_owned() "."
+                };
+                return call.receiver_span.error(err).err();
+            }
+        }
+    }
+
+    if numbers.is_empty() {
+        let err = match statistic {
+            Statistic::Average => "Cannot take the average of an empty collection.",
+            Statistic::Max => "Cannot take the maximum of an empty collection.",
+            Statistic::Min => "Cannot take the minimum of an empty collection.",
+        };
+        return call.method_span.error(err).err();
+    }
+
+    let result = match statistic {
+        Statistic::Max => numbers.iter().max().expect("Checked non-empty above."),
+        Statistic::Min => numbers.iter().min().expect("Checked non-empty above."),
+        Statistic::Average => {
+            let n = Decimal::from(numbers.len() as i64);
+            let mean = numbers
+                .iter()
+                .try_fold(Decimal::from(0), |acc, x| acc.checked_add(x))
+                .and_then(|sum| sum.checked_div_rounded(&n, NUM_SIGNIFICANT_DIGITS));
+            return match mean {
+                Some(d) => Ok(Value::Number(d)),
+                None => call
+                    .method_span
+                    .error("Overflow while computing average.")
+                    .err(),
+            };
+        }
+    };
+    Ok(Value::Number((*result).clone()))
+}
+
+builtin_method!("List.average", () -> Number, const LIST_AVERAGE, builtin_list_average);
+fn builtin_list_average(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let list = call.receiver.expect_list();
+    builtin_statistic_impl(call, list, Statistic::Average)
+}
+
+builtin_method!("List.max", () -> Number, const LIST_MAX, builtin_list_max);
+fn builtin_list_max(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let list = call.receiver.expect_list();
+    builtin_statistic_impl(call, list, Statistic::Max)
+}
+
+builtin_method!("List.min", () -> Number, const LIST_MIN, builtin_list_min);
+fn builtin_list_min(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let list = call.receiver.expect_list();
+    builtin_statistic_impl(call, list, Statistic::Min)
+}
+
+builtin_method!("Set.average", () -> Number, const SET_AVERAGE, builtin_set_average);
+fn builtin_set_average(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let set = call.receiver.expect_set();
+    builtin_statistic_impl(call, set, Statistic::Average)
+}
+
+builtin_method!("Set.max", () -> Number, const SET_MAX, builtin_set_max);
+fn builtin_set_max(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let set = call.receiver.expect_set();
+    builtin_statistic_impl(call, set, Statistic::Max)
+}
+
+builtin_method!("Set.min", () -> Number, const SET_MIN, builtin_set_min);
+fn builtin_set_min(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let set = call.receiver.expect_set();
+    builtin_statistic_impl(call, set, Statistic::Min)
+}
+
 builtin_method!(
     "Number.round",
     (n_decimals: Number) -> Number,
@@ -952,12 +2200,252 @@ fn builtin_number_round(_eval: &mut Evaluator, call: MethodCall) -> Result<Value
     }
 }
 
-/// Which function to implement in [`builtin_any_all_impl`].
-enum AllAny {
+/// Which result to return from [`builtin_number_division_impl`].
+enum Division {
+    /// The quotient, rounded towards negative infinity.
+    Div,
+    /// The remainder of the division that rounds towards negative infinity.
+    Mod,
+    /// The remainder of the division that rounds towards zero.
+    Rem,
+}
+
+/// Shared implementation for `Number.{div,mod,rem}`.
+fn builtin_number_division_impl(call: MethodCall, division: Division) -> Result<Value> {
+    let x = call.receiver.expect_number();
+    let arg = &call.call.args[0];
+    let y = expect_arg_number(arg)?;
+
+    if y.is_zero() {
+        return arg.span.error("Division by zero.").err();
+    }
+
+    let result = match division {
+        Division::Div => x.checked_div_mod(y).map(|(q, _)| q),
+        Division::Mod => x.checked_div_mod(y).map(|(_, r)| r),
+        Division::Rem => x.checked_div_rem(y).map(|(_, r)| r),
+    };
+    match result {
+        Some(d) => Ok(Value::Number(d)),
+        None => call
+            .method_span
+            .error("Overflow while dividing number.")
+            .err(),
+    }
+}
+
+builtin_method!(
+    "Number.div",
+    (divisor: Number) -> Number,
+    const NUMBER_DIV,
+    builtin_number_div
+);
+fn builtin_number_div(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    builtin_number_division_impl(call, Division::Div)
+}
+
+builtin_method!(
+    "Number.mod",
+    (divisor: Number) -> Number,
+    const NUMBER_MOD,
+    builtin_number_mod
+);
+fn builtin_number_mod(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    builtin_number_division_impl(call, Division::Mod)
+}
+
+builtin_method!(
+    "Number.rem",
+    (divisor: Number) -> Number,
+    const NUMBER_REM,
+    builtin_number_rem
+);
+fn builtin_number_rem(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    builtin_number_division_impl(call, Division::Rem)
+}
+
+builtin_method!(
+    "Number.pow",
+    (exponent: Number) -> Number,
+    const NUMBER_POW,
+    builtin_number_pow
+);
+fn builtin_number_pow(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let arg = &call.call.args[0];
+    let exponent = expect_arg_i64(arg, "exponent")?;
+
+    if exponent < 0 {
+        return arg
+            .span
+            .error("Exponent must be at least 0.")
+            .with_help("To raise to a negative power 'n', divide 1 by the number raised to '-n'.")
+            .err();
+    }
+
+    match call.receiver.expect_number().checked_pow(exponent as u64) {
+        Some(d) => Ok(Value::Number(d)),
+        None => call
+            .method_span
+            .error("Overflow while raising number to a power.")
+            .err(),
+    }
+}
+
+/// The number of significant digits for results that cannot be exact.
+///
+/// This applies to `Number.sqrt` and `{List,Set}.average`. When the result can
+/// be represented exactly as a decimal number, we return it exactly.
+const NUM_SIGNIFICANT_DIGITS: u32 = 20;
+
+/// The number of significant digits for `Number.log2`.
+///
+/// We compute the logarithm through a float, which has about 16 significant
+/// digits. We round to fewer digits, so the result does not depend on the
+/// last bits of the float.
+const LOG2_SIGNIFICANT_DIGITS: u32 = 14;
+
+builtin_method!("Number.abs", () -> Number, const NUMBER_ABS, builtin_number_abs);
+fn builtin_number_abs(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    Ok(Value::Number(call.receiver.expect_number().abs()))
+}
+
+builtin_method!("Number.ceil", () -> Number, const NUMBER_CEIL, builtin_number_ceil);
+fn builtin_number_ceil(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    match call.receiver.expect_number().ceil() {
+        Some(d) => Ok(Value::Number(d)),
+        None => call
+            .method_span
+            .error("Overflow while rounding number.")
+            .err(),
+    }
+}
+
+builtin_method!("Number.floor", () -> Number, const NUMBER_FLOOR, builtin_number_floor);
+fn builtin_number_floor(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    match call.receiver.expect_number().floor() {
+        Some(d) => Ok(Value::Number(d)),
+        None => call
+            .method_span
+            .error("Overflow while rounding number.")
+            .err(),
+    }
+}
+
+builtin_method!(
+    "Number.clamp",
+    (lower: Number, upper: Number) -> Number,
+    const NUMBER_CLAMP,
+    builtin_number_clamp
+);
+fn builtin_number_clamp(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let x = call.receiver.expect_number();
+    let lower = expect_arg_number(&call.call.args[0])?;
+    let upper = expect_arg_number(&call.call.args[1])?;
+
+    if lower > upper {
+        return call.call.args[1]
+            .span
+            .error(concat! {
+                "Upper bound " upper.format() " is less than lower bound " lower.format() "."
+            })
+            .err();
+    }
+
+    Ok(Value::Number(x.clone().clamp(lower.clone(), upper.clone())))
+}
+
+builtin_method!("Number.max", (other: Number) -> Number, const NUMBER_MAX, builtin_number_max);
+fn builtin_number_max(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let x = call.receiver.expect_number();
+    let y = expect_arg_number(&call.call.args[0])?;
+    // On a tie, return the receiver, unlike `Ord::max`.
+    Ok(Value::Number(if y > x { y.clone() } else { x.clone() }))
+}
+
+builtin_method!("Number.min", (other: Number) -> Number, const NUMBER_MIN, builtin_number_min);
+fn builtin_number_min(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let x = call.receiver.expect_number();
+    let y = expect_arg_number(&call.call.args[0])?;
+    Ok(Value::Number(if y < x { y.clone() } else { x.clone() }))
+}
+
+builtin_method!("Number.sqrt", () -> Number, const NUMBER_SQRT, builtin_number_sqrt);
+fn builtin_number_sqrt(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let x = call.receiver.expect_number();
+
+    if x.signum() < 0 {
+        return call
+            .receiver_span
+            .error(concat! {
+                "Cannot take the square root of negative number " x.format() "."
+            })
+            .err();
+    }
+
+    match x.sqrt(NUM_SIGNIFICANT_DIGITS) {
+        Some(d) => Ok(Value::Number(d)),
+        None => call
+            .method_span
+            .error("Overflow while taking square root.")
+            .err(),
+    }
+}
+
+builtin_method!("Number.log2", () -> Number, const NUMBER_LOG2, builtin_number_log2);
+fn builtin_number_log2(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let x = call.receiver.expect_number();
+
+    if x.signum() <= 0 {
+        return call
+            .receiver_span
+            .error(concat! {
+                "Logarithm is only defined for positive numbers, but got " x.format() "."
+            })
+            .err();
+    }
+
+    match x.log2(LOG2_SIGNIFICANT_DIGITS) {
+        Some(d) => Ok(Value::Number(d)),
+        None => call
+            .method_span
+            .error("Overflow while taking logarithm.")
+            .err(),
+    }
+}
+
+/// Which function to implement in [`builtin_any_all_impl`].
+enum AllAny {
     All,
     Any,
 }
 
+/// Call the predicate argument of a method on one element.
+///
+/// This is used by methods that may not need to call the predicate on every
+/// element, such as `List.any`.
+fn builtin_call_predicate(
+    eval: &mut Evaluator,
+    predicate_arg: &CallArg<Value>,
+    name: &'static str,
+    x: &Value,
+) -> Result<bool> {
+    let result = builtin_call_internal(eval, predicate_arg, "predicate", name, [x.clone()])?;
+    match result {
+        Value::Bool(b) => Ok(b),
+        not_bool => predicate_arg
+            .span
+            .error("Type mismatch.")
+            .with_body(concat! {
+                "Expected predicate for '" Doc::highlight(name) "' to return "
+                "Bool".format_type()
+                ", but it returned "
+                format_rcl(&not_bool).into_owned()
+                "."
+            })
+            .err(),
+    }
+}
+
 /// Shared implementation for `{List,Set}.{any,all}`.
 #[inline(always)]
 fn builtin_all_any_impl<'a>(
@@ -967,55 +2455,16 @@ fn builtin_all_any_impl<'a>(
     mode: AllAny,
     xs: impl IntoIterator<Item = &'a Value>,
 ) -> Result<bool> {
-    let predicate = &call.call.args[0].value;
-    let predicate_span = call.call.args[0].span;
-
     for x in xs {
-        // The call that we construct here is internal, there is no span in the
-        // source code that we could point at. Point at the argument so we still
-        // have something to highlight.
-        let args = [CallArg {
-            span: predicate_span,
-            value: x.clone(),
-        }];
-        let call = FunctionCall {
-            call_open: predicate_span,
-            call_close: predicate_span,
-            args: &args,
-        };
-        let result = eval
-            .eval_call(predicate_span, predicate, call)
-            .map_err(|mut err| {
-                // If the call includes a call frame for this call, then replace
-                // it with a more descriptive message, since the span is a bit
-                // misleading.
-                err.replace_call_frame(
-                    predicate_span,
-                    concat! { "In internal call to predicate from '" Doc::highlight(name) "'." },
-                );
-                err
-            })?;
-        match result {
-            Value::Bool(true) => match mode {
+        match builtin_call_predicate(eval, &call.call.args[0], name, x)? {
+            true => match mode {
                 AllAny::All => continue,
                 AllAny::Any => return Ok(true),
             },
-            Value::Bool(false) => match mode {
+            false => match mode {
                 AllAny::All => return Ok(false),
                 AllAny::Any => continue,
             },
-            not_bool => {
-                return predicate_span
-                    .error("Type mismatch.")
-                    .with_body(concat! {
-                        "Expected predicate for '" Doc::highlight(name) "' to return "
-                        "Bool".format_type()
-                        ", but it returned "
-                        format_rcl(&not_bool).into_owned()
-                        "."
-                    })
-                    .err();
-            }
         }
     }
     match mode {
@@ -1137,13 +2586,21 @@ builtin_method!(
     builtin_string_parse_int
 );
 fn builtin_string_parse_int(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
-    use std::str::FromStr;
-
     let string = call.receiver.expect_string();
 
-    match i64::from_str(string) {
-        Ok(i) => Ok(Value::int(i)),
-        Err(..) => call
+    let (is_negative, digits) = match string.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, string.strip_prefix('+').unwrap_or(string)),
+    };
+
+    match Decimal::from_str_radix(digits, 10) {
+        Some(mut d) => {
+            if is_negative {
+                d.mantissa = d.mantissa.neg();
+            }
+            Ok(Value::Number(d))
+        }
+        None => call
             .receiver_span
             .error("Failed to parse as integer:")
             .with_body(format_rcl(call.receiver).into_owned())
@@ -1204,11 +2661,11 @@ fn builtin_string_parse_number(_eval: &mut Evaluator, call: MethodCall) -> Resul
             Some(Token::NumBinary) => {
                 // Remove the "0b" prefix, strip numeric underscores.
                 let num_str = str[2..].replace('_', "");
-                i64::from_str_radix(&num_str, 2).ok().map(Decimal::from)
+                Decimal::from_str_radix(&num_str, 2)
             }
             Some(Token::NumHexadecimal) => {
                 let num_str = str[2..].replace('_', "");
-                i64::from_str_radix(&num_str, 16).ok().map(Decimal::from)
+                Decimal::from_str_radix(&num_str, 16)
             }
             Some(Token::NumDecimal) => Decimal::parse_str(str).map(Decimal::from),
             _ => break "Failed to parse as number:",
@@ -1222,10 +2679,11 @@ fn builtin_string_parse_number(_eval: &mut Evaluator, call: MethodCall) -> Resul
             None => break "Overflow while parsing number:",
         };
 
-        // If there was a leading `-`, flip the sign. This does not overflow,
-        // because abs(i64::MIN) > abs(i64::MAX). It does mean that we can't
-        // parse i64::MIN itself, but I think that's acceptable for now.
-        result.mantissa *= sign;
+        // If there was a leading `-`, flip the sign. The mantissa can grow
+        // beyond an i64, so this does not overflow.
+        if sign < 0 {
+            result.mantissa = result.mantissa.neg();
+        }
 
         return Ok(Value::Number(result));
     };
@@ -1335,6 +2793,146 @@ fn builtin_string_replace(_eval: &mut Evaluator, call: MethodCall) -> Result<Val
     Ok(Value::String(string.replace(needle, replacement).into()))
 }
 
+/// Report an error about a byte range in a string argument.
+///
+/// When the argument is a string literal, the error points at the offending
+/// part inside the literal, otherwise at the argument as a whole.
+fn string_arg_error(
+    eval: &Evaluator,
+    arg: &CallArg<Value>,
+    message: &'static str,
+    range: Range<usize>,
+) -> Error {
+    let text = match &arg.value {
+        Value::String(s) => s.as_ref(),
+        _ => unreachable!("Only string arguments have byte ranges."),
+    };
+    let literal = arg.span.resolve(eval.loader.get_doc(arg.span.doc()).data);
+    match locate_in_literal(literal, arg.span, text, range.clone()) {
+        Some(span) => span.error(message),
+        None => arg.span.error(message).with_body(concat! {
+            "At byte offset " range.start.to_string() " in "
+            format_rcl(&arg.value).into_owned() "."
+        }),
+    }
+}
+
+/// Report an error in a pattern or replacement string argument.
+fn regex_arg_error(eval: &Evaluator, arg: &CallArg<Value>, err: PatternError) -> Error {
+    string_arg_error(eval, arg, err.message, err.range)
+}
+
+/// Compile the pattern argument of a regex method.
+fn compile_regex_arg(eval: &Evaluator, arg: &CallArg<Value>) -> Result<Regex> {
+    let pattern = match &arg.value {
+        Value::String(s) => s.as_ref(),
+        _ => return arg.span.error("Pattern must be a string.").err(),
+    };
+    Regex::new(pattern).map_err(|err| regex_arg_error(eval, arg, err).into())
+}
+
+/// Extract the matched substrings from a match, with null for missing groups.
+fn regex_captures_value(string: &str, captures: Vec<Option<Range<usize>>>) -> Value {
+    let groups = captures
+        .into_iter()
+        .map(|group| match group {
+            Some(range) => Value::from(&string[range]),
+            None => Value::Null,
+        })
+        .collect();
+    Value::List(Rc::new(groups))
+}
+
+builtin_method!(
+    "String.matches",
+    (pattern: String) -> Bool,
+    const STRING_MATCHES,
+    builtin_string_matches
+);
+fn builtin_string_matches(eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let string = call.receiver.expect_string();
+    let regex = compile_regex_arg(eval, &call.call.args[0])?;
+    Ok(Value::Bool(regex.is_match(string)))
+}
+
+builtin_method!(
+    "String.find_all",
+    (pattern: String) -> [String],
+    const STRING_FIND_ALL,
+    builtin_string_find_all
+);
+fn builtin_string_find_all(eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let string = call.receiver.expect_string();
+    let regex = compile_regex_arg(eval, &call.call.args[0])?;
+    let result = regex
+        .captures_all(string)
+        .into_iter()
+        .map(|captures| {
+            let range = captures[0].clone().expect("Group 0 always participates.");
+            Value::from(&string[range])
+        })
+        .collect();
+    Ok(Value::List(Rc::new(result)))
+}
+
+builtin_method!(
+    "String.captures",
+    (pattern: String) -> Any,
+    const STRING_CAPTURES,
+    builtin_string_captures
+);
+fn builtin_string_captures(eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let string = call.receiver.expect_string();
+    let regex = compile_regex_arg(eval, &call.call.args[0])?;
+    match regex.captures_at(string, 0) {
+        Some(captures) => Ok(regex_captures_value(string, captures)),
+        None => Ok(Value::Null),
+    }
+}
+
+builtin_method!(
+    "String.replace_regex",
+    (pattern: String, replacement: String) -> String,
+    const STRING_REPLACE_REGEX,
+    builtin_string_replace_regex
+);
+fn builtin_string_replace_regex(eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let string = call.receiver.expect_string();
+    let regex = compile_regex_arg(eval, &call.call.args[0])?;
+    let replacement_arg = &call.call.args[1];
+    let replacement = match &replacement_arg.value {
+        Value::String(s) => s.as_ref(),
+        _ => {
+            return replacement_arg
+                .span
+                .error("Replacement must be a string.")
+                .err()
+        }
+    };
+    let parts = parse_replacement(replacement, regex.n_groups())
+        .map_err(|err| regex_arg_error(eval, replacement_arg, err))?;
+
+    let mut result = String::with_capacity(string.len());
+    let mut last_end = 0;
+    for captures in regex.captures_all(string) {
+        let range = captures[0].clone().expect("Group 0 always participates.");
+        result.push_str(&string[last_end..range.start]);
+        for part in parts.iter() {
+            match part {
+                Replacement::Literal(s) => result.push_str(s),
+                Replacement::Group(i) => {
+                    if let Some(group) = &captures[*i] {
+                        result.push_str(&string[group.clone()]);
+                    }
+                }
+            }
+        }
+        last_end = range.end;
+    }
+    result.push_str(&string[last_end..]);
+    Ok(Value::String(result.into()))
+}
+
 builtin_method!(
     "String.remove_prefix",
     (prefix: String) -> String,
@@ -1413,6 +3011,393 @@ fn builtin_string_to_uppercase(_eval: &mut Evaluator, call: MethodCall) -> Resul
     Ok(Value::String(string.to_uppercase().into()))
 }
 
+builtin_method!("String.bytes", () -> [Number], const STRING_BYTES, builtin_string_bytes);
+fn builtin_string_bytes(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let string = call.receiver.expect_string();
+    let bytes = string.bytes().map(|b| Value::int(b as i64)).collect();
+    Ok(Value::List(Rc::new(bytes)))
+}
+
+/// Resolve a possibly negative index of a position in a sequence.
+///
+/// The index refers to a position between elements, so for a sequence of
+/// length `n`, indices `0` through `n` inclusive are valid, and negative
+/// indices count from the end.
+fn resolve_bound(
+    len: usize,
+    arg: &CallArg<Value>,
+    arg_name: &'static str,
+    type_name: &'static str,
+) -> Result<usize> {
+    let i_signed = expect_arg_i64(arg, arg_name)?;
+    match i_signed {
+        _ if i_signed >= 0 && (i_signed as usize) <= len => Ok(i_signed as usize),
+        _ if i_signed >= -(len as i64) && i_signed < 0 => Ok(len - (-i_signed as usize)),
+        _ => {
+            let error = concat! {
+                "Index "
+                i_signed.to_string()
+                " is out of bounds for "
+                type_name
+                " of length "
+                len.to_string()
+                "."
+            };
+            arg.span.error(error).err()
+        }
+    }
+}
+
+/// Resolve a possibly negative code point index into a byte offset.
+///
+/// See also [`resolve_bound`].
+fn resolve_string_index(
+    string: &str,
+    arg: &CallArg<Value>,
+    arg_name: &'static str,
+) -> Result<usize> {
+    let i = resolve_bound(string.chars().count(), arg, arg_name, "string")?;
+    Ok(string
+        .char_indices()
+        .nth(i)
+        .map(|(offset, _)| offset)
+        .unwrap_or(string.len()))
+}
+
+/// Report an error for a slice whose end lies before its start.
+fn slice_reversed_error(call: &MethodCall) -> Result<Value> {
+    let args_span = call.call.args[0].span.union(call.call.args[1].span);
+    args_span
+        .error("The end of the slice lies before its start.")
+        .err()
+}
+
+builtin_method!(
+    "String.slice",
+    (start: Number, end: Number) -> String,
+    const STRING_SLICE,
+    builtin_string_slice
+);
+fn builtin_string_slice(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let string = call.receiver.expect_string();
+    let start = resolve_string_index(string, &call.call.args[0], "start index")?;
+    let end = resolve_string_index(string, &call.call.args[1], "end index")?;
+    if end < start {
+        return slice_reversed_error(&call);
+    }
+    Ok(Value::from(&string[start..end]))
+}
+
+builtin_method!(
+    "String.split_at",
+    (index: Number) -> [String],
+    const STRING_SPLIT_AT,
+    builtin_string_split_at
+);
+fn builtin_string_split_at(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let string = call.receiver.expect_string();
+    let i = resolve_string_index(string, &call.call.args[0], "index")?;
+    let (before, after) = string.split_at(i);
+    Ok(Value::List(Rc::new(vec![before.into(), after.into()])))
+}
+
+/// Shared implementation for `String.{index_of,last_index_of}`.
+fn builtin_string_index_of_impl(
+    call: MethodCall,
+    find: fn(&str, &str) -> Option<usize>,
+) -> Result<Value> {
+    let string = call.receiver.expect_string();
+    let needle_arg = &call.call.args[0];
+    let needle = match &needle_arg.value {
+        Value::String(s) => s.as_ref(),
+        _ => return needle_arg.span.error("Needle must be a string.").err(),
+    };
+    match find(string, needle) {
+        Some(offset) => Ok(Value::int(string[..offset].chars().count() as i64)),
+        None => Ok(Value::Null),
+    }
+}
+
+builtin_method!(
+    "String.index_of",
+    (needle: String) -> Any,
+    const STRING_INDEX_OF,
+    builtin_string_index_of
+);
+fn builtin_string_index_of(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    builtin_string_index_of_impl(call, |s, needle| s.find(needle))
+}
+
+builtin_method!(
+    "String.last_index_of",
+    (needle: String) -> Any,
+    const STRING_LAST_INDEX_OF,
+    builtin_string_last_index_of
+);
+fn builtin_string_last_index_of(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    builtin_string_index_of_impl(call, |s, needle| s.rfind(needle))
+}
+
+builtin_method!("String.trim", () -> String, const STRING_TRIM, builtin_string_trim);
+fn builtin_string_trim(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let string = call.receiver.expect_string();
+    Ok(Value::from(string.trim()))
+}
+
+builtin_method!(
+    "String.trim_start",
+    () -> String,
+    const STRING_TRIM_START,
+    builtin_string_trim_start
+);
+fn builtin_string_trim_start(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let string = call.receiver.expect_string();
+    Ok(Value::from(string.trim_start()))
+}
+
+builtin_method!(
+    "String.trim_end",
+    () -> String,
+    const STRING_TRIM_END,
+    builtin_string_trim_end
+);
+fn builtin_string_trim_end(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let string = call.receiver.expect_string();
+    Ok(Value::from(string.trim_end()))
+}
+
+/// The maximum length in bytes of strings built by `repeat` and padding.
+///
+/// Like for `std.range`, it's easy to run out of memory with a single call,
+/// so we put an upper limit on the size, and a lower one when fuzzing.
+#[cfg(fuzzing)]
+const MAX_BUILT_STRING_LEN: usize = 500;
+#[cfg(not(fuzzing))]
+const MAX_BUILT_STRING_LEN: usize = 1_000_000;
+
+fn max_built_string_len_error(at: Span) -> Result<Value> {
+    at.error(concat! {
+        "The result would exceed the maximum length of "
+        Doc::string(MAX_BUILT_STRING_LEN.to_string()).with_markup(Markup::Number)
+        " bytes."
+    })
+    .err()
+}
+
+builtin_method!(
+    "String.repeat",
+    (count: Number) -> String,
+    const STRING_REPEAT,
+    builtin_string_repeat
+);
+fn builtin_string_repeat(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let string = call.receiver.expect_string();
+    let arg = &call.call.args[0];
+    let count = expect_arg_i64(arg, "count")?;
+    if count < 0 {
+        return arg.span.error("Count must be at least 0.").err();
+    }
+    match (count as u64).checked_mul(string.len() as u64) {
+        Some(n) if n <= MAX_BUILT_STRING_LEN as u64 => {}
+        _ => return max_built_string_len_error(arg.span),
+    }
+    Ok(Value::String(string.repeat(count as usize).into()))
+}
+
+/// Which side to pad in [`builtin_string_pad_impl`].
+enum Pad {
+    Start,
+    End,
+}
+
+/// Shared implementation for `String.{pad_start,pad_end}`.
+fn builtin_string_pad_impl(call: MethodCall, pad: Pad) -> Result<Value> {
+    use unicode_width::UnicodeWidthStr;
+
+    let string = call.receiver.expect_string();
+    let width_arg = &call.call.args[0];
+    let width = expect_arg_i64(width_arg, "width")?;
+    if width < 0 {
+        return width_arg.span.error("Width must be at least 0.").err();
+    }
+
+    let fill_arg = &call.call.args[1];
+    let fill = match &fill_arg.value {
+        Value::String(s) if s.chars().count() == 1 && s.width() == 1 => s.as_ref(),
+        _ => {
+            return fill_arg
+                .span
+                .error(concat! {
+                    "Expected the fill to be a single character of width 1, but got "
+                    format_rcl(&fill_arg.value).into_owned()
+                    "."
+                })
+                .err();
+        }
+    };
+
+    // Width is the display width, so East Asian wide characters count for
+    // two columns, and combining characters count for none.
+    let n_fill = (width as u64).saturating_sub(string.width() as u64);
+    if string.len() as u64 + n_fill * fill.len() as u64 > MAX_BUILT_STRING_LEN as u64 {
+        return max_built_string_len_error(width_arg.span);
+    }
+
+    let padding = fill.repeat(n_fill as usize);
+    let result = match pad {
+        Pad::Start => padding + string,
+        Pad::End => string.to_string() + &padding,
+    };
+    Ok(Value::String(result.into()))
+}
+
+builtin_method!(
+    "String.pad_start",
+    (width: Number, fill: String) -> String,
+    const STRING_PAD_START,
+    builtin_string_pad_start
+);
+fn builtin_string_pad_start(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    builtin_string_pad_impl(call, Pad::Start)
+}
+
+builtin_method!(
+    "String.pad_end",
+    (width: Number, fill: String) -> String,
+    const STRING_PAD_END,
+    builtin_string_pad_end
+);
+fn builtin_string_pad_end(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    builtin_string_pad_impl(call, Pad::End)
+}
+
+/// Split an identifier-like string into words, for case conversion.
+///
+/// Words are separated by characters that are not alphanumeric, and by a
+/// change from lowercase to uppercase, as in `camelCase`. In a run of
+/// uppercase letters, the last one starts a new word if a lowercase letter
+/// follows, so `HTTPServer` splits into `HTTP` and `Server`.
+fn split_words(string: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    let mut word_start = None;
+    let mut chars = string.char_indices().peekable();
+    let mut prev: Option<char> = None;
+    while let Some((i, ch)) = chars.next() {
+        if !ch.is_alphanumeric() {
+            if let Some(start) = word_start.take() {
+                words.push(&string[start..i]);
+            }
+            prev = None;
+            continue;
+        }
+        let next = chars.peek().map(|(_, c)| *c);
+        let is_boundary = match prev {
+            Some(p) if ch.is_uppercase() => {
+                p.is_lowercase()
+                    || p.is_numeric()
+                    || (p.is_uppercase() && next.map(|n| n.is_lowercase()).unwrap_or(false))
+            }
+            _ => false,
+        };
+        match word_start {
+            Some(start) if is_boundary => {
+                words.push(&string[start..i]);
+                word_start = Some(i);
+            }
+            Some(..) => {}
+            None => word_start = Some(i),
+        }
+        prev = Some(ch);
+    }
+    if let Some(start) = word_start {
+        words.push(&string[start..]);
+    }
+    words
+}
+
+/// Uppercase the first character of a word, and lowercase the rest.
+fn capitalize(word: &str, into: &mut String) {
+    let mut chars = word.chars();
+    if let Some(first) = chars.next() {
+        into.extend(first.to_uppercase());
+        into.push_str(&chars.as_str().to_lowercase());
+    }
+}
+
+/// Which case to convert to in [`builtin_string_case_impl`].
+enum Case {
+    Camel,
+    Kebab,
+    Snake,
+    Title,
+}
+
+/// Shared implementation for `String.to_{camel,kebab,snake,title}_case`.
+fn builtin_string_case_impl(call: MethodCall, case: Case) -> Result<Value> {
+    let string = call.receiver.expect_string();
+    let mut result = String::with_capacity(string.len());
+    for (i, word) in split_words(string).into_iter().enumerate() {
+        match case {
+            Case::Camel if i == 0 => result.push_str(&word.to_lowercase()),
+            Case::Camel => capitalize(word, &mut result),
+            Case::Kebab | Case::Snake | Case::Title => {
+                if i > 0 {
+                    result.push(match case {
+                        Case::Kebab => '-',
+                        Case::Snake => '_',
+                        _ => ' ',
+                    });
+                }
+                match case {
+                    Case::Title => capitalize(word, &mut result),
+                    _ => result.push_str(&word.to_lowercase()),
+                }
+            }
+        }
+    }
+    Ok(Value::String(result.into()))
+}
+
+builtin_method!(
+    "String.to_camel_case",
+    () -> String,
+    const STRING_TO_CAMEL_CASE,
+    builtin_string_to_camel_case
+);
+fn builtin_string_to_camel_case(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    builtin_string_case_impl(call, Case::Camel)
+}
+
+builtin_method!(
+    "String.to_kebab_case",
+    () -> String,
+    const STRING_TO_KEBAB_CASE,
+    builtin_string_to_kebab_case
+);
+fn builtin_string_to_kebab_case(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    builtin_string_case_impl(call, Case::Kebab)
+}
+
+builtin_method!(
+    "String.to_snake_case",
+    () -> String,
+    const STRING_TO_SNAKE_CASE,
+    builtin_string_to_snake_case
+);
+fn builtin_string_to_snake_case(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    builtin_string_case_impl(call, Case::Snake)
+}
+
+builtin_method!(
+    "String.to_title_case",
+    () -> String,
+    const STRING_TO_TITLE_CASE,
+    builtin_string_to_title_case
+);
+fn builtin_string_to_title_case(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    builtin_string_case_impl(call, Case::Title)
+}
+
 builtin_method!(
     "List.fold",
     (
@@ -1435,34 +3420,13 @@ fn builtin_list_fold(eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
     let mut acc = seed.value.clone();
 
     for element in list.iter() {
-        // The call that we construct here is internal, there is no span in the
-        // source code that we could point at. To have something to pin errors
-        // to, we'll take the entire span of the 'reduce' argument.
-        let args = [
-            CallArg {
-                span: reduce.span,
-                value: acc,
-            },
-            CallArg {
-                span: reduce.span,
-                value: element.clone(),
-            },
-        ];
-        let call = FunctionCall {
-            call_open: reduce.span,
-            call_close: reduce.span,
-            args: &args,
-        };
-        acc = eval.eval_call(reduce.span, &reduce.value, call).map_err(|mut err| {
-            // If the call includes a call frame for this call, then replace
-            // it with a more descriptive message, since the span is a bit
-            // misleading.
-            err.replace_call_frame(
-                reduce.span,
-                concat! { "In internal call to reduce function from '" Doc::highlight("List.fold") "'." }
-            );
-            err
-        })?;
+        acc = builtin_call_internal(
+            eval,
+            reduce,
+            "reduce function",
+            "List.fold",
+            [acc, element.clone()],
+        )?;
 
         // Avoid hangs from very deeply nested values.
         check_value_depth(method_span, "Accumulator", &acc)?;
@@ -1508,6 +3472,362 @@ fn builtin_list_reverse(_eval: &mut Evaluator, call: MethodCall) -> Result<Value
     Ok(Value::List(Rc::new(reversed)))
 }
 
+builtin_method!(
+    "List.slice",
+    (start: Number, end: Number) -> [Any],
+    const LIST_SLICE,
+    builtin_list_slice
+);
+fn builtin_list_slice(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let list = call.receiver.expect_list();
+    let start = resolve_bound(list.len(), &call.call.args[0], "start index", "list")?;
+    let end = resolve_bound(list.len(), &call.call.args[1], "end index", "list")?;
+    if end < start {
+        return slice_reversed_error(&call);
+    }
+    Ok(Value::List(Rc::new(list[start..end].to_vec())))
+}
+
+builtin_method!("List.take", (count: Number) -> [Any], const LIST_TAKE, builtin_list_take);
+fn builtin_list_take(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let list = call.receiver.expect_list();
+    let n = expect_arg_count(&call.call.args[0], "count")?;
+    Ok(Value::List(Rc::new(list.iter().take(n).cloned().collect())))
+}
+
+builtin_method!("List.skip", (count: Number) -> [Any], const LIST_SKIP, builtin_list_skip);
+fn builtin_list_skip(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let list = call.receiver.expect_list();
+    let n = expect_arg_count(&call.call.args[0], "count")?;
+    Ok(Value::List(Rc::new(list.iter().skip(n).cloned().collect())))
+}
+
+builtin_method!(
+    "List.take_while",
+    (predicate: (fn (element: Any) -> Bool)) -> [Any],
+    const LIST_TAKE_WHILE,
+    builtin_list_take_while
+);
+fn builtin_list_take_while(eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let list = call.receiver.expect_list();
+    let mut result = Vec::new();
+    for x in list.iter() {
+        if !builtin_call_predicate(eval, &call.call.args[0], "List.take_while", x)? {
+            break;
+        }
+        result.push(x.clone());
+    }
+    Ok(Value::List(Rc::new(result)))
+}
+
+/// Shared implementation for `List.{first,last}`.
+fn builtin_list_first_last_impl(call: MethodCall, is_first: bool) -> Result<Value> {
+    let list = call.receiver.expect_list();
+    let element = match is_first {
+        true => list.first(),
+        false => list.last(),
+    };
+    match element {
+        Some(x) => Ok(x.clone()),
+        None => call
+            .method_span
+            .error(match is_first {
+                true => "Cannot take the first element of an empty list.",
+                false => "Cannot take the last element of an empty list.",
+            })
+            .err(),
+    }
+}
+
+builtin_method!("List.first", () -> Any, const LIST_FIRST, builtin_list_first);
+fn builtin_list_first(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    builtin_list_first_last_impl(call, true)
+}
+
+builtin_method!("List.last", () -> Any, const LIST_LAST, builtin_list_last);
+fn builtin_list_last(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    builtin_list_first_last_impl(call, false)
+}
+
+builtin_method!(
+    "List.zip",
+    (other: [Any]) -> [[Any]],
+    const LIST_ZIP,
+    builtin_list_zip
+);
+fn builtin_list_zip(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let list = call.receiver.expect_list();
+    let other_arg = &call.call.args[0];
+    let other = match &other_arg.value {
+        Value::List(ys) => ys,
+        _ => return other_arg.span.error("Expected a list to zip with.").err(),
+    };
+    if list.len() != other.len() {
+        return other_arg
+            .span
+            .error(concat! {
+                "Cannot zip lists of different lengths, "
+                list.len().to_string()
+                " and "
+                other.len().to_string()
+                "."
+            })
+            .with_help("Use 'take' to make the lists equally long.")
+            .err();
+    }
+    let pairs = list
+        .iter()
+        .zip(other.iter())
+        .map(|(x, y)| Value::List(Rc::new(vec![x.clone(), y.clone()])))
+        .collect();
+    Ok(Value::List(Rc::new(pairs)))
+}
+
+builtin_method!(
+    "List.chunks",
+    (size: Number) -> [[Any]],
+    const LIST_CHUNKS,
+    builtin_list_chunks
+);
+fn builtin_list_chunks(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let list = call.receiver.expect_list();
+    let n = expect_arg_size(&call.call.args[0], "chunk size")?;
+    let chunks = list
+        .chunks(n)
+        .map(|chunk| Value::List(Rc::new(chunk.to_vec())))
+        .collect();
+    Ok(Value::List(Rc::new(chunks)))
+}
+
+builtin_method!(
+    "List.windows",
+    (size: Number) -> [[Any]],
+    const LIST_WINDOWS,
+    builtin_list_windows
+);
+fn builtin_list_windows(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let list = call.receiver.expect_list();
+    let n = expect_arg_size(&call.call.args[0], "window size")?;
+    let windows = list
+        .windows(n)
+        .map(|window| Value::List(Rc::new(window.to_vec())))
+        .collect();
+    Ok(Value::List(Rc::new(windows)))
+}
+
+builtin_method!(
+    "List.flatten",
+    () -> [Any],
+    const LIST_FLATTEN,
+    builtin_list_flatten
+);
+fn builtin_list_flatten(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let list = call.receiver.expect_list();
+    let mut result = Vec::new();
+    for x in list.iter() {
+        match x {
+            Value::List(ys) => result.extend(ys.iter().cloned()),
+            not_list => {
+                return call
+                    .receiver_span
+                    .error(concat! {
+                        "Expected a list of lists, but found "
+                        format_rcl(not_list).into_owned()
+                        "."
+                    })
+                    .err();
+            }
+        }
+    }
+    Ok(Value::List(Rc::new(result)))
+}
+
+builtin_method!(
+    "List.find",
+    (predicate: (fn (element: Any) -> Bool)) -> Any,
+    const LIST_FIND,
+    builtin_list_find
+);
+fn builtin_list_find(eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let list = call.receiver.expect_list();
+    for x in list.iter() {
+        if builtin_call_predicate(eval, &call.call.args[0], "List.find", x)? {
+            return Ok(x.clone());
+        }
+    }
+    Ok(Value::Null)
+}
+
+builtin_method!(
+    "List.find_index",
+    (predicate: (fn (element: Any) -> Bool)) -> Any,
+    const LIST_FIND_INDEX,
+    builtin_list_find_index
+);
+fn builtin_list_find_index(eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let list = call.receiver.expect_list();
+    for (i, x) in list.iter().enumerate() {
+        if builtin_call_predicate(eval, &call.call.args[0], "List.find_index", x)? {
+            return Ok(Value::int(i as i64));
+        }
+    }
+    Ok(Value::Null)
+}
+
+builtin_method!(
+    "List.index_of",
+    (element: Any) -> Any,
+    const LIST_INDEX_OF,
+    builtin_list_index_of
+);
+fn builtin_list_index_of(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let list = call.receiver.expect_list();
+    let needle = &call.call.args[0].value;
+    match list.iter().position(|x| x == needle) {
+        Some(i) => Ok(Value::int(i as i64)),
+        None => Ok(Value::Null),
+    }
+}
+
+builtin_method!(
+    "List.count",
+    (predicate: (fn (element: Any) -> Bool)) -> Number,
+    const LIST_COUNT,
+    builtin_list_count
+);
+fn builtin_list_count(eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let list = call.receiver.expect_list();
+    let mut n: i64 = 0;
+    builtin_filter_impl(eval, call, "List.count", list.iter(), |_| n += 1)?;
+    Ok(Value::int(n))
+}
+
+builtin_method!(
+    "List.partition",
+    (predicate: (fn (element: Any) -> Bool)) -> [[Any]],
+    const LIST_PARTITION,
+    builtin_list_partition
+);
+fn builtin_list_partition(eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let list = call.receiver.expect_list();
+    let predicate_span = call.call.args[0].span;
+    let mut matching = Vec::new();
+    let mut rest = Vec::new();
+    builtin_generic_map_impl(
+        eval,
+        call,
+        "predicate",
+        "List.partition",
+        list.iter(),
+        |orig, result| {
+            match expect_predicate_result(predicate_span, result)? {
+                true => matching.push(orig.clone()),
+                false => rest.push(orig.clone()),
+            }
+            Ok(())
+        },
+    )?;
+    Ok(Value::List(Rc::new(vec![
+        Value::List(Rc::new(matching)),
+        Value::List(Rc::new(rest)),
+    ])))
+}
+
+builtin_method!("List.dedup", () -> [Any], const LIST_DEDUP, builtin_list_dedup);
+fn builtin_list_dedup(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let list = call.receiver.expect_list();
+    let mut seen = BTreeSet::new();
+    let result = list.iter().filter(|x| seen.insert(*x)).cloned().collect();
+    Ok(Value::List(Rc::new(result)))
+}
+
+builtin_method!(
+    "List.unique_by",
+    // TODO: Add type variables so we can describe this more accurately.
+    (get_key: (fn (element: Any) -> Any)) -> [Any],
+    const LIST_UNIQUE_BY,
+    builtin_list_unique_by
+);
+fn builtin_list_unique_by(eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    let list = call.receiver.expect_list();
+    let mut seen = BTreeSet::new();
+    let mut result = Vec::new();
+    builtin_loop_get_key_impl(eval, call, "List.unique_by", list.iter(), |key, x| {
+        if seen.insert(key) {
+            result.push(x.clone());
+        }
+    })?;
+    Ok(Value::List(Rc::new(result)))
+}
+
+/// Shared implementation for `List.{min_by,max_by}`.
+///
+/// Keeps the element whose key compares as `keep` against the best key so far,
+/// so when multiple elements have the extreme key, this returns the first one.
+fn builtin_list_extreme_by_impl(
+    eval: &mut Evaluator,
+    call: MethodCall,
+    name: &'static str,
+    keep: Ordering,
+) -> Result<Value> {
+    let list = call.receiver.expect_list();
+    let get_key_arg = &call.call.args[0];
+    let mut best: Option<(Value, &Value)> = None;
+    for x in list.iter() {
+        let key = builtin_call_internal(eval, get_key_arg, "key selector", name, [x.clone()])?;
+        match &best {
+            // Values of different types do have an order, but it is not a
+            // meaningful one to pick an extreme by, so reject mixed keys.
+            Some((best_key, _))
+                if std::mem::discriminant(&key) != std::mem::discriminant(best_key) =>
+            {
+                let err = concat! {
+                    "Expected keys of the same type, but found "
+                    format_rcl(best_key).into_owned()
+                    " and "
+                    format_rcl(&key).into_owned()
+                    "."
+                };
+                return get_key_arg.span.error(err).err();
+            }
+            Some((best_key, _)) if key.cmp(best_key) != keep => {}
+            _ => best = Some((key, x)),
+        }
+    }
+    match best {
+        Some((_key, x)) => Ok(x.clone()),
+        None => call
+            .method_span
+            .error(match keep {
+                Ordering::Greater => "Cannot take the maximum of an empty collection.",
+                _ => "Cannot take the minimum of an empty collection.",
+            })
+            .err(),
+    }
+}
+
+builtin_method!(
+    "List.max_by",
+    // TODO: Add type variables so we can describe this more accurately.
+    (get_key: (fn (element: Any) -> Any)) -> Any,
+    const LIST_MAX_BY,
+    builtin_list_max_by
+);
+fn builtin_list_max_by(eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    builtin_list_extreme_by_impl(eval, call, "List.max_by", Ordering::Greater)
+}
+
+builtin_method!(
+    "List.min_by",
+    // TODO: Add type variables so we can describe this more accurately.
+    (get_key: (fn (element: Any) -> Any)) -> Any,
+    const LIST_MIN_BY,
+    builtin_list_min_by
+);
+fn builtin_list_min_by(eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
+    builtin_list_extreme_by_impl(eval, call, "List.min_by", Ordering::Less)
+}
+
 builtin_method!(
     "List.enumerate",
     () -> {Number: Any},
@@ -1516,7 +3836,7 @@ builtin_method!(
 );
 fn builtin_list_enumerate(_eval: &mut Evaluator, call: MethodCall) -> Result<Value> {
     let list = call.receiver.expect_list();
-    let kv: BTreeMap<_, _> = list
+    let kv: OrderedMap<_, _> = list
         .iter()
         .zip(0..)
         .map(|(v, i)| (Value::int(i), v.clone()))
@@ -7,6 +7,8 @@
 
 //! Utilities for working with strings.
 
+use std::ops::Range;
+
 use crate::cst::StringPart;
 use crate::error::{IntoError, Result};
 use crate::lexer::Escape;
@@ -179,6 +181,65 @@ pub fn count_common_leading_spaces(input: &str, parts: &[StringPart]) -> usize {
     n_spaces.unwrap_or(0)
 }
 
+/// Locate a byte range of a string value inside the literal that produced it.
+///
+/// The `literal` is the source code of the expression at `span`, and `value`
+/// the string it evaluated to. This works for `"`-quoted literals without
+/// interpolation, including escape sequences. For other expressions, we can't
+/// map offsets in the value back to the source, and we return `None`.
+pub fn locate_in_literal(
+    literal: &str,
+    span: Span,
+    value: &str,
+    range: Range<usize>,
+) -> Option<Span> {
+    if literal.len() < 2
+        || !literal.starts_with('"')
+        || !literal.ends_with('"')
+        || literal.starts_with("\"\"\"")
+    {
+        return None;
+    }
+    let body = &literal[1..literal.len() - 1];
+
+    // Walk the literal and the value in lockstep to find the source offsets of
+    // the range bounds.
+    let mut start = None;
+    let mut end = None;
+    let (mut i, mut j) = (0, 0);
+    loop {
+        if j == range.start {
+            start = Some(i);
+        }
+        if j == range.end {
+            end = Some(i);
+        }
+        let ch = match value[j..].chars().next() {
+            Some(ch) => ch,
+            None => break,
+        };
+        let bytes = &body.as_bytes()[i..];
+        let n_source = match bytes {
+            [b'\\', b'u', b'{', ..] => bytes.iter().position(|b| *b == b'}')? + 1,
+            [b'\\', b'u', ..] => 6,
+            [b'\\', ..] => 2,
+            _ if body[i..].starts_with(ch) => ch.len_utf8(),
+            _ => return None,
+        };
+        i += n_source;
+        j += ch.len_utf8();
+        if i > body.len() {
+            return None;
+        }
+    }
+
+    if i != body.len() {
+        return None;
+    }
+    let offset = span.start() + 1;
+    Some(Span::new(span.doc(), offset + start?, offset + end?))
+}
+
 // Note, most testing is done through golden tests and fuzzing, not unit tests.
 #[cfg(test)]
 mod test {
@@ -261,6 +322,32 @@ mod test {
 
     // Note, the main tests for unescaping are the golden tests.
 
+    fn locate_in_literal(literal: &str, range: std::ops::Range<usize>) -> Option<&str> {
+        let span = crate::source::Span::new(DocId(0), 0, literal.len());
+        let value = unescape(literal).unwrap();
+        super::locate_in_literal(literal, span, &value, range).map(|s| s.resolve(literal))
+    }
+
+    #[test]
+    fn locate_in_literal_handles_escapes() {
+        assert_eq!(locate_in_literal(r#""abcd""#, 1..3), Some("bc"));
+        assert_eq!(locate_in_literal(r#""abcd""#, 4..4), Some(""));
+        assert_eq!(locate_in_literal(r#""a\\d+""#, 1..3), Some(r"\\d"));
+        assert_eq!(locate_in_literal(r#""\u{e9}x""#, 2..3), Some("x"));
+        assert_eq!(locate_in_literal(r#""\u00e9x\n""#, 0..3), Some(r"\u00e9x"));
+        assert_eq!(locate_in_literal(r#""é\"x""#, 2..3), Some(r#"\""#));
+        let span = crate::source::Span::new(DocId(0), 0, 6);
+        assert_eq!(
+            super::locate_in_literal(r#"f"abc""#, span, "abc", 1..2),
+            None
+        );
+        let span = crate::source::Span::new(DocId(0), 0, 10);
+        assert_eq!(
+            super::locate_in_literal(r#""""abc""""#, span, "abc", 1..2),
+            None
+        );
+    }
+
     #[test]
     fn escape_json_handles_predefined_escapes() {
         assert_eq!(escape_json("\"\\/\x08\x0c\n\r\t"), r#"\"\\/\b\f\n\r\t"#,)
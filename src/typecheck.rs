@@ -73,7 +73,7 @@ fn report_unknown_type<T>(span: Span, name: &str, error_msg: &'static str) -> Re
 }
 
 /// Parse a type expression.
-fn eval_type_expr(expr: &AType) -> Result<SourcedType> {
+pub fn eval_type_expr(expr: &AType) -> Result<SourcedType> {
     match expr {
         AType::Term { span, name } => {
             if let Some(prim) = get_primitive_type(name.as_ref()) {
@@ -87,6 +87,29 @@ def hash_keyword(slot: int, keyword: str) -> bytes:
     return h.digest()
 
 
+def fill_slots(keywords: Iterable[str]) -> List[str]:
+    """
+    Fill 256 slots with keywords using rendezvous hashing, such that every
+    keyword occupies at least one slot.
+    """
+    candidates = sorted(keywords)
+    slots = [min((hash_keyword(i, k), k) for k in candidates)[1] for i in range(256)]
+
+    # Rendezvous hashing does not guarantee that every keyword gets a slot.
+    # Give each keyword that has none the slot where it ranks highest, among
+    # the slots whose keyword also occupies another slot. A keyword only loses
+    # slots when it has more than one, so this never undoes an earlier move.
+    for k in candidates:
+        if k in slots:
+            continue
+        _, i = min(
+            (hash_keyword(i, k), i) for i in range(256) if slots.count(slots[i]) > 1
+        )
+        slots[i] = k
+
+    return slots
+
+
 def generate_smith_builtins() -> None:
     """
     Generate a Rust file with the builtins, for use by the smith fuzzer.
@@ -99,25 +122,18 @@ def generate_smith_builtins() -> None:
     ideal when switching branches. So instead, we use rendezvous hashing
     to generate a slice of 256 elements, such that the changes to the
     slice are minimal when the builtins change.
+
+    With more than a few dozen builtins, rendezvous hashing alone is unlikely
+    to give every builtin a slot, so `fill_slots` then moves the builtins that
+    did not get a slot into slots held by a builtin that has more than one.
     """
     assert len(TYPES) <= 256, "Smith keyword indices are only 8 bits."
     assert len(BUILTINS) <= 256, "Smith keyword indices are only 8 bits."
 
-    types_list: List[str] = []
-    builtins_list: List[str] = []
-
-    # Fill up the 256 slots in these lists using rendezvous hashing.
-    for i in range(0, 256):
-        _, t = min((hash_keyword(i, t), t) for t in TYPES)
-        _, b = min((hash_keyword(i, b), b) for b in BUILTINS)
-
-        types_list.append(t)
-        builtins_list.append(b)
+    types_list = fill_slots(TYPES)
+    builtins_list = fill_slots(BUILTINS)
 
-    # There is no theoretical guarantee every type/builtin got placed into
-    # one of the slots, so confirm that here. We have plenty of slots so
-    # it's unlikely to fail, but if it does fail, we should change the salt
-    # for the hash function.
+    # Confirm that every type/builtin got placed into one of the slots.
     for t in TYPES:
         assert t in types_list, "Generated slice must contain all types."
     for b in BUILTINS: